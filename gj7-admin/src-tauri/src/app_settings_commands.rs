@@ -0,0 +1,137 @@
+// src/app_settings_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::audit_log::{payload_summary, AuditAction};
+use crate::db::idle_lock::IdleLockSettings;
+use crate::db::library_timezone::LibraryTimezone;
+use crate::db::network_settings::NetworkServerSettings;
+use crate::network_server::{load_server_certificate, NetworkServerControl};
+use crate::session::SessionState;
+use rusqlite::Result;
+
+// The library's UTC offset, e.g. "+08:00"
+#[tauri::command]
+pub async fn get_library_timezone(
+    state: State<'_, DbState>
+) -> Result<String, String> {
+    let db = state.0.clone();
+    
+    db.with_connection(move |conn| {
+        LibraryTimezone::load(conn).map(|timezone| timezone.utc_offset())
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_library_timezone(
+    state: State<'_, DbState>,
+    utc_offset: String,
+    username: String,
+    password: String
+) -> Result<String, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let utc_offset = LibraryTimezone::save(conn, &utc_offset)?.utc_offset();
+            audit_log.record(conn, &username, AuditAction::SettingsChanged, Some("Library timezone"), Some(&utc_offset))?;
+            Ok(utc_offset)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn get_idle_lock_settings(
+    state: State<'_, DbState>
+) -> Result<IdleLockSettings, String> {
+    let db = state.0.clone();
+    
+    db.with_connection(move |conn| {
+        IdleLockSettings::load(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_idle_lock_settings(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    settings: IdleLockSettings,
+    username: String,
+    password: String
+) -> Result<IdleLockSettings, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    let saved = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let settings = IdleLockSettings::save(conn, settings)?;
+            audit_log.record(conn, &username, AuditAction::SettingsChanged, Some("Idle lock"), payload_summary(&settings).as_deref())?;
+            Ok(settings)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.set_idle_timeout(saved.timeout());
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn get_network_server_settings(
+    state: State<'_, DbState>
+) -> Result<NetworkServerSettings, String> {
+    let db = state.0.clone();
+    
+    db.with_connection(move |conn| {
+        NetworkServerSettings::load(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+// Saving restarts the network server on the new address; connected kiosks reconnect
+#[tauri::command]
+pub async fn set_network_server_settings(
+    state: State<'_, DbState>,
+    control: State<'_, NetworkServerControl>,
+    settings: NetworkServerSettings,
+    username: String,
+    password: String
+) -> Result<NetworkServerSettings, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    let saved = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let settings = NetworkServerSettings::save(conn, settings)?;
+            audit_log.record(conn, &username, AuditAction::SettingsChanged, Some("Network server"), payload_summary(&settings).as_deref())?;
+            Ok(settings)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    control.restart();
+    Ok(saved)
+}
+
+// Shown so operators can check the key kiosks pin when they pair
+#[tauri::command]
+pub async fn get_server_certificate_fingerprint(
+    state: State<'_, DbState>
+) -> Result<String, String> {
+    let db = state.0.clone();
+
+    let settings = db.with_connection(move |conn| {
+        NetworkServerSettings::load(conn)
+    }).await.map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        load_server_certificate(settings.socket_addr().ip())?
+            .fingerprint()
+            .map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
+}
@@ -0,0 +1,47 @@
+// src/attendance_analytics_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::attendance_analytics::{AnalyticsDimension, AttendanceAnalytics, ChartSeries};
+use rusqlite::Result;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn get_attendance_analytics(
+    state: State<'_, DbState>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    semester_id: Option<Uuid>
+) -> Result<AttendanceAnalytics, String> {
+    if from >= to {
+        return Err("The start of the range must be before its end".to_string());
+    }
+
+    let db = state.0.clone();
+    let analytics_repo = Arc::clone(&db.attendance_analytics);
+    
+    db.with_connection(move |conn| {
+        analytics_repo.get_analytics(conn, from, to, semester_id)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_attendance_series(
+    state: State<'_, DbState>,
+    dimension: AnalyticsDimension,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    semester_id: Option<Uuid>
+) -> Result<ChartSeries, String> {
+    if from >= to {
+        return Err("The start of the range must be before its end".to_string());
+    }
+
+    let db = state.0.clone();
+    let analytics_repo = Arc::clone(&db.attendance_analytics);
+    
+    db.with_connection(move |conn| {
+        analytics_repo.get_series(conn, dimension, from, to, semester_id)
+    }).await.map_err(|e| e.to_string())
+}
\ No newline at end of file
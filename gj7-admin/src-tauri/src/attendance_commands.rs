@@ -2,21 +2,43 @@
 use tauri::State;
 use uuid::Uuid;
 use crate::DbState;
-use crate::db::attendance::{Attendance, CreateAttendanceRequest, UpdateAttendanceRequest};
+use crate::db::attendance::{
+    Attendance,
+    AttendanceFilter,
+    AttendanceSort,
+    CreateAttendanceRequest,
+    ManualAttendanceRequest,
+    PaginatedAttendances,
+    UpdateAttendanceRequest
+};
+use crate::db::attendance_bulk::{apply_bulk_action, preview_bulk_action, BulkAttendanceAction, BulkPreview};
+use crate::db::attendance_history::AttendanceChange;
+use crate::db::audit_log::{payload_summary, AuditAction};
+use crate::db::scan_cooldown::ScanCooldown;
 use rusqlite::Result;
 use std::sync::Arc;
-use chrono::{DateTime, Utc};
 
 #[tauri::command]
 pub async fn get_filtered_attendances(
     state: State<'_, DbState>,
-    course: Option<String>,
-    date: Option<DateTime<Utc>>
-) -> Result<Vec<Attendance>, String> {
+    filter: AttendanceFilter,
+    sort: Option<AttendanceSort>,
+    page: Option<u64>,
+    page_size: Option<u64>
+) -> Result<PaginatedAttendances, String> {
+    let page = page.unwrap_or(1);
+    let page_size = page_size.unwrap_or(50);
+
     let db = state.0.clone();
     let attendance_repo = Arc::clone(&db.attendance_repository);
     db.with_connection(move |conn| {
-        attendance_repo.get_filtered_attendances(conn, course, date)
+        attendance_repo.get_filtered_attendances(
+            conn,
+            &filter,
+            sort.unwrap_or_default(),
+            page,
+            page_size
+        )
     }).await.map_err(|e| e.to_string())
 }
 
@@ -32,6 +54,18 @@ pub async fn get_all_courses(
     }).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_attendance_locations(
+    state: State<'_, DbState>
+) -> Result<Vec<String>, String> {
+    let db = state.0.clone();
+    let attendance_repo = Arc::clone(&db.attendance_repository);
+
+    db.with_connection(move |conn| {
+        attendance_repo.get_all_locations(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn create_attendance(
     state: State<'_, DbState>,
@@ -52,6 +86,55 @@ pub async fn create_attendance(
     }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
 }
 
+#[tauri::command]
+pub async fn create_manual_attendance(
+    state: State<'_, DbState>,
+    attendance: ManualAttendanceRequest,
+    username: String,
+    password: String
+) -> Result<Attendance, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let attendance_repo = Arc::clone(&db.attendance_repository);
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let attendance = attendance_repo.create_manual_attendance(conn, attendance, &username)?;
+            audit_log.record(
+                conn,
+                &username,
+                AuditAction::AttendanceCreated,
+                Some(&attendance.id.to_string()),
+                Some(&format!("{} ({})", attendance.full_name, attendance.school_id))
+            )?;
+            Ok(attendance)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn checkout_attendance(
+    state: State<'_, DbState>,
+    school_id: String,
+    username: String,
+    password: String
+) -> Result<Attendance, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let attendance_repo = Arc::clone(&db.attendance_repository);
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            attendance_repo.checkout_attendance(conn, &school_id, None, None)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Checkout failed: {}", e.to_string()))
+}
+
 #[tauri::command]
 pub async fn get_all_attendances(
     state: State<'_, DbState>
@@ -82,16 +165,20 @@ pub async fn update_attendance(
     state: State<'_, DbState>,
     id: Uuid,
     attendance: UpdateAttendanceRequest,
+    reason: Option<String>,
     username: String,
     password: String
 ) -> Result<Attendance, String> {
     let db = state.0.clone();
     let auth = db.auth.clone();
     let attendance_repo = Arc::clone(&db.attendance_repository);
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            attendance_repo.update_attendance(conn, id, attendance)
+            let attendance = attendance_repo.update_attendance(conn, id, attendance, &username, reason.as_deref())?;
+            audit_log.record(conn, &username, AuditAction::AttendanceUpdated, Some(&id.to_string()), reason.as_deref())?;
+            Ok(attendance)
         } else {
             Err(rusqlite::Error::QueryReturnedNoRows)
         }
@@ -102,22 +189,106 @@ pub async fn update_attendance(
 pub async fn delete_attendance(
     state: State<'_, DbState>,
     id: Uuid,
+    reason: Option<String>,
     username: String,
     password: String
 ) -> Result<(), String> {
     let db = state.0.clone();
     let auth = db.auth.clone();
     let attendance_repo = Arc::clone(&db.attendance_repository);
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            attendance_repo.delete_attendance(conn, id, &username, reason.as_deref())?;
+            audit_log.record(conn, &username, AuditAction::AttendanceDeleted, Some(&id.to_string()), reason.as_deref())
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn restore_attendance(
+    state: State<'_, DbState>,
+    id: Uuid,
+    reason: Option<String>,
+    username: String,
+    password: String
+) -> Result<Attendance, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let attendance_repo = Arc::clone(&db.attendance_repository);
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let attendance = attendance_repo.restore_attendance(conn, id, &username, reason.as_deref())?;
+            audit_log.record(conn, &username, AuditAction::AttendanceRestored, Some(&id.to_string()), reason.as_deref())?;
+            Ok(attendance)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn preview_bulk_attendance_action(
+    state: State<'_, DbState>,
+    filter: AttendanceFilter,
+    action: BulkAttendanceAction
+) -> Result<BulkPreview, String> {
+    let db = state.0.clone();
+    
+    db.with_connection(move |conn| {
+        preview_bulk_action(conn, &filter, &action)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn apply_bulk_attendance_action(
+    state: State<'_, DbState>,
+    filter: AttendanceFilter,
+    action: BulkAttendanceAction,
+    expected_count: u64,
+    reason: Option<String>,
+    username: String,
+    password: String
+) -> Result<u64, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            attendance_repo.delete_attendance(conn, id)
+            let changed = apply_bulk_action(conn, &filter, &action, expected_count, &username, reason.as_deref())?;
+            let summary = format!(
+                "{} records: {}{}",
+                changed,
+                payload_summary(&action).unwrap_or_default(),
+                reason.as_deref().map(|reason| format!(" ({})", reason)).unwrap_or_default()
+            );
+            audit_log.record(conn, &username, AuditAction::AttendanceBulkAction, None, Some(&summary))?;
+            Ok(changed)
         } else {
             Err(rusqlite::Error::QueryReturnedNoRows)
         }
     }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
 }
 
+#[tauri::command]
+pub async fn get_attendance_history(
+    state: State<'_, DbState>,
+    id: Uuid
+) -> Result<Vec<AttendanceChange>, String> {
+    let db = state.0.clone();
+    let history_repo = Arc::clone(&db.attendance_history);
+    
+    db.with_connection(move |conn| {
+        history_repo.get_attendance_history(conn, id)
+    }).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_attendances_by_semester(
     state: State<'_, DbState>,
@@ -142,4 +313,67 @@ pub async fn get_attendances_by_school_account(
     db.with_connection(move |conn| {
         attendance_repo.get_attendances_by_school_account(conn, school_account_id)
     }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_scan_cooldowns(
+    state: State<'_, DbState>
+) -> Result<Vec<ScanCooldown>, String> {
+    let db = state.0.clone();
+    let scan_cooldowns = db.scan_cooldowns.clone();
+    
+    db.with_connection(move |conn| {
+        scan_cooldowns.get_all_scan_cooldowns(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_scan_cooldown(
+    state: State<'_, DbState>,
+    cooldown: ScanCooldown,
+    username: String,
+    password: String
+) -> Result<ScanCooldown, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let scan_cooldowns = db.scan_cooldowns.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let cooldown = scan_cooldowns.set_scan_cooldown(conn, cooldown)?;
+            audit_log.record(conn, &username, AuditAction::SettingsChanged, Some("Scan cooldown"), payload_summary(&cooldown).as_deref())?;
+            Ok(cooldown)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn delete_scan_cooldown(
+    state: State<'_, DbState>,
+    classification: String,
+    username: String,
+    password: String
+) -> Result<(), String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let scan_cooldowns = db.scan_cooldowns.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            scan_cooldowns.delete_scan_cooldown(conn, &classification)?;
+            audit_log.record(
+                conn,
+                &username,
+                AuditAction::SettingsChanged,
+                Some("Scan cooldown"),
+                Some(&format!("Removed cooldown for {}", classification))
+            )
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+// src/attendance_export_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::attendance::{AttendanceFilter, AttendanceSort};
+use crate::db::attendance_export::{for_each_export_row, ExportColumn};
+use rust_xlsxwriter::{Format, Workbook};
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+// Keeps one long purpose or name from making a column unreadably wide
+const MAX_COLUMN_WIDTH: usize = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    pub file_path: String,
+    pub row_count: usize,
+}
+
+#[tauri::command]
+pub async fn export_attendances(
+    state: State<'_, DbState>,
+    filter: AttendanceFilter,
+    sort: Option<AttendanceSort>,
+    columns: Vec<ExportColumn>,
+    format: ExportFormat,
+    file_path: String
+) -> Result<ExportSummary, String> {
+    let columns = if columns.is_empty() { ExportColumn::defaults() } else { columns };
+    let sort = sort.unwrap_or_default();
+
+    let conn = state.0.pool.get()
+        .map_err(|e| format!("Failed to get connection: {}", e))?;
+
+    let result: Result<usize, Box<dyn Error>> = match format {
+        ExportFormat::Csv => {
+            (|| {
+                let mut writer = csv::Writer::from_path(&file_path)?;
+                writer.write_record(columns.iter().map(|column| column.header()))?;
+
+                let count = for_each_export_row(&conn, &filter, sort, |row| {
+                    writer.write_record(columns.iter().map(|column| row.value(*column)))?;
+                    Ok::<(), Box<dyn Error>>(())
+                })?;
+
+                writer.flush()?;
+                Ok(count)
+            })()
+        },
+        ExportFormat::Xlsx => {
+            (|| {
+                // Rows are flushed to a temp file as they are written, so memory
+                // stays flat however many rows the export has
+                let mut workbook = Workbook::new();
+                let worksheet = workbook.add_worksheet_with_constant_memory();
+                worksheet.set_name("Attendance")?;
+
+                // autofit needs every row in memory, so widths are tracked while streaming
+                let mut widths: Vec<usize> = columns.iter().map(|column| column.header().len()).collect();
+
+                let header_format = Format::new().set_bold();
+                for (col, column) in columns.iter().enumerate() {
+                    worksheet.write_string_with_format(0, col as u16, column.header(), &header_format)?;
+                }
+
+                let mut row_index: u32 = 1;
+                let count = for_each_export_row(&conn, &filter, sort, |row| {
+                    for (col, column) in columns.iter().enumerate() {
+                        // Keep durations numeric so spreadsheets can sum them
+                        match (column, row.duration_minutes()) {
+                            (ExportColumn::DurationMinutes, Some(minutes)) => {
+                                worksheet.write_number(row_index, col as u16, minutes as f64)?;
+                            },
+                            _ => {
+                                let value = row.value(*column);
+                                widths[col] = widths[col].max(value.chars().count());
+                                worksheet.write_string(row_index, col as u16, value)?;
+                            },
+                        }
+                    }
+                    row_index += 1;
+                    Ok::<(), Box<dyn Error>>(())
+                })?;
+
+                for (col, width) in widths.into_iter().enumerate() {
+                    worksheet.set_column_width(col as u16, (width + 2).min(MAX_COLUMN_WIDTH) as f64)?;
+                }
+                workbook.save(&file_path)?;
+                Ok(count)
+            })()
+        },
+    };
+
+    let row_count = result.map_err(|e| format!("Export failed: {}", e))?;
+
+    Ok(ExportSummary {
+        file_path,
+        row_count,
+    })
+}
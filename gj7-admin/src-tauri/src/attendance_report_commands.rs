@@ -0,0 +1,37 @@
+// src/attendance_report_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::attendance_report::{build_attendance_report, ReportPeriod};
+use crate::report_pdf::write_report_pdf;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ReportSummary {
+    pub file_path: String,
+    pub title: String,
+    pub total_visits: u64,
+    pub page_count: usize,
+}
+
+#[tauri::command]
+pub async fn generate_attendance_report(
+    state: State<'_, DbState>,
+    period: ReportPeriod,
+    file_path: String,
+    prepared_by: Option<String>
+) -> Result<ReportSummary, String> {
+    let db = state.0.clone();
+
+    let report = db.with_connection(move |conn| {
+        build_attendance_report(conn, &period)
+    }).await.map_err(|e| format!("Failed to build report: {}", e))?;
+
+    let page_count = write_report_pdf(&report, prepared_by.as_deref(), &file_path)?;
+
+    Ok(ReportSummary {
+        file_path,
+        title: report.title,
+        total_visits: report.total_visits,
+        page_count,
+    })
+}
@@ -0,0 +1,76 @@
+// src/audit_log_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::attendance_export_commands::ExportSummary;
+use crate::db::audit_log::{AuditLogFilter, PaginatedAuditLog};
+use crate::db::hash_chain::ChainVerification;
+use crate::db::library_timezone::LibraryTimezone;
+use rusqlite::Result;
+use std::error::Error;
+
+#[tauri::command]
+pub async fn get_audit_log(
+    state: State<'_, DbState>,
+    filter: AuditLogFilter,
+    page: Option<u64>,
+    page_size: Option<u64>
+) -> Result<PaginatedAuditLog, String> {
+    let db = state.0.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        audit_log.get_audit_log(conn, &filter, page.unwrap_or(1), page_size.unwrap_or(0))
+    }).await.map_err(|e| e.to_string())
+}
+
+// Writes the matching entries to a CSV file, oldest first, in library time
+#[tauri::command]
+pub async fn export_audit_log(
+    state: State<'_, DbState>,
+    filter: AuditLogFilter,
+    file_path: String
+) -> Result<ExportSummary, String> {
+    let audit_log = state.0.audit_log.clone();
+    let conn = state.0.pool.get()
+        .map_err(|e| format!("Failed to get connection: {}", e))?;
+
+    let result: Result<usize, Box<dyn Error>> = (|| {
+        let timezone = LibraryTimezone::load(&conn)?;
+        let mut writer = csv::Writer::from_path(&file_path)?;
+        writer.write_record(["Time", "User", "Action", "Target", "Summary"])?;
+
+        let count = audit_log.for_each_audit_entry(&conn, &filter, |entry| {
+            writer.write_record([
+                timezone.local(entry.occurred_at).format("%Y-%m-%d %H:%M:%S").to_string().as_str(),
+                &entry.username,
+                entry.action.as_str(),
+                entry.target.as_deref().unwrap_or(""),
+                entry.summary.as_deref().unwrap_or(""),
+            ])?;
+            Ok::<(), Box<dyn Error>>(())
+        })?;
+
+        writer.flush()?;
+        Ok(count)
+    })();
+
+    let row_count = result.map_err(|e| format!("Export failed: {}", e))?;
+
+    Ok(ExportSummary {
+        file_path,
+        row_count,
+    })
+}
+
+// Walks the attendance and audit hash chain and reports the first broken link
+#[tauri::command]
+pub async fn verify_hash_chain(
+    state: State<'_, DbState>
+) -> Result<ChainVerification, String> {
+    let db = state.0.clone();
+    let hash_chain = db.hash_chain.clone();
+
+    db.with_connection(move |conn| {
+        hash_chain.verify(conn)
+    }).await.map_err(|e| e.to_string())
+}
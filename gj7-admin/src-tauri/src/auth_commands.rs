@@ -0,0 +1,354 @@
+// src/auth_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::auth::{Credentials, PinCheck, User};
+use crate::db::audit_log::AuditAction;
+use crate::db::roles::Role;
+use crate::session::{LockState, SessionState, SessionUser};
+use rusqlite::Result;
+use chrono::Utc;
+
+#[tauri::command]
+pub async fn login(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    username: String,
+    password: String
+) -> Result<SessionUser, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    let (user, locked_until) = db.with_connection(move |conn| {
+        let user = auth.login(conn, &username, &password)?;
+        let locked_until = match user {
+            Some(_) => None,
+            None => auth.login_locked_until(conn, &username)?,
+        };
+        Ok((user, locked_until))
+    }).await.map_err(|e| e.to_string())?;
+
+    if let Some(until) = locked_until {
+        return Err(format!(
+            "Too many wrong passwords; try again in {} minute(s)",
+            (until - Utc::now()).num_minutes() + 1
+        ));
+    }
+
+    user.map(|user| session.sign_in(&user))
+        .ok_or_else(|| "Invalid username or password".to_string())
+}
+
+#[tauri::command]
+pub async fn logout(
+    session: State<'_, SessionState>
+) -> Result<(), String> {
+    session.sign_out();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_current_user(
+    session: State<'_, SessionState>
+) -> Result<Option<SessionUser>, String> {
+    Ok(session.current())
+}
+
+#[tauri::command]
+pub async fn get_lock_state(
+    session: State<'_, SessionState>
+) -> Result<LockState, String> {
+    Ok(session.lock_state())
+}
+
+// Sent by the frontend on user input so the idle lock knows the app is in use
+#[tauri::command]
+pub async fn record_activity(
+    session: State<'_, SessionState>
+) -> Result<(), String> {
+    session.record_activity();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn lock_session(
+    session: State<'_, SessionState>
+) -> Result<LockState, String> {
+    Ok(session.lock())
+}
+
+#[tauri::command]
+pub async fn set_pin(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    pin: String,
+    username: String,
+    password: String
+) -> Result<User, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    let user = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            auth.set_pin(conn, &username, &pin)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.refresh(&user);
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn clear_pin(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    username: String,
+    password: String
+) -> Result<User, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    let user = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            auth.clear_pin(conn, &username)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.refresh(&user);
+    Ok(user)
+}
+
+// Remaining attempts and any lockout for the signed-in user's PIN
+#[tauri::command]
+pub async fn get_pin_status(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>
+) -> Result<PinCheck, String> {
+    let user_id = session.current().ok_or("Sign in first")?.id;
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    db.with_connection(move |conn| {
+        auth.get_pin_status(conn, user_id)
+    }).await.map_err(|e| e.to_string())
+}
+
+// Checks the signed-in user's PIN and unlocks the app when it matches
+#[tauri::command]
+pub async fn verify_pin(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    pin: String
+) -> Result<PinCheck, String> {
+    let user_id = session.current().ok_or("Sign in first")?.id;
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    let check = db.with_connection(move |conn| {
+        auth.verify_pin(conn, user_id, &pin)
+    }).await.map_err(|e| e.to_string())?;
+
+    if check.verified {
+        session.unlock();
+    }
+    Ok(check)
+}
+
+#[tauri::command]
+pub async fn change_password(
+    state: State<'_, DbState>,
+    username: String,
+    current_password: String,
+    new_password: String
+) -> Result<(), String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &current_password)? {
+            auth.set_password(conn, &username, &new_password)?;
+            audit_log.record(conn, &username, AuditAction::PasswordChanged, Some(&username), None)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+// Returns the new codes once; only their hashes are kept
+#[tauri::command]
+pub async fn generate_recovery_codes(
+    state: State<'_, DbState>,
+    username: String,
+    password: String
+) -> Result<Vec<String>, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            auth.generate_recovery_codes(conn, &username)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn reset_password_with_recovery_code(
+    state: State<'_, DbState>,
+    username: String,
+    recovery_code: String,
+    new_password: String
+) -> Result<(), String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        auth.reset_password_with_recovery_code(conn, &username, &recovery_code, &new_password)?;
+        audit_log.record(conn, &username, AuditAction::PasswordReset, Some(&username), Some("With a recovery code"))
+    }).await.map_err(|e| format!("Password reset failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn list_users(
+    state: State<'_, DbState>
+) -> Result<Vec<User>, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    
+    db.with_connection(move |conn| {
+        auth.list_users(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_user(
+    state: State<'_, DbState>,
+    new_user: Credentials,
+    role: Role,
+    username: String,
+    password: String
+) -> Result<User, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let user = auth.create_user(conn, &new_user, role)?;
+            audit_log.record(conn, &username, AuditAction::UserCreated, Some(&user.username), Some(role.as_str()))?;
+            Ok(user)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn set_user_role(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    user_id: i64,
+    role: Role,
+    username: String,
+    password: String
+) -> Result<User, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    let user = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let user = auth.set_user_role(conn, user_id, role)?;
+            audit_log.record(conn, &username, AuditAction::UserRoleChanged, Some(&user.username), Some(role.as_str()))?;
+            Ok(user)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.refresh(&user);
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn set_user_active(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    user_id: i64,
+    is_active: bool,
+    username: String,
+    password: String
+) -> Result<User, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    let user = db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let user = auth.set_user_active(conn, user_id, is_active)?;
+            let action = if is_active { AuditAction::UserActivated } else { AuditAction::UserDeactivated };
+            audit_log.record(conn, &username, action, Some(&user.username), None)?;
+            Ok(user)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.refresh(&user);
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn delete_user(
+    state: State<'_, DbState>,
+    session: State<'_, SessionState>,
+    user_id: i64,
+    username: String,
+    password: String
+) -> Result<(), String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let deleted = auth.get_user(conn, user_id)?;
+            auth.delete_user(conn, user_id)?;
+            audit_log.record(conn, &username, AuditAction::UserDeleted, Some(&deleted.username), None)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))?;
+
+    session.forget(user_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reset_user_password(
+    state: State<'_, DbState>,
+    user_id: i64,
+    new_password: String,
+    username: String,
+    password: String
+) -> Result<(), String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            auth.reset_user_password(conn, user_id, &new_password)?;
+            let user = auth.get_user(conn, user_id)?;
+            audit_log.record(conn, &username, AuditAction::PasswordReset, Some(&user.username), Some("By a librarian"))
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
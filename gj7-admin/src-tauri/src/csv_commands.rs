@@ -9,6 +9,8 @@ use crate::db::school_accounts::SchoolAccount;
 use crate::redis_csv_processor::RedisCsvProcessor;
 use crate::db::csv_import::ValidationErrorType;
 use crate::logger::{emit_log, LogMessage};
+use crate::db::audit_log::AuditAction;
+use crate::session::SessionState;
 use std::sync::Arc;
 use csv::StringRecord;
 use log::{info, error};
@@ -140,10 +142,26 @@ pub async fn validate_csv_file(
 }
 
 
+// Records who imported which file, once the import has finished
+async fn record_import(
+    state: &State<'_, DbState>,
+    session: &State<'_, SessionState>,
+    file_name: String,
+    summary: String
+) -> Result<(), String> {
+    let audit_log = state.0.audit_log.clone();
+    let actor = session.actor();
+
+    state.0.with_connection(move |conn| {
+        audit_log.record(conn, &actor, AuditAction::CsvImported, Some(&file_name), Some(&summary))
+    }).await.map_err(|e| format!("Failed to record import in audit log: {}", e))
+}
+
 #[command]
 pub async fn import_csv_file(
     app_handle: tauri::AppHandle,
     state: State<'_, DbState>,
+    session: State<'_, SessionState>,
     file_path: String,
     last_updated_semester_id: Uuid,
     force_update: bool
@@ -221,7 +239,17 @@ pub async fn import_csv_file(
                                         account_request.clone().into()
                                     ) {
                                         Ok(updated_account) => {
-                                            successful_imports += 1;
+                                            match state.0.semester_accounts.record_semester_account(
+                                                conn,
+                                                last_updated_semester_id,
+                                                &account_request
+                                            ) {
+                                                Ok(_) => successful_imports += 1,
+                                                Err(e) => {
+                                                    failed_imports += 1;
+                                                    error_details.push(format!("Semester snapshot failed for {}: {}", account_request.school_id, e));
+                                                }
+                                            }
                                             existing_accounts.push(updated_account);
                                         },
                                         Err(e) => {
@@ -236,7 +264,19 @@ pub async fn import_csv_file(
                             },
                             Err(_) => {
                                 match state.0.school_accounts.create_school_account(conn, account_request.clone()) {
-                                    Ok(_) => successful_imports += 1,
+                                    Ok(_) => {
+                                        match state.0.semester_accounts.record_semester_account(
+                                            conn,
+                                            last_updated_semester_id,
+                                            &account_request
+                                        ) {
+                                            Ok(_) => successful_imports += 1,
+                                            Err(e) => {
+                                                failed_imports += 1;
+                                                error_details.push(format!("Semester snapshot failed for {}: {}", account_request.school_id, e));
+                                            }
+                                        }
+                                    },
                                     Err(e) => {
                                         failed_imports += 1;
                                         error_details.push(format!("Import failed: {}", e));
@@ -288,6 +328,16 @@ pub async fn import_csv_file(
     }).await.map_err(|e| format!("Failed to get final counts: {}", e))?;
     
     let deactivated_accounts = total_accounts_after - activated_accounts;
+
+    record_import(
+        &state,
+        &session,
+        validation_result.file_name.clone(),
+        format!(
+            "{} processed, {} imported, {} failed, {} deactivated, semester {}",
+            total_processed, successful_imports, failed_imports, deactivated_accounts, last_updated_semester_id
+        )
+    ).await?;
     
     Ok(CsvImportResponse {
         validation_result: validation_result,
@@ -312,6 +362,7 @@ pub async fn import_csv_file(
 pub async fn import_csv_file_parallel(
     app_handle: tauri::AppHandle,
     state: State<'_, DbState>,
+    session: State<'_, SessionState>,
     file_path: String,
     last_updated_semester_id: Uuid,
     force_update: bool,
@@ -439,6 +490,10 @@ pub async fn import_csv_file_parallel(
                         activated_accounts += 1;
                     }
                 }
+
+                // Keep what the account looked like in this semester
+                state.0.semester_accounts.record_semester_account(&tx, last_updated_semester_id, &create_request)
+                    .map_err(|e| format!("Failed to record semester snapshot for {}: {}", school_id, e))?;
             },
             Err(e) => {
                 return Err(format!("Transform error for {}: {}", school_id, e));
@@ -458,6 +513,16 @@ pub async fn import_csv_file_parallel(
     
     let deactivated_accounts = total_accounts_after - activated_accounts;
 
+    record_import(
+        &state,
+        &session,
+        validation_result.file_name.clone(),
+        format!(
+            "{} processed, {} imported, {} failed, {} deactivated, semester {} (parallel)",
+            records.len(), processing_result.successful, processing_result.failed, deactivated_accounts, last_updated_semester_id
+        )
+    ).await?;
+
     // Prepare response
     let import_response = CsvImportResponse {
         validation_result,
@@ -13,21 +13,49 @@ use crate::config;
 use crate::storage::AppStorage;
 pub mod notes;
 pub mod auth;
+pub mod roles;
+pub mod idle_lock;
+pub mod network_settings;
 pub mod school_accounts;
 pub mod csv_import;
 pub mod csv_transform;
 pub mod semester;
+pub mod semester_accounts;
 pub mod attendance;
+pub mod attendance_history;
+pub mod attendance_bulk;
 pub mod purpose;
 pub mod settings_styles;
+pub mod scan_cooldown;
+pub mod attendance_analytics;
+pub mod attendance_export;
+pub mod attendance_report;
+pub mod app_settings;
+pub mod library_timezone;
+pub mod occupancy;
+pub mod search;
+pub mod audit_log;
+pub mod hash_chain;
+pub mod kiosk_devices;
 
 use notes::NotesDatabase;
 use auth::AuthDatabase;
 use school_accounts::{SchoolAccountRepository, SqliteSchoolAccountRepository};
 use semester::{SemesterRepository, SqliteSemesterRepository};
+use semester_accounts::{SemesterAccountRepository, SqliteSemesterAccountRepository};
 use attendance::{AttendanceRepository, SqliteAttendanceRepository};
+use attendance_history::{AttendanceHistoryRepository, SqliteAttendanceHistoryRepository};
 use purpose::{PurposeRepository, SqlitePurposeRepository};
 use settings_styles::SettingsStylesDatabase;
+use scan_cooldown::ScanCooldownDatabase;
+use app_settings::AppSettingsDatabase;
+use library_timezone::LibraryTimezone;
+use occupancy::OccupancyDatabase;
+use search::SearchDatabase;
+use audit_log::AuditLogDatabase;
+use hash_chain::HashChainDatabase;
+use kiosk_devices::KioskDeviceDatabase;
+use attendance_analytics::{AttendanceAnalyticsRepository, SqliteAttendanceAnalyticsRepository};
 use std::sync::Arc;
 use crate::parallel_csv_validator::ParallelCsvValidator;
 
@@ -43,15 +71,26 @@ pub struct Database {
     pub auth: AuthDatabase,
     pub school_accounts: Arc<dyn SchoolAccountRepository + Send + Sync>,
     pub semester_repository: Box<dyn SemesterRepository + Send + Sync>,
+    pub semester_accounts: Arc<dyn SemesterAccountRepository + Send + Sync>,
     pub attendance_repository: Arc<dyn AttendanceRepository + Send + Sync>,
+    pub attendance_history: Arc<dyn AttendanceHistoryRepository + Send + Sync>,
+    pub attendance_analytics: Arc<dyn AttendanceAnalyticsRepository + Send + Sync>,
     pub purpose_repository: Arc<dyn PurposeRepository + Send + Sync>,
     pub settings_styles: SettingsStylesDatabase,
+    pub scan_cooldowns: ScanCooldownDatabase,
+    pub app_settings: AppSettingsDatabase,
+    pub occupancy: OccupancyDatabase,
+    pub search: SearchDatabase,
+    pub audit_log: AuditLogDatabase,
+    pub hash_chain: HashChainDatabase,
+    pub kiosk_devices: KioskDeviceDatabase,
     db_path: PathBuf,
 }
 
 impl Clone for Database {
     fn clone(&self) -> Self {
-        let manager = SqliteConnectionManager::file(self.db_path.clone());
+        let manager = SqliteConnectionManager::file(self.db_path.clone())
+            .with_init(|conn| hash_chain::register_functions(conn));
         let pool = Pool::new(manager).expect("Failed to create connection pool");
 
         Database {
@@ -60,9 +99,19 @@ impl Clone for Database {
             auth: self.auth.clone(),
             school_accounts: Arc::clone(&self.school_accounts),
             semester_repository: Box::new(SqliteSemesterRepository),
+            semester_accounts: Arc::new(SqliteSemesterAccountRepository),
             attendance_repository: Arc::new(SqliteAttendanceRepository),
+            attendance_history: Arc::new(SqliteAttendanceHistoryRepository),
+            attendance_analytics: Arc::new(SqliteAttendanceAnalyticsRepository),
             purpose_repository: Arc::new(SqlitePurposeRepository),
             settings_styles: self.settings_styles.clone(),
+            scan_cooldowns: self.scan_cooldowns.clone(),
+            app_settings: self.app_settings.clone(),
+            occupancy: self.occupancy.clone(),
+            search: self.search.clone(),
+            audit_log: self.audit_log.clone(),
+            hash_chain: self.hash_chain.clone(),
+            kiosk_devices: self.kiosk_devices.clone(),
             db_path: self.db_path.clone(),
         }
     }
@@ -126,7 +175,7 @@ impl Database {
                     PRAGMA query_only=0;
                     PRAGMA optimize;
                 ")?;
-                Ok(())
+                hash_chain::register_functions(conn)
             });
 
         let pool = Pool::builder()
@@ -146,12 +195,23 @@ impl Database {
         info!("Creating database tables...");
         school_accounts::create_school_accounts_table(&conn)?;
         semester::create_semesters_table(&conn)?;
+        semester_accounts::create_semester_accounts_table(&conn)?;
         purpose::create_purposes_table(&conn)?;
         attendance::create_attendance_table(&conn)?;
+        attendance_history::create_attendance_history_table(&conn)?;
         
         let notes_db = NotesDatabase::init(&conn)?;
         let auth_db = AuthDatabase::init(&conn)?;
         let settings_styles_db = SettingsStylesDatabase::init(&conn)?;
+        let scan_cooldowns_db = ScanCooldownDatabase::init(&conn)?;
+        let app_settings_db = AppSettingsDatabase::init(&conn)?;
+        // Saves this machine's offset on first start unless a timezone is set
+        LibraryTimezone::load(&conn)?;
+        let search_db = SearchDatabase::init(&conn)?;
+        let audit_log_db = AuditLogDatabase::init(&conn)?;
+        let kiosk_devices_db = KioskDeviceDatabase::init(&conn)?;
+        // Last, so the chain starts from every attendance and audit row
+        let hash_chain_db = HashChainDatabase::init(&conn)?;
         
         info!("Database initialization completed successfully");
         Ok(Database {
@@ -160,9 +220,19 @@ impl Database {
             auth: auth_db,
             school_accounts: Arc::new(SqliteSchoolAccountRepository),
             semester_repository: Box::new(SqliteSemesterRepository),
+            semester_accounts: Arc::new(SqliteSemesterAccountRepository),
             attendance_repository: Arc::new(SqliteAttendanceRepository),
+            attendance_history: Arc::new(SqliteAttendanceHistoryRepository),
+            attendance_analytics: Arc::new(SqliteAttendanceAnalyticsRepository),
             purpose_repository: Arc::new(SqlitePurposeRepository),
             settings_styles: settings_styles_db,
+            scan_cooldowns: scan_cooldowns_db,
+            app_settings: app_settings_db,
+            occupancy: OccupancyDatabase,
+            search: search_db,
+            audit_log: audit_log_db,
+            hash_chain: hash_chain_db,
+            kiosk_devices: kiosk_devices_db,
             db_path,
         })
     }
@@ -178,6 +248,32 @@ impl Database {
     }
 }
 
+// Adds a column to an existing table when an older database is missing it.
+// CREATE TABLE IF NOT EXISTS never touches existing tables, so new columns
+// have to be added this way for databases created by earlier versions.
+pub(crate) fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str
+) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        info!("Adding column {}.{}", table, column);
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
 fn get_database_path(db_dir: &PathBuf) -> Result<PathBuf, String> {
     let db_name = config::load_database_name()
         .map_err(|e| format!("Failed to load database name: {}", e))?;
@@ -0,0 +1,57 @@
+// src/db/app_settings.rs
+
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
+
+// Key/value store for single application-wide settings
+#[derive(Clone)]
+pub struct AppSettingsDatabase;
+
+impl AppSettingsDatabase {
+    pub fn init(conn: &Connection) -> SqliteResult<Self> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS app_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(AppSettingsDatabase)
+    }
+
+    pub fn get_setting(&self, conn: &Connection, key: &str) -> SqliteResult<Option<String>> {
+        conn.query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        ).optional()
+    }
+
+    // Parses a stored setting, treating unreadable values as unset
+    pub fn get_parsed_setting<T: std::str::FromStr>(&self, conn: &Connection, key: &str) -> SqliteResult<Option<T>> {
+        Ok(self.get_setting(conn, key)?.and_then(|value| value.parse().ok()))
+    }
+
+    pub fn set_setting(&self, conn: &Connection, key: &str, value: &str) -> SqliteResult<()> {
+        conn.execute(
+            "INSERT INTO app_settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove_setting(&self, conn: &Connection, key: &str) -> SqliteResult<()> {
+        conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
+    // Stores the value when present and clears the setting otherwise
+    pub fn set_optional_setting<T: ToString>(&self, conn: &Connection, key: &str, value: Option<T>) -> SqliteResult<()> {
+        match value {
+            Some(value) => self.set_setting(conn, key, &value.to_string()),
+            None => self.remove_setting(conn, key),
+        }
+    }
+}
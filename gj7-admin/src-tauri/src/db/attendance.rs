@@ -1,9 +1,20 @@
 // src/db/attendance.rs
 
 use uuid::Uuid;
-use rusqlite::{params, Connection, Result};
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row, Transaction, TransactionBehavior};
+use rusqlite::types::Value;
 use serde::{Serialize, Deserialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::fmt;
+
+use crate::db::add_column_if_missing;
+use crate::db::app_settings::AppSettingsDatabase;
+use crate::db::attendance_history::{AttendanceAction, AttendanceHistoryRepository, SqliteAttendanceHistoryRepository};
+use crate::db::library_timezone::LibraryTimezone;
+use crate::db::scan_cooldown::{CooldownMode, ScanCooldownDatabase};
+use crate::db::search::fts_query;
+use crate::db::semester::{SemesterRepository, SqliteSemesterRepository};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Attendance {
@@ -13,6 +24,15 @@ pub struct Attendance {
     pub time_in_date: DateTime<Utc>,
     pub classification: String,
     pub purpose_label: Option<String>,
+    pub time_out_date: Option<DateTime<Utc>>,
+    pub semester_id: Option<Uuid>,
+    // Entered by an admin after the fact rather than scanned at the kiosk
+    pub is_manual: bool,
+    pub entered_by: Option<String>,
+    pub is_deleted: bool,
+    // The kiosk the visit was checked in at, and where it stood at the time
+    pub device_id: Option<i64>,
+    pub location: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,6 +41,25 @@ pub struct CreateAttendanceRequest {
     pub full_name: String,
     pub classification: Option<String>,
     pub purpose_label: Option<String>,
+    // Filled in by the network server from the kiosk's token, never read from
+    // the request body, so a kiosk cannot claim to be another gate
+    #[serde(default, skip_deserializing)]
+    pub device_id: Option<i64>,
+    #[serde(default, skip_deserializing)]
+    pub location: Option<String>,
+}
+
+// A visit copied from the paper logbook while the kiosk was unavailable
+#[derive(Debug, Deserialize, Clone)]
+pub struct ManualAttendanceRequest {
+    pub school_id: String,
+    pub full_name: String,
+    pub classification: Option<String>,
+    pub purpose_label: Option<String>,
+    pub time_in_date: DateTime<Utc>,
+    pub time_out_date: Option<DateTime<Utc>>,
+    // Defaults to the semester the time-in falls in
+    pub semester_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,25 +70,406 @@ pub struct UpdateAttendanceRequest {
     pub purpose_label: Option<String>,
 }
 
+// A visit is open until it is checked out, but only for the library day it
+// started; a visit left open yesterday must not swallow today's first scan
+pub(crate) fn open_visit_condition(timezone: &LibraryTimezone) -> String {
+    format!(
+        "is_deleted = 0 AND time_out_date IS NULL AND time_in_date >= {}",
+        timezone.start_of_day(timezone.today()).timestamp_millis()
+    )
+}
+
+// Attendance joined with the scanner's account and its snapshot for the visit's
+// semester, shared by every filtered query
+pub(crate) const ATTENDANCE_WITH_ACCOUNT: &str =
+    "attendance a
+     LEFT JOIN school_accounts sa ON a.school_id = sa.school_id
+     LEFT JOIN semester_accounts sem ON sem.semester_id = a.semester_id AND sem.school_id = a.school_id";
+
+// Account details as they were in the visit's semester, falling back to the
+// current account when that semester has no snapshot
+pub(crate) const ACCOUNT_COURSE: &str =
+    "CASE WHEN sem.school_id IS NOT NULL THEN sem.course ELSE sa.course END";
+pub(crate) const ACCOUNT_DEPARTMENT: &str =
+    "CASE WHEN sem.school_id IS NOT NULL THEN sem.department ELSE sa.department END";
+pub(crate) const ACCOUNT_YEAR_LEVEL: &str =
+    "CASE WHEN sem.school_id IS NOT NULL THEN sem.year_level ELSE sa.year_level END";
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AttendanceFilter {
+    // Inclusive start of the time-in range
+    pub from: Option<DateTime<Utc>>,
+    // Exclusive end of the time-in range
+    pub to: Option<DateTime<Utc>>,
+    // Whole days in the library's timezone, both inclusive
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub course: Option<String>,
+    pub purpose_label: Option<String>,
+    pub classification: Option<String>,
+    pub school_id_prefix: Option<String>,
+    pub year_level: Option<String>,
+    pub department: Option<String>,
+    pub semester_id: Option<Uuid>,
+    pub is_manual: Option<bool>,
+    pub device_id: Option<i64>,
+    pub location: Option<String>,
+    // Deleted records are hidden unless asked for, e.g. to restore them
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+impl AttendanceFilter {
+    // Builds the conditions to append after `WHERE 1=1` on ATTENDANCE_WITH_ACCOUNT
+    pub(crate) fn to_sql(&self, timezone: &LibraryTimezone) -> (String, Vec<Value>) {
+        let mut conditions = String::new();
+        let mut values = Vec::new();
+
+        if !self.include_deleted {
+            conditions.push_str(" AND a.is_deleted = 0");
+        }
+
+        if let Some(from) = self.from {
+            conditions.push_str(" AND a.time_in_date >= ?");
+            values.push(Value::Integer(from.timestamp_millis()));
+        }
+
+        if let Some(to) = self.to {
+            conditions.push_str(" AND a.time_in_date < ?");
+            values.push(Value::Integer(to.timestamp_millis()));
+        }
+
+        if let Some(from_date) = self.from_date {
+            conditions.push_str(" AND a.time_in_date >= ?");
+            values.push(Value::Integer(timezone.start_of_day(from_date).timestamp_millis()));
+        }
+
+        if let Some(to_date) = self.to_date {
+            conditions.push_str(" AND a.time_in_date < ?");
+            values.push(Value::Integer(
+                timezone.start_of_day(to_date + Duration::days(1)).timestamp_millis()
+            ));
+        }
+
+        if let Some(semester_id) = self.semester_id {
+            conditions.push_str(" AND a.semester_id = ?");
+            values.push(Value::Text(semester_id.to_string()));
+        }
+
+        if let Some(is_manual) = self.is_manual {
+            conditions.push_str(" AND a.is_manual = ?");
+            values.push(Value::Integer(is_manual as i64));
+        }
+
+        if let Some(device_id) = self.device_id {
+            conditions.push_str(" AND a.device_id = ?");
+            values.push(Value::Integer(device_id));
+        }
+
+        let exact_matches = [
+            (ACCOUNT_COURSE, &self.course),
+            ("a.purpose_label", &self.purpose_label),
+            ("a.classification", &self.classification),
+            (ACCOUNT_YEAR_LEVEL, &self.year_level),
+            (ACCOUNT_DEPARTMENT, &self.department),
+            ("a.location", &self.location),
+        ];
+
+        for (column, value) in exact_matches {
+            if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+                conditions.push_str(&format!(" AND {} = ?", column));
+                values.push(Value::Text(value.clone()));
+            }
+        }
+
+        if let Some(prefix) = self.school_id_prefix.as_ref().filter(|p| !p.is_empty()) {
+            let escaped = prefix
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            conditions.push_str(" AND a.school_id LIKE ? ESCAPE '\\'");
+            values.push(Value::Text(format!("{}%", escaped)));
+        }
+
+        (conditions, values)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub enum AttendanceSortField {
+    #[default]
+    TimeIn,
+    TimeOut,
+    SchoolId,
+    FullName,
+    Classification,
+    Purpose,
+    Course,
+    Location,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct AttendanceSort {
+    #[serde(default)]
+    pub field: AttendanceSortField,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl AttendanceSort {
+    pub(crate) fn to_sql(self) -> String {
+        let column = match self.field {
+            AttendanceSortField::TimeIn => "a.time_in_date",
+            AttendanceSortField::TimeOut => "a.time_out_date",
+            AttendanceSortField::SchoolId => "a.school_id",
+            AttendanceSortField::FullName => "a.full_name COLLATE NOCASE",
+            AttendanceSortField::Classification => "a.classification",
+            AttendanceSortField::Purpose => "a.purpose_label",
+            AttendanceSortField::Course => ACCOUNT_COURSE,
+            AttendanceSortField::Location => "a.location",
+        };
+        let order = match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        // Newest first as a tie-breaker keeps pages stable
+        format!("ORDER BY {} {}, a.time_in_date DESC, a.id", column, order)
+    }
+}
+
+// Largest page get_filtered_attendances returns, however many are asked for
+const MAX_PAGE_SIZE: u64 = 500;
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedAttendances {
+    pub attendances: Vec<Attendance>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+    pub total_pages: u64,
+}
+
+// Result of a kiosk scan
+#[derive(Debug, Clone)]
+pub enum ScanOutcome {
+    CheckedIn(Attendance),
+    CheckedOut(Attendance),
+    // Repeat scan inside the cooldown window, folded into the existing record
+    Merged(Attendance),
+}
+
+impl ScanOutcome {
+    pub fn into_attendance(self) -> Attendance {
+        match self {
+            ScanOutcome::CheckedIn(attendance)
+            | ScanOutcome::CheckedOut(attendance)
+            | ScanOutcome::Merged(attendance) => attendance,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScanError {
+    // Repeat scan inside the cooldown window while the cooldown mode is Reject
+    DuplicateScan {
+        school_id: String,
+        retry_after_seconds: i64,
+    },
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::DuplicateScan { school_id, retry_after_seconds } => write!(
+                f,
+                "{} was already scanned, try again in {} seconds",
+                school_id, retry_after_seconds
+            ),
+            ScanError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+impl From<rusqlite::Error> for ScanError {
+    fn from(err: rusqlite::Error) -> Self {
+        ScanError::Database(err)
+    }
+}
+
 pub trait AttendanceRepository: Send + Sync {
     fn clone_box(&self) -> Box<dyn AttendanceRepository + Send + Sync>;
     fn create_attendance(&self, conn: &Connection, attendance: CreateAttendanceRequest) -> Result<Attendance>;
+    fn create_manual_attendance(
+        &self,
+        conn: &Connection,
+        attendance: ManualAttendanceRequest,
+        entered_by: &str
+    ) -> Result<Attendance>;
     fn get_attendance(&self, conn: &Connection, id: Uuid) -> Result<Attendance>;
     fn get_attendances_by_school_id(&self, conn: &Connection, school_id: &str) -> Result<Vec<Attendance>>;
-    fn delete_attendance(&self, conn: &Connection, id: Uuid) -> Result<()>;
+    fn delete_attendance(&self, conn: &Connection, id: Uuid, deleted_by: &str, reason: Option<&str>) -> Result<()>;
+    fn restore_attendance(&self, conn: &Connection, id: Uuid, restored_by: &str, reason: Option<&str>) -> Result<Attendance>;
     fn get_all_attendances(&self, conn: &Connection) -> Result<Vec<Attendance>>;
     fn search_attendances(&self, conn: &Connection, query: &str) -> Result<Vec<Attendance>>;
-    fn update_attendance(&self, conn: &Connection, id: Uuid, attendance: UpdateAttendanceRequest) -> Result<Attendance>;
+    fn update_attendance(
+        &self,
+        conn: &Connection,
+        id: Uuid,
+        attendance: UpdateAttendanceRequest,
+        updated_by: &str,
+        reason: Option<&str>
+    ) -> Result<Attendance>;
     fn get_attendances_by_semester(&self, conn: &Connection, semester_id: Uuid) -> Result<Vec<Attendance>>;
     fn get_attendances_by_school_account(&self, conn: &Connection, school_account_id: Uuid) -> Result<Vec<Attendance>>;
     fn get_last_n_attendances(&self, conn: &Connection, n: usize) -> Result<Vec<Attendance>, rusqlite::Error>;
     fn get_filtered_attendances(
         &self, 
         conn: &Connection, 
-        course: Option<String>, 
-        date: Option<DateTime<Utc>>
-    ) -> Result<Vec<Attendance>>;
+        filter: &AttendanceFilter,
+        sort: AttendanceSort,
+        page: u64,
+        page_size: u64
+    ) -> Result<PaginatedAttendances>;
     fn get_all_courses(&self, conn: &Connection) -> Result<Vec<String>>;
+    fn get_all_locations(&self, conn: &Connection) -> Result<Vec<String>>;
+    fn get_open_attendance(&self, conn: &Connection, school_id: &str) -> Result<Option<Attendance>>;
+    // A kiosk checkout fills in its device and location on a visit recorded without them
+    fn checkout_attendance(
+        &self,
+        conn: &Connection,
+        school_id: &str,
+        device_id: Option<i64>,
+        location: Option<&str>
+    ) -> Result<Attendance>;
+    fn scan_attendance(&self, conn: &Connection, attendance: CreateAttendanceRequest) -> Result<ScanOutcome, ScanError>;
+}
+
+// Attendance timestamps are stored as milliseconds since the Unix epoch
+pub(crate) fn from_millis(value: i64, column: usize) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(value)
+        .ok_or(rusqlite::Error::IntegralValueOutOfRange(column, value))
+}
+
+fn parse_timestamp(value: &str, column: usize) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Text,
+            Box::new(e)
+        ))
+}
+
+// A malformed id fails the query instead of panicking inside the row mapper
+fn parse_uuid(value: &str, column: usize) -> Result<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Text,
+            Box::new(e)
+        ))
+}
+
+// Maps a row selected with `SELECT *` (or the same column order) to an Attendance
+pub(crate) fn row_to_attendance(row: &Row) -> Result<Attendance> {
+    Ok(Attendance {
+        id: parse_uuid(&row.get::<_, String>(0)?, 0)?,
+        school_id: row.get(1)?,
+        full_name: row.get(2)?,
+        time_in_date: from_millis(row.get(3)?, 3)?,
+        classification: row.get(4)?,
+        purpose_label: row.get(5)?,
+        time_out_date: row.get::<_, Option<i64>>(6)?
+            .map(|value| from_millis(value, 6))
+            .transpose()?,
+        semester_id: row.get::<_, Option<String>>(7)?
+            .map(|id| parse_uuid(&id, 7))
+            .transpose()?,
+        is_manual: row.get(8)?,
+        entered_by: row.get(9)?,
+        is_deleted: row.get(10)?,
+        device_id: row.get(11)?,
+        location: row.get(12)?,
+    })
+}
+
+// Prefers the name on the school account, falling back to the one supplied
+fn resolve_full_name(conn: &Connection, school_id: &str, full_name: &str) -> String {
+    conn.query_row(
+        "SELECT 
+            COALESCE(
+                CASE 
+                    WHEN first_name IS NOT NULL AND middle_name IS NOT NULL AND last_name IS NOT NULL THEN 
+                        first_name || ' ' || middle_name || ' ' || last_name
+                    WHEN first_name IS NOT NULL AND last_name IS NOT NULL THEN 
+                        first_name || ' ' || last_name
+                    ELSE first_name
+                END, 
+                ?1
+            ) as computed_full_name
+        FROM school_accounts 
+        WHERE school_id = ?2",
+        params![full_name, school_id],
+        |row| row.get::<_, String>(0)
+    ).unwrap_or_else(|_| full_name.to_string())
+}
+
+// Finds the semester a backdated visit belongs to. A semester runs from its
+// creation until the next one is created; with no semesters at all the visit
+// is left unassigned, as scans are when no semester is active
+fn semester_for_visit(conn: &Connection, requested: Option<Uuid>, time_in: DateTime<Utc>) -> Result<Option<Uuid>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, created_at, LEAD(created_at) OVER (ORDER BY datetime(created_at))
+         FROM semesters"
+    )?;
+    let bounds = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+    })?.collect::<Result<Vec<_>>>()?;
+
+    if bounds.is_empty() {
+        return Ok(None);
+    }
+
+    for (id, starts_at, ends_at) in bounds {
+        let id = Uuid::parse_str(&id).unwrap();
+        let starts_at = parse_timestamp(&starts_at, 1)?;
+        let ends_at = ends_at.map(|value| parse_timestamp(&value, 2)).transpose()?;
+        let covers = starts_at <= time_in && match ends_at {
+            Some(ends_at) => time_in < ends_at,
+            None => true,
+        };
+
+        match requested {
+            Some(requested) if requested == id => {
+                if !covers {
+                    return Err(rusqlite::Error::InvalidParameterName(format!(
+                        "Time in must be between {} and {} for the selected semester",
+                        starts_at.to_rfc3339(),
+                        ends_at.map(|ends_at| ends_at.to_rfc3339()).unwrap_or_else(|| "now".to_string())
+                    )));
+                }
+                return Ok(Some(id));
+            },
+            None if covers => return Ok(Some(id)),
+            _ => {},
+        }
+    }
+
+    Err(rusqlite::Error::InvalidParameterName(match requested {
+        Some(_) => "Semester not found".to_string(),
+        None => "Time in is before the first semester".to_string(),
+    }))
 }
 
 // Implement Clone for SqliteAttendanceRepository
@@ -67,9 +487,12 @@ impl AttendanceRepository for SqliteAttendanceRepository {
     }
 
     fn get_all_courses(&self, conn: &Connection) -> Result<Vec<String>> {
+        // Include courses students had in earlier semesters so old visits stay filterable
         let query = "
-            SELECT DISTINCT course 
-            FROM school_accounts 
+            SELECT course FROM school_accounts 
+            WHERE course IS NOT NULL AND course != '' 
+            UNION
+            SELECT course FROM semester_accounts 
             WHERE course IS NOT NULL AND course != '' 
             ORDER BY course ASC
         ";
@@ -87,77 +510,154 @@ impl AttendanceRepository for SqliteAttendanceRepository {
         Ok(courses)
     }
 
+    // Every location a visit was recorded at, including kiosks since moved or revoked
+    fn get_all_locations(&self, conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT location FROM attendance
+             WHERE location IS NOT NULL AND location != ''
+             ORDER BY location ASC"
+        )?;
+
+        let locations = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        locations.collect()
+    }
+
+    fn get_open_attendance(&self, conn: &Connection, school_id: &str) -> Result<Option<Attendance>> {
+        conn.query_row(
+            &format!(
+                "SELECT * FROM attendance 
+                 WHERE school_id = ?1 AND {} 
+                 ORDER BY time_in_date DESC 
+                 LIMIT 1",
+                open_visit_condition(&LibraryTimezone::load(conn)?)
+            ),
+            params![school_id],
+            row_to_attendance,
+        ).optional()
+    }
+
+    fn checkout_attendance(
+        &self,
+        conn: &Connection,
+        school_id: &str,
+        device_id: Option<i64>,
+        location: Option<&str>
+    ) -> Result<Attendance> {
+        let open_attendance = self.get_open_attendance(conn, school_id)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        conn.execute(
+            "UPDATE attendance
+             SET time_out_date = ?1,
+                 device_id = COALESCE(device_id, ?2),
+                 location = COALESCE(location, ?3)
+             WHERE id = ?4",
+            params![Utc::now().timestamp_millis(), device_id, location, open_attendance.id.to_string()],
+        )?;
+
+        self.get_attendance(conn, open_attendance.id)
+    }
+
+    fn scan_attendance(&self, conn: &Connection, attendance: CreateAttendanceRequest) -> Result<ScanOutcome, ScanError> {
+        let classification = attendance.classification.clone()
+            .unwrap_or_else(|| "Visitor".to_string());
+
+        // The cooldown check, open-visit lookup and write run as one unit so two kiosks
+        // scanning the same ID at once cannot both open a visit. IMMEDIATE takes the
+        // write lock up front; a deferred transaction would let both scans read first.
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+
+        // Repeat scans inside the cooldown window are rejected or merged
+        let latest = tx.query_row(
+            "SELECT * FROM attendance WHERE school_id = ?1 AND is_deleted = 0 ORDER BY time_in_date DESC LIMIT 1",
+            params![attendance.school_id],
+            row_to_attendance,
+        ).optional()?;
+
+        if let (Some(latest), Some(cooldown)) = (
+            latest,
+            ScanCooldownDatabase.get_scan_cooldown(&tx, &classification)?
+        ) {
+            let last_scan = latest.time_out_date.unwrap_or(latest.time_in_date);
+            let elapsed = (Utc::now() - last_scan).num_seconds();
+
+            if elapsed < cooldown.cooldown_seconds {
+                return match cooldown.mode {
+                    CooldownMode::Merge => Ok(ScanOutcome::Merged(latest)),
+                    CooldownMode::Reject => Err(ScanError::DuplicateScan {
+                        school_id: attendance.school_id,
+                        retry_after_seconds: cooldown.cooldown_seconds - elapsed,
+                    }),
+                };
+            }
+        }
+
+        // A second scan by the same school_id closes the open visit
+        let outcome = if self.get_open_attendance(&tx, &attendance.school_id)?.is_some() {
+            ScanOutcome::CheckedOut(self.checkout_attendance(
+                &tx,
+                &attendance.school_id,
+                attendance.device_id,
+                attendance.location.as_deref()
+            )?)
+        } else {
+            ScanOutcome::CheckedIn(self.create_attendance(&tx, attendance)?)
+        };
+
+        tx.commit()?;
+        Ok(outcome)
+    }
+
     fn get_filtered_attendances(
         &self, 
         conn: &Connection, 
-        course: Option<String>, 
-        date: Option<DateTime<Utc>>
-    ) -> Result<Vec<Attendance>> {
-        // Base query with flexible filtering
-        let mut query = String::from("
-            SELECT DISTINCT a.* FROM attendance a
-            LEFT JOIN school_accounts sa ON a.school_id = sa.school_id
-            WHERE 1=1
-        ");
-    
-        // Prepare parameters for the query
-        let mut param_conditions = Vec::new();
-        let mut param_values = Vec::new();
-    
-        // Add course filter if specified
-        if let Some(course_name) = course {
-            param_conditions.push("sa.course = ?");
-            param_values.push(course_name);
-        }
-    
-        // Add date filter if specified (exact date match)
-        if let Some(filter_date) = date {
-            // Match the entire day
-            param_conditions.push("date(a.time_in_date) = date(?)");
-            param_values.push(filter_date.to_rfc3339());
-        }
-    
-        // Add conditions to query if any
-        if !param_conditions.is_empty() {
-            query.push_str(" AND ");
-            query.push_str(&param_conditions.join(" AND "));
-        }
-    
-        // Add ordering
-        query.push_str(" ORDER BY a.time_in_date DESC");
-    
-        // Prepare the statement with dynamic parameters
+        filter: &AttendanceFilter,
+        sort: AttendanceSort,
+        page: u64,
+        page_size: u64
+    ) -> Result<PaginatedAttendances> {
+        let page = page.max(1);
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        let offset = (page - 1) * page_size;
+
+        let (conditions, mut values) = filter.to_sql(&LibraryTimezone::load(conn)?);
+
+        // Count total records matching the filter
+        let total_count: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE 1=1{}", ATTENDANCE_WITH_ACCOUNT, conditions),
+            rusqlite::params_from_iter(values.iter()),
+            |row| row.get(0)
+        )?;
+
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as u64;
+
+        // Fetch the requested page
+        let query = format!(
+            "SELECT a.* FROM {} WHERE 1=1{} {} LIMIT ? OFFSET ?",
+            ATTENDANCE_WITH_ACCOUNT,
+            conditions,
+            sort.to_sql()
+        );
+        values.push(Value::Integer(page_size as i64));
+        values.push(Value::Integer(offset as i64));
+
         let mut stmt = conn.prepare(&query)?;
-        
-        let attendance_iter = stmt.query_map(rusqlite::params_from_iter(param_values.iter().map(|v| v.as_str())), |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-    
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
-    
+        let attendance_iter = stmt.query_map(rusqlite::params_from_iter(values.iter()), row_to_attendance)?;
+
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
             attendances.push(attendance?);
         }
-    
-        Ok(attendances)
+
+        Ok(PaginatedAttendances {
+            attendances,
+            total_count,
+            page,
+            page_size,
+            total_pages,
+        })
     }
     
-    
     fn create_attendance(&self, conn: &Connection, attendance: CreateAttendanceRequest) -> Result<Attendance> {
         if attendance.school_id.is_empty() {
             let err = rusqlite::Error::InvalidParameterName("School ID cannot be empty".to_string());
@@ -165,48 +665,33 @@ impl AttendanceRepository for SqliteAttendanceRepository {
         }
         
         // Only get the full name from database if not provided
-        let full_name = match conn.query_row(
-            "SELECT 
-                COALESCE(
-                    CASE 
-                        WHEN first_name IS NOT NULL AND middle_name IS NOT NULL AND last_name IS NOT NULL THEN 
-                            first_name || ' ' || middle_name || ' ' || last_name
-                        WHEN first_name IS NOT NULL AND last_name IS NOT NULL THEN 
-                            first_name || ' ' || last_name
-                        ELSE first_name
-                    END, 
-                    ?1
-                ) as computed_full_name
-            FROM school_accounts 
-            WHERE school_id = ?2",
-            params![
-                attendance.full_name, 
-                attendance.school_id
-            ],
-            |row| row.get::<_, String>(0)
-        ) {
-            Ok(name) => name,
-            Err(_) => attendance.full_name.clone()
-        };
+        let full_name = resolve_full_name(conn, &attendance.school_id, &attendance.full_name);
         
         let id = Uuid::new_v4();
         let time_in_date = Utc::now();
-        let time_in_str = time_in_date.to_rfc3339();
         
         // Use the classification provided by the frontend, with "Visitor" as fallback
         let classification = attendance.classification.unwrap_or_else(|| "Visitor".to_string());
+
+        // Stamp the visit with the semester that is active when it happens
+        let semester_id = SqliteSemesterRepository.get_active_semester(conn)?
+            .map(|semester| semester.id);
         
         conn.execute(
             "INSERT INTO attendance (
-                id, school_id, full_name, time_in_date, classification, purpose_label
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                id, school_id, full_name, time_in_date, classification, purpose_label, semester_id,
+                device_id, location
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 id.to_string(),
                 attendance.school_id,
                 full_name,
-                time_in_str,
+                time_in_date.timestamp_millis(),
                 classification,
-                attendance.purpose_label
+                attendance.purpose_label,
+                semester_id.map(|id| id.to_string()),
+                attendance.device_id,
+                attendance.location
             ],
         )?;
         
@@ -217,36 +702,86 @@ impl AttendanceRepository for SqliteAttendanceRepository {
             time_in_date,
             classification,
             purpose_label: attendance.purpose_label,
+            time_out_date: None,
+            semester_id,
+            is_manual: false,
+            entered_by: None,
+            is_deleted: false,
+            device_id: attendance.device_id,
+            location: attendance.location,
         };
         
         Ok(created_attendance)
     }
 
+    fn create_manual_attendance(
+        &self,
+        conn: &Connection,
+        attendance: ManualAttendanceRequest,
+        entered_by: &str
+    ) -> Result<Attendance> {
+        if attendance.school_id.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("School ID cannot be empty".to_string()));
+        }
+
+        let now = Utc::now();
+        if attendance.time_in_date > now {
+            return Err(rusqlite::Error::InvalidParameterName("Time in cannot be in the future".to_string()));
+        }
+
+        if let Some(time_out_date) = attendance.time_out_date {
+            if time_out_date <= attendance.time_in_date {
+                return Err(rusqlite::Error::InvalidParameterName("Time out must be after time in".to_string()));
+            }
+            if time_out_date > now {
+                return Err(rusqlite::Error::InvalidParameterName("Time out cannot be in the future".to_string()));
+            }
+        }
+
+        let semester_id = semester_for_visit(conn, attendance.semester_id, attendance.time_in_date)?;
+        let full_name = resolve_full_name(conn, &attendance.school_id, &attendance.full_name);
+        let classification = attendance.classification.unwrap_or_else(|| "Visitor".to_string());
+        let id = Uuid::new_v4();
+
+        conn.execute(
+            "INSERT INTO attendance (
+                id, school_id, full_name, time_in_date, classification, purpose_label,
+                time_out_date, semester_id, is_manual, entered_by
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, ?9)",
+            params![
+                id.to_string(),
+                attendance.school_id,
+                full_name,
+                attendance.time_in_date.timestamp_millis(),
+                classification,
+                attendance.purpose_label,
+                attendance.time_out_date.map(|time_out| time_out.timestamp_millis()),
+                semester_id.map(|id| id.to_string()),
+                entered_by
+            ],
+        )?;
+
+        let created = self.get_attendance(conn, id)?;
+        SqliteAttendanceHistoryRepository.record_change(
+            conn,
+            AttendanceAction::Created,
+            None,
+            Some(&created),
+            entered_by,
+            Some("Manual entry")
+        )?;
+
+        info!("Manual attendance for {} entered by {}", created.school_id, entered_by);
+        Ok(created)
+    }
+
     
 
     fn get_attendance(&self, conn: &Connection, id: Uuid) -> Result<Attendance> {
         let attendance = conn.query_row(
             "SELECT * FROM attendance WHERE id = ?1",
             params![id.to_string()],
-            |row| {
-                let time_in_str: String = row.get(3)?;
-                let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        rusqlite::types::Type::Text,
-                        Box::new(e)
-                    ))?;
-
-                Ok(Attendance {
-                    id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                    school_id: row.get(1)?,
-                    full_name: row.get(2)?,
-                    time_in_date,
-                    classification: row.get(4)?,
-                    purpose_label: row.get(5)?,
-                })
-            },
+            row_to_attendance,
         )?;
 
         Ok(attendance)
@@ -254,60 +789,24 @@ impl AttendanceRepository for SqliteAttendanceRepository {
 
     fn get_last_n_attendances(&self, conn: &Connection, n: usize) -> Result<Vec<Attendance>, rusqlite::Error> {
         let query = "
-            SELECT id, school_id, full_name, time_in_date, classification, purpose_label
-            FROM attendance 
+            SELECT * FROM attendance 
+            WHERE is_deleted = 0
             ORDER BY time_in_date DESC 
             LIMIT ?
         ";
         
         let mut stmt = conn.prepare(query)?;
-        let attendance_iter = stmt.query_map([n], |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-    
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
+        let attendance_iter = stmt.query_map([n], row_to_attendance)?;
     
         attendance_iter.collect::<Result<Vec<Attendance>, _>>()
     }
 
     fn get_attendances_by_school_id(&self, conn: &Connection, school_id: &str) -> Result<Vec<Attendance>> {
         let mut stmt = conn.prepare(
-            "SELECT * FROM attendance WHERE school_id = ?1 ORDER BY time_in_date DESC"
+            "SELECT * FROM attendance WHERE school_id = ?1 AND is_deleted = 0 ORDER BY time_in_date DESC"
         )?;
         
-        let attendance_iter = stmt.query_map(params![school_id], |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
+        let attendance_iter = stmt.query_map(params![school_id], row_to_attendance)?;
 
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
@@ -320,30 +819,11 @@ impl AttendanceRepository for SqliteAttendanceRepository {
     fn get_attendances_by_semester(&self, conn: &Connection, semester_id: Uuid) -> Result<Vec<Attendance>> {
         let mut stmt = conn.prepare(
             "SELECT * FROM attendance 
-             JOIN semester_accounts ON attendance.school_id = semester_accounts.school_id 
-             WHERE semester_accounts.semester_id = ?1 
-             ORDER BY attendance.time_in_date DESC"
+             WHERE semester_id = ?1 AND is_deleted = 0
+             ORDER BY time_in_date DESC"
         )?;
         
-        let attendance_iter = stmt.query_map(params![semester_id.to_string()], |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
+        let attendance_iter = stmt.query_map(params![semester_id.to_string()], row_to_attendance)?;
 
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
@@ -359,29 +839,11 @@ impl AttendanceRepository for SqliteAttendanceRepository {
              WHERE school_id = (
                  SELECT school_id FROM school_accounts 
                  WHERE id = ?1
-             ) 
+             ) AND is_deleted = 0
              ORDER BY time_in_date DESC"
         )?;
         
-        let attendance_iter = stmt.query_map(params![school_account_id.to_string()], |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
+        let attendance_iter = stmt.query_map(params![school_account_id.to_string()], row_to_attendance)?;
 
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
@@ -391,39 +853,65 @@ impl AttendanceRepository for SqliteAttendanceRepository {
         Ok(attendances)
     }
 
-    fn delete_attendance(&self, conn: &Connection, id: Uuid) -> Result<()> {
-        conn.execute(
-            "DELETE FROM attendance WHERE id = ?1",
+    // Hides the record instead of removing it, so it can be restored
+    fn delete_attendance(&self, conn: &Connection, id: Uuid, deleted_by: &str, reason: Option<&str>) -> Result<()> {
+        let before = self.get_attendance(conn, id)?;
+        if before.is_deleted {
+            return Err(rusqlite::Error::InvalidParameterName("Attendance record is already deleted".to_string()));
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE attendance SET is_deleted = 1 WHERE id = ?1",
             params![id.to_string()],
         )?;
+        let after = self.get_attendance(&tx, id)?;
+        SqliteAttendanceHistoryRepository.record_change(
+            &tx,
+            AttendanceAction::Deleted,
+            Some(&before),
+            Some(&after),
+            deleted_by,
+            reason
+        )?;
+        tx.commit()?;
 
+        info!("Attendance {} deleted by {}", id, deleted_by);
         Ok(())
     }
 
+    fn restore_attendance(&self, conn: &Connection, id: Uuid, restored_by: &str, reason: Option<&str>) -> Result<Attendance> {
+        let before = self.get_attendance(conn, id)?;
+        if !before.is_deleted {
+            return Err(rusqlite::Error::InvalidParameterName("Attendance record is not deleted".to_string()));
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE attendance SET is_deleted = 0 WHERE id = ?1",
+            params![id.to_string()],
+        )?;
+        let after = self.get_attendance(&tx, id)?;
+        SqliteAttendanceHistoryRepository.record_change(
+            &tx,
+            AttendanceAction::Restored,
+            Some(&before),
+            Some(&after),
+            restored_by,
+            reason
+        )?;
+        tx.commit()?;
+
+        info!("Attendance {} restored by {}", id, restored_by);
+        Ok(after)
+    }
+
     fn get_all_attendances(&self, conn: &Connection) -> Result<Vec<Attendance>> {
         let mut stmt = conn.prepare(
-            "SELECT * FROM attendance ORDER BY time_in_date DESC"
+            "SELECT * FROM attendance WHERE is_deleted = 0 ORDER BY time_in_date DESC"
         )?;
         
-        let attendance_iter = stmt.query_map([], |row| {
-            let time_in_str: String = row.get(3)?;
-            let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(e)
-                ))?;
-
-            Ok(Attendance {
-                id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                school_id: row.get(1)?,
-                full_name: row.get(2)?,
-                time_in_date,
-                classification: row.get(4)?,
-                purpose_label: row.get(5)?,
-            })
-        })?;
+        let attendance_iter = stmt.query_map([], row_to_attendance)?;
 
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
@@ -433,7 +921,19 @@ impl AttendanceRepository for SqliteAttendanceRepository {
         Ok(attendances)
     }
 
-    fn update_attendance(&self, conn: &Connection, id: Uuid, attendance: UpdateAttendanceRequest) -> Result<Attendance> {
+    fn update_attendance(
+        &self,
+        conn: &Connection,
+        id: Uuid,
+        attendance: UpdateAttendanceRequest,
+        updated_by: &str,
+        reason: Option<&str>
+    ) -> Result<Attendance> {
+        let before = self.get_attendance(conn, id)?;
+        if before.is_deleted {
+            return Err(rusqlite::Error::InvalidParameterName("Deleted attendance records cannot be edited".to_string()));
+        }
+
         let mut update_parts = Vec::new();
         let mut params_values: Vec<String> = Vec::new();
         let mut param_count = 1;
@@ -458,7 +958,7 @@ impl AttendanceRepository for SqliteAttendanceRepository {
     
         if update_parts.is_empty() {
             // If no updates are provided, return the existing record
-            return self.get_attendance(conn, id);
+            return Ok(before);
         }
     
         let sql = format!(
@@ -469,48 +969,41 @@ impl AttendanceRepository for SqliteAttendanceRepository {
     
         params_values.push(id.to_string());
     
+        let tx = conn.unchecked_transaction()?;
+
         // Use the params! macro to create parameters
-        let result = conn.execute(
+        tx.execute(
             &sql, 
             rusqlite::params_from_iter(params_values.iter().map(|v| v.as_str()))
         )?;
     
-        // Retrieve and return the updated record
-        self.get_attendance(conn, id)
+        // Record the change next to the updated record
+        let after = self.get_attendance(&tx, id)?;
+        SqliteAttendanceHistoryRepository.record_change(
+            &tx,
+            AttendanceAction::Updated,
+            Some(&before),
+            Some(&after),
+            updated_by,
+            reason
+        )?;
+        tx.commit()?;
+
+        Ok(after)
     }
 
     fn search_attendances(&self, conn: &Connection, query: &str) -> Result<Vec<Attendance>> {
-        let sql = "SELECT * FROM attendance 
-                   WHERE school_id LIKE ? OR 
-                         full_name LIKE ? OR
-                         purpose_label LIKE ?
-                   ORDER BY time_in_date DESC";
-        
-        let search_pattern = format!("%{}%", query);
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = "SELECT a.* FROM attendance_fts
+                   JOIN attendance a ON a.id = attendance_fts.id
+                   WHERE attendance_fts MATCH ?1 AND a.is_deleted = 0
+                   ORDER BY bm25(attendance_fts), a.time_in_date DESC";
         
         let mut stmt = conn.prepare(sql)?;
-        let attendance_iter = stmt.query_map(
-            params![&search_pattern, &search_pattern, &search_pattern],
-            |row| {
-                let time_in_str: String = row.get(3)?;
-                let time_in_date = DateTime::parse_from_rfc3339(&time_in_str)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
-                        3,
-                        rusqlite::types::Type::Text,
-                        Box::new(e)
-                    ))?;
-    
-                Ok(Attendance {
-                    id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
-                    school_id: row.get(1)?,
-                    full_name: row.get(2)?,
-                    time_in_date,
-                    classification: row.get(4)?,
-                    purpose_label: row.get(5)?, // Use purpose_label instead of purpose_id
-                })
-            }
-        )?;
+        let attendance_iter = stmt.query_map(params![fts_query], row_to_attendance)?;
     
         let mut attendances = Vec::new();
         for attendance in attendance_iter {
@@ -521,18 +1014,424 @@ impl AttendanceRepository for SqliteAttendanceRepository {
     }
 }
 
-pub fn create_attendance_table(conn: &Connection) -> Result<()> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS attendance (
-            id TEXT PRIMARY KEY,
-            school_id TEXT NOT NULL,
-            full_name TEXT NOT NULL,
-            time_in_date TEXT NOT NULL,
-            classification TEXT NOT NULL,
-            purpose_label TEXT
-        )",
+// Set once rows from before semester_id existed have been given a semester
+const SEMESTER_BACKFILL_KEY: &str = "attendance.semester_backfill";
+
+const CREATE_ATTENDANCE_TABLE: &str =
+    "CREATE TABLE IF NOT EXISTS attendance (
+        id TEXT PRIMARY KEY,
+        school_id TEXT NOT NULL,
+        full_name TEXT NOT NULL,
+        time_in_date INTEGER NOT NULL,
+        classification TEXT NOT NULL,
+        purpose_label TEXT,
+        time_out_date INTEGER,
+        semester_id TEXT,
+        is_manual INTEGER NOT NULL DEFAULT 0,
+        entered_by TEXT,
+        is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+        device_id INTEGER,
+        location TEXT
+    )";
+
+// Rebuilds a table that still stores RFC3339 text timestamps so they become
+// epoch milliseconds, which sort and range-scan on an index
+fn migrate_text_timestamps(conn: &Connection) -> Result<()> {
+    let time_in_type: String = conn.query_row(
+        "SELECT type FROM pragma_table_info('attendance') WHERE name = 'time_in_date'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !time_in_type.eq_ignore_ascii_case("TEXT") {
+        return Ok(());
+    }
+
+    info!("Migrating attendance timestamps to epoch milliseconds");
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(&CREATE_ATTENDANCE_TABLE.replace("attendance (", "attendance_migrated ("), [])?;
+
+    // Every column is copied as it is, so none can be left behind, and the two
+    // timestamps are then converted in place
+    let column_names = |table: &str| -> Result<Vec<String>> {
+        tx.prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+            .query_map([], |row| row.get(0))?
+            .collect()
+    };
+    let columns = column_names("attendance_migrated")?;
+    if column_names("attendance")?.len() != columns.len() {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "The attendance table has columns the migration does not know; not migrating it".to_string()
+        ));
+    }
+    tx.execute(
+        &format!(
+            "INSERT INTO attendance_migrated ({columns}) SELECT {columns} FROM attendance",
+            columns = columns.join(", ")
+        ),
+        [],
+    )?;
+
+    let migrated = {
+        let timestamps = tx
+            .prepare("SELECT id, time_in_date, time_out_date FROM attendance_migrated")?
+            .query_map([], |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            )))?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut update = tx.prepare(
+            "UPDATE attendance_migrated SET time_in_date = ?1, time_out_date = ?2 WHERE id = ?3"
+        )?;
+        for (id, time_in, time_out) in &timestamps {
+            let time_in = parse_timestamp(time_in, 1)?;
+            let time_out = time_out.as_deref()
+                .map(|value| parse_timestamp(value, 2))
+                .transpose()?;
+
+            update.execute(params![
+                time_in.timestamp_millis(),
+                time_out.map(|time_out| time_out.timestamp_millis()),
+                id
+            ])?;
+        }
+        timestamps.len()
+    };
+
+    tx.execute_batch(
+        "DROP TABLE attendance;
+         ALTER TABLE attendance_migrated RENAME TO attendance;"
+    )?;
+    tx.commit()?;
+
+    info!("Migrated timestamps of {} attendance rows", migrated);
+    Ok(())
+}
+
+// Rows recorded before semester_id existed belong to the latest semester created
+// on or before the visit. This runs once; visits recorded later while no semester
+// is active are left without one.
+fn backfill_semesters(conn: &Connection) -> Result<()> {
+    // Runs before the other tables are set up on a new database
+    let settings = AppSettingsDatabase::init(conn)?;
+    if settings.get_setting(conn, SEMESTER_BACKFILL_KEY)?.is_some() {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let backfilled = tx.execute(
+        "UPDATE attendance SET semester_id = (
+            SELECT s.id FROM semesters s
+            WHERE datetime(s.created_at) <= datetime(attendance.time_in_date / 1000, 'unixepoch')
+            ORDER BY datetime(s.created_at) DESC
+            LIMIT 1
+         )
+         WHERE semester_id IS NULL
+           AND EXISTS (
+               SELECT 1 FROM semesters s
+               WHERE datetime(s.created_at) <= datetime(attendance.time_in_date / 1000, 'unixepoch')
+           )",
         [],
     )?;
+    settings.set_setting(&tx, SEMESTER_BACKFILL_KEY, "done")?;
+    tx.commit()?;
 
+    if backfilled > 0 {
+        info!("Backfilled semester for {} attendance rows", backfilled);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn create_attendance_table(conn: &Connection) -> Result<()> {
+    conn.execute(CREATE_ATTENDANCE_TABLE, [])?;
+
+    add_column_if_missing(conn, "attendance", "time_out_date", "INTEGER")?;
+    add_column_if_missing(conn, "attendance", "semester_id", "TEXT")?;
+    add_column_if_missing(conn, "attendance", "is_manual", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "attendance", "entered_by", "TEXT")?;
+    add_column_if_missing(conn, "attendance", "is_deleted", "BOOLEAN NOT NULL DEFAULT FALSE")?;
+    add_column_if_missing(conn, "attendance", "device_id", "INTEGER")?;
+    add_column_if_missing(conn, "attendance", "location", "TEXT")?;
+    migrate_text_timestamps(conn)?;
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_attendance_time_in ON attendance (time_in_date);
+         CREATE INDEX IF NOT EXISTS idx_attendance_school_id ON attendance (school_id, time_in_date);
+         CREATE INDEX IF NOT EXISTS idx_attendance_semester ON attendance (semester_id, time_in_date);
+         CREATE INDEX IF NOT EXISTS idx_attendance_location ON attendance (location, time_in_date);"
+    )?;
+
+    backfill_semesters(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::scan_cooldown::{ScanCooldown, DEFAULT_COOLDOWN_CLASSIFICATION};
+    use crate::db::school_accounts::create_school_accounts_table;
+    use crate::db::semester::create_semesters_table;
+    use crate::db::semester_accounts::create_semester_accounts_table;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_school_accounts_table(&conn).unwrap();
+        create_semesters_table(&conn).unwrap();
+        create_semester_accounts_table(&conn).unwrap();
+        create_attendance_table(&conn).unwrap();
+        ScanCooldownDatabase::init(&conn).unwrap();
+        AppSettingsDatabase::init(&conn).unwrap();
+        conn
+    }
+
+    fn scan(conn: &Connection, school_id: &str) -> Result<ScanOutcome, ScanError> {
+        SqliteAttendanceRepository.scan_attendance(conn, CreateAttendanceRequest {
+            school_id: school_id.to_string(),
+            full_name: "Juan Dela Cruz".to_string(),
+            classification: Some("Student".to_string()),
+            purpose_label: None,
+            device_id: None,
+            location: None,
+        })
+    }
+
+    fn set_cooldown(conn: &Connection, classification: &str, cooldown_seconds: i64, mode: CooldownMode) {
+        let cooldown = ScanCooldown { classification: classification.to_string(), cooldown_seconds, mode };
+        ScanCooldownDatabase.set_scan_cooldown(conn, cooldown).unwrap();
+    }
+
+    // Moves every visit back in time, as if the scans happened that long ago
+    fn age_visits(conn: &Connection, seconds: i64) {
+        conn.execute(
+            "UPDATE attendance SET time_in_date = time_in_date - ?1,
+                                   time_out_date = time_out_date - ?1",
+            params![seconds * 1000],
+        ).unwrap();
+    }
+
+    fn visit_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM attendance", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn scans_alternate_check_in_and_check_out_without_a_cooldown() {
+        // No cooldown is configured until an admin sets one
+        let conn = setup();
+        assert!(ScanCooldownDatabase.get_scan_cooldown(&conn, "Student").unwrap().is_none());
+
+        let checked_in = match scan(&conn, "2024-0001").unwrap() {
+            ScanOutcome::CheckedIn(attendance) => attendance,
+            outcome => panic!("expected a check-in, got {:?}", outcome),
+        };
+        assert!(checked_in.time_out_date.is_none());
+
+        match scan(&conn, "2024-0001").unwrap() {
+            ScanOutcome::CheckedOut(attendance) => {
+                assert_eq!(attendance.id, checked_in.id);
+                assert!(attendance.time_out_date.is_some());
+            }
+            outcome => panic!("expected a check-out, got {:?}", outcome),
+        }
+
+        assert!(matches!(scan(&conn, "2024-0001").unwrap(), ScanOutcome::CheckedIn(_)));
+        assert_eq!(visit_count(&conn), 2);
+    }
+
+    #[test]
+    fn reject_cooldown_refuses_repeat_scans_inside_the_window() {
+        let conn = setup();
+        set_cooldown(&conn, DEFAULT_COOLDOWN_CLASSIFICATION, 60, CooldownMode::Reject);
+        scan(&conn, "2024-0001").unwrap();
+
+        match scan(&conn, "2024-0001") {
+            Err(ScanError::DuplicateScan { school_id, retry_after_seconds }) => {
+                assert_eq!(school_id, "2024-0001");
+                assert!(retry_after_seconds > 0 && retry_after_seconds <= 60);
+            }
+            outcome => panic!("expected a duplicate scan, got {:?}", outcome),
+        }
+        // Other IDs are not held back
+        assert!(matches!(scan(&conn, "2024-0002").unwrap(), ScanOutcome::CheckedIn(_)));
+
+        age_visits(&conn, 61);
+        assert!(matches!(scan(&conn, "2024-0001").unwrap(), ScanOutcome::CheckedOut(_)));
+    }
+
+    #[test]
+    fn merge_cooldown_returns_the_existing_visit() {
+        let conn = setup();
+        set_cooldown(&conn, DEFAULT_COOLDOWN_CLASSIFICATION, 60, CooldownMode::Merge);
+        let checked_in = scan(&conn, "2024-0001").unwrap().into_attendance();
+
+        match scan(&conn, "2024-0001").unwrap() {
+            ScanOutcome::Merged(attendance) => {
+                assert_eq!(attendance.id, checked_in.id);
+                assert!(attendance.time_out_date.is_none());
+            }
+            outcome => panic!("expected a merged scan, got {:?}", outcome),
+        }
+        assert_eq!(visit_count(&conn), 1);
+    }
+
+    #[test]
+    fn cooldown_window_starts_at_the_check_out() {
+        let conn = setup();
+        scan(&conn, "2024-0001").unwrap();
+        age_visits(&conn, 3600);
+        scan(&conn, "2024-0001").unwrap();
+        set_cooldown(&conn, DEFAULT_COOLDOWN_CLASSIFICATION, 60, CooldownMode::Reject);
+
+        // The visit began an hour ago but ended just now
+        assert!(matches!(scan(&conn, "2024-0001"), Err(ScanError::DuplicateScan { .. })));
+    }
+
+    #[test]
+    fn classification_cooldown_overrides_the_default() {
+        let conn = setup();
+        set_cooldown(&conn, DEFAULT_COOLDOWN_CLASSIFICATION, 60, CooldownMode::Reject);
+        set_cooldown(&conn, "Student", 0, CooldownMode::Reject);
+        scan(&conn, "2024-0001").unwrap();
+
+        assert!(matches!(scan(&conn, "2024-0001").unwrap(), ScanOutcome::CheckedOut(_)));
+    }
+
+    #[test]
+    fn kiosk_checkout_keeps_the_check_in_gate_and_fills_a_missing_one() {
+        let conn = setup();
+        let at_gate = |school_id: &str, device_id: i64, location: &str| CreateAttendanceRequest {
+            school_id: school_id.to_string(),
+            full_name: "Juan Dela Cruz".to_string(),
+            classification: Some("Student".to_string()),
+            purpose_label: None,
+            device_id: Some(device_id),
+            location: Some(location.to_string()),
+        };
+        let repo = SqliteAttendanceRepository;
+
+        repo.scan_attendance(&conn, at_gate("2024-0001", 1, "North gate")).unwrap();
+        let checked_out = repo.scan_attendance(&conn, at_gate("2024-0001", 2, "South gate")).unwrap().into_attendance();
+        assert_eq!(checked_out.device_id, Some(1));
+        assert_eq!(checked_out.location.as_deref(), Some("North gate"));
+
+        // Checked in at the desk, so the kiosk it leaves through is all that is known
+        scan(&conn, "2024-0002").unwrap();
+        let checked_out = repo.checkout_attendance(&conn, "2024-0002", Some(2), Some("South gate")).unwrap();
+        assert_eq!(checked_out.device_id, Some(2));
+        assert_eq!(checked_out.location.as_deref(), Some("South gate"));
+    }
+
+    #[test]
+    fn text_timestamps_migrate_to_epoch_milliseconds_with_every_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_school_accounts_table(&conn).unwrap();
+        create_semesters_table(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE attendance (
+                id TEXT PRIMARY KEY,
+                school_id TEXT NOT NULL,
+                full_name TEXT NOT NULL,
+                time_in_date TEXT NOT NULL,
+                classification TEXT NOT NULL,
+                purpose_label TEXT,
+                time_out_date TEXT,
+                semester_id TEXT,
+                is_manual INTEGER NOT NULL DEFAULT 0,
+                entered_by TEXT,
+                is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+                device_id INTEGER,
+                location TEXT
+            );
+
+            INSERT INTO attendance VALUES (
+                '8c5e8f1e-2b7a-4f43-9d1e-3a6f0f6f9b11', '2024-0001', 'Juan Dela Cruz',
+                '2024-03-01T08:30:00+08:00', 'Student', 'Study', '2024-03-01T10:00:00Z',
+                NULL, 1, 'librarian', FALSE, 7, 'North gate'
+            );
+            INSERT INTO attendance (id, school_id, full_name, time_in_date, classification)
+            VALUES ('0b0f5a52-4c1d-4a8e-9f0e-0d1f3c2b5a66', '2024-0002', 'Maria Clara',
+                    '2024-03-01T09:00:00Z', 'Faculty');"
+        ).unwrap();
+
+        create_attendance_table(&conn).unwrap();
+
+        let time_in_type: String = conn.query_row(
+            "SELECT type FROM pragma_table_info('attendance') WHERE name = 'time_in_date'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(time_in_type, "INTEGER");
+
+        let attendances = SqliteAttendanceRepository.get_all_attendances(&conn).unwrap();
+        assert_eq!(attendances.len(), 2);
+        let migrated = attendances.iter().find(|attendance| attendance.school_id == "2024-0001").unwrap();
+        assert_eq!(migrated.time_in_date, DateTime::parse_from_rfc3339("2024-03-01T00:30:00Z").unwrap());
+        assert_eq!(migrated.time_out_date, Some(DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z").unwrap().with_timezone(&Utc)));
+        assert_eq!(migrated.full_name, "Juan Dela Cruz");
+        assert_eq!(migrated.purpose_label.as_deref(), Some("Study"));
+        assert!(migrated.is_manual);
+        assert_eq!(migrated.entered_by.as_deref(), Some("librarian"));
+        assert_eq!(migrated.device_id, Some(7));
+        assert_eq!(migrated.location.as_deref(), Some("North gate"));
+
+        let open = attendances.iter().find(|attendance| attendance.school_id == "2024-0002").unwrap();
+        assert!(open.time_out_date.is_none());
+        assert!(!open.is_manual);
+    }
+
+    #[test]
+    fn semesters_are_backfilled_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_school_accounts_table(&conn).unwrap();
+        create_semesters_table(&conn).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE attendance (
+                id TEXT PRIMARY KEY,
+                school_id TEXT NOT NULL,
+                full_name TEXT NOT NULL,
+                time_in_date INTEGER NOT NULL,
+                classification TEXT NOT NULL,
+                purpose_label TEXT
+            );
+
+            INSERT INTO semesters (id, label, is_active, created_at, updated_at)
+            VALUES ('5f0c1a52-4c1d-4a8e-9f0e-0d1f3c2b5a66', '2023-2024 2nd', 0,
+                    '2024-01-08T00:00:00Z', '2024-01-08T00:00:00Z');
+
+            INSERT INTO attendance (id, school_id, full_name, time_in_date, classification)
+            VALUES ('8c5e8f1e-2b7a-4f43-9d1e-3a6f0f6f9b11', '2024-0001', 'Juan Dela Cruz', 1709251200000, 'Student');"
+        ).unwrap();
+
+        create_attendance_table(&conn).unwrap();
+        let semester_id: Option<String> = conn.query_row("SELECT semester_id FROM attendance", [], |row| row.get(0)).unwrap();
+        assert_eq!(semester_id.as_deref(), Some("5f0c1a52-4c1d-4a8e-9f0e-0d1f3c2b5a66"));
+
+        // Recorded while no semester was active, so it stays without one
+        conn.execute(
+            "INSERT INTO attendance (id, school_id, full_name, time_in_date, classification)
+             VALUES ('0b0f5a52-4c1d-4a8e-9f0e-0d1f3c2b5a66', '2024-0002', 'Maria Clara', 1709337600000, 'Student')",
+            [],
+        ).unwrap();
+        create_attendance_table(&conn).unwrap();
+        let unassigned: i64 = conn.query_row("SELECT COUNT(*) FROM attendance WHERE semester_id IS NULL", [], |row| row.get(0)).unwrap();
+        assert_eq!(unassigned, 1);
+    }
+
+    #[test]
+    fn malformed_semester_id_fails_the_query() {
+        let conn = setup();
+        scan(&conn, "2024-0001").unwrap();
+        conn.execute("UPDATE attendance SET semester_id = 'not-a-uuid'", []).unwrap();
+
+        assert!(SqliteAttendanceRepository.get_all_attendances(&conn).is_err());
+    }
+
+    #[test]
+    fn page_size_is_capped() {
+        let conn = setup();
+        let page = SqliteAttendanceRepository
+            .get_filtered_attendances(&conn, &AttendanceFilter::default(), AttendanceSort::default(), 1, 1_000_000)
+            .unwrap();
+
+        assert_eq!(page.page_size, MAX_PAGE_SIZE);
+    }
+}
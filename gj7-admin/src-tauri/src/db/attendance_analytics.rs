@@ -0,0 +1,230 @@
+// src/db/attendance_analytics.rs
+
+use rusqlite::{params_from_iter, Connection, Result};
+use rusqlite::types::Value;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::db::attendance::{ACCOUNT_COURSE, ATTENDANCE_WITH_ACCOUNT};
+use crate::db::library_timezone::LibraryTimezone;
+
+const WEEKDAY_LABELS: [&str; 7] = [
+    "Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum AnalyticsDimension {
+    HourOfDay,
+    Weekday,
+    Purpose,
+    Classification,
+    Course,
+    Location,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SeriesPoint {
+    pub label: String,
+    pub value: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChartSeries {
+    pub dimension: AnalyticsDimension,
+    pub points: Vec<SeriesPoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAnalytics {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub semester_id: Option<Uuid>,
+    pub total_visits: u64,
+    pub unique_visitors: u64,
+    pub average_visit_minutes: Option<f64>,
+    pub by_hour: ChartSeries,
+    pub by_weekday: ChartSeries,
+    pub by_purpose: ChartSeries,
+    pub by_classification: ChartSeries,
+    pub by_course: ChartSeries,
+    pub by_location: ChartSeries,
+}
+
+pub trait AttendanceAnalyticsRepository: Send + Sync {
+    fn get_series(
+        &self,
+        conn: &Connection,
+        dimension: AnalyticsDimension,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        semester_id: Option<Uuid>
+    ) -> Result<ChartSeries>;
+    fn get_analytics(
+        &self,
+        conn: &Connection,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        semester_id: Option<Uuid>
+    ) -> Result<AttendanceAnalytics>;
+}
+
+pub struct SqliteAttendanceAnalyticsRepository;
+
+impl SqliteAttendanceAnalyticsRepository {
+    // Conditions limiting visits to [from, to) and, when given, one semester
+    fn scope(from: DateTime<Utc>, to: DateTime<Utc>, semester_id: Option<Uuid>) -> (String, Vec<Value>) {
+        let mut conditions = String::from(
+            "a.is_deleted = 0 AND a.time_in_date >= ? AND a.time_in_date < ?"
+        );
+        let mut values = vec![Value::Integer(from.timestamp_millis()), Value::Integer(to.timestamp_millis())];
+
+        if let Some(semester_id) = semester_id {
+            conditions.push_str(" AND a.semester_id = ?");
+            values.push(Value::Text(semester_id.to_string()));
+        }
+
+        (conditions, values)
+    }
+
+    // Counts visits in scope grouped by the given SQL expression
+    fn count_by(
+        &self,
+        conn: &Connection,
+        group_expr: &str,
+        order_by: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        semester_id: Option<Uuid>
+    ) -> Result<Vec<(String, u64)>> {
+        let (conditions, values) = Self::scope(from, to, semester_id);
+        let query = format!(
+            "SELECT {} AS bucket, COUNT(*) AS visits
+             FROM {}
+             WHERE {}
+             GROUP BY bucket
+             ORDER BY {}",
+            group_expr, ATTENDANCE_WITH_ACCOUNT, conditions, order_by
+        );
+
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+        })?;
+
+        rows.collect()
+    }
+
+    // Spreads counts over a fixed set of buckets so charts always get every label
+    fn fixed_buckets(counts: Vec<(String, u64)>, labels: Vec<String>) -> Vec<SeriesPoint> {
+        let mut values = vec![0u64; labels.len()];
+        for (bucket, count) in counts {
+            if let Ok(index) = bucket.parse::<usize>() {
+                if index < values.len() {
+                    values[index] = count;
+                }
+            }
+        }
+
+        labels.into_iter()
+            .zip(values)
+            .map(|(label, value)| SeriesPoint { label, value })
+            .collect()
+    }
+}
+
+impl AttendanceAnalyticsRepository for SqliteAttendanceAnalyticsRepository {
+    fn get_series(
+        &self,
+        conn: &Connection,
+        dimension: AnalyticsDimension,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        semester_id: Option<Uuid>
+    ) -> Result<ChartSeries> {
+        let points = match dimension {
+            AnalyticsDimension::HourOfDay => {
+                // Hours and weekdays are read off the library's clock
+                let hour_expr = format!("strftime('%H', {})", LibraryTimezone::load(conn)?.sql_local_time("a.time_in_date"));
+                let counts = self.count_by(conn, &hour_expr, "bucket", from, to, semester_id)?;
+                let labels = (0..24).map(|hour| format!("{:02}:00", hour)).collect();
+                Self::fixed_buckets(counts, labels)
+            },
+            AnalyticsDimension::Weekday => {
+                let weekday_expr = format!("strftime('%w', {})", LibraryTimezone::load(conn)?.sql_local_time("a.time_in_date"));
+                let counts = self.count_by(conn, &weekday_expr, "bucket", from, to, semester_id)?;
+                let labels = WEEKDAY_LABELS.iter().map(|day| day.to_string()).collect();
+                Self::fixed_buckets(counts, labels)
+            },
+            AnalyticsDimension::Purpose => {
+                self.count_by(conn, "COALESCE(a.purpose_label, 'Unspecified')", "visits DESC, bucket", from, to, semester_id)?
+                    .into_iter()
+                    .map(|(label, value)| SeriesPoint { label, value })
+                    .collect()
+            },
+            AnalyticsDimension::Classification => {
+                self.count_by(conn, "a.classification", "visits DESC, bucket", from, to, semester_id)?
+                    .into_iter()
+                    .map(|(label, value)| SeriesPoint { label, value })
+                    .collect()
+            },
+            AnalyticsDimension::Course => {
+                let course_expr = format!("COALESCE(NULLIF({}, ''), 'No Course')", ACCOUNT_COURSE);
+                self.count_by(conn, &course_expr, "visits DESC, bucket", from, to, semester_id)?
+                    .into_iter()
+                    .map(|(label, value)| SeriesPoint { label, value })
+                    .collect()
+            },
+            AnalyticsDimension::Location => {
+                // Manual entries and visits from before kiosks were paired have no location
+                self.count_by(conn, "COALESCE(NULLIF(a.location, ''), 'No Location')", "visits DESC, bucket", from, to, semester_id)?
+                    .into_iter()
+                    .map(|(label, value)| SeriesPoint { label, value })
+                    .collect()
+            },
+        };
+
+        Ok(ChartSeries { dimension, points })
+    }
+
+    fn get_analytics(
+        &self,
+        conn: &Connection,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        semester_id: Option<Uuid>
+    ) -> Result<AttendanceAnalytics> {
+        let (conditions, values) = Self::scope(from, to, semester_id);
+        let (total_visits, unique_visitors, average_visit_minutes) = conn.query_row(
+            &format!(
+                "SELECT
+                    COUNT(*),
+                    COUNT(DISTINCT a.school_id),
+                    AVG(CASE
+                        WHEN a.time_out_date IS NOT NULL
+                        THEN (a.time_out_date - a.time_in_date) / 60000.0
+                    END)
+                 FROM attendance a
+                 WHERE {}",
+                conditions
+            ),
+            params_from_iter(values.iter()),
+            |row| Ok((row.get::<_, u64>(0)?, row.get::<_, u64>(1)?, row.get::<_, Option<f64>>(2)?))
+        )?;
+
+        Ok(AttendanceAnalytics {
+            from,
+            to,
+            semester_id,
+            total_visits,
+            unique_visitors,
+            average_visit_minutes,
+            by_hour: self.get_series(conn, AnalyticsDimension::HourOfDay, from, to, semester_id)?,
+            by_weekday: self.get_series(conn, AnalyticsDimension::Weekday, from, to, semester_id)?,
+            by_purpose: self.get_series(conn, AnalyticsDimension::Purpose, from, to, semester_id)?,
+            by_classification: self.get_series(conn, AnalyticsDimension::Classification, from, to, semester_id)?,
+            by_course: self.get_series(conn, AnalyticsDimension::Course, from, to, semester_id)?,
+            by_location: self.get_series(conn, AnalyticsDimension::Location, from, to, semester_id)?,
+        })
+    }
+}
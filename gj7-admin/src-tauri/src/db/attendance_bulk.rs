@@ -0,0 +1,196 @@
+// src/db/attendance_bulk.rs
+
+use rusqlite::{params, Connection, Result};
+use rusqlite::types::Value;
+use serde::{Serialize, Deserialize};
+use log::info;
+
+use crate::db::attendance::{
+    row_to_attendance,
+    Attendance,
+    AttendanceFilter,
+    AttendanceRepository,
+    AttendanceSort,
+    SqliteAttendanceRepository,
+    ATTENDANCE_WITH_ACCOUNT
+};
+use crate::db::attendance_history::{AttendanceAction, AttendanceHistoryRepository, SqliteAttendanceHistoryRepository};
+use crate::db::library_timezone::LibraryTimezone;
+
+// How many matching records a preview shows
+const PREVIEW_SAMPLE_SIZE: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum BulkAttendanceAction {
+    SetPurpose { purpose_label: Option<String> },
+    SetClassification { classification: String },
+    Delete,
+    Restore,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkPreview {
+    // Records the action would change; pass it back as `expected_count` to apply
+    pub matched: u64,
+    pub sample: Vec<Attendance>,
+}
+
+impl BulkAttendanceAction {
+    fn validate(&self, conn: &Connection) -> Result<()> {
+        match self {
+            BulkAttendanceAction::SetPurpose { purpose_label: Some(label) } => {
+                let exists: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM purposes WHERE label = ?1 AND is_deleted = FALSE)",
+                    params![label],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Err(rusqlite::Error::InvalidParameterName(format!("Unknown purpose: {}", label)));
+                }
+            },
+            BulkAttendanceAction::SetClassification { classification } if classification.trim().is_empty() => {
+                return Err(rusqlite::Error::InvalidParameterName("Classification cannot be empty".to_string()));
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    // Conditions selecting the records the action would actually change,
+    // so rows that already have the new value are not counted
+    fn to_sql(&self, filter: &AttendanceFilter, timezone: &LibraryTimezone) -> (String, Vec<Value>) {
+        let filter = AttendanceFilter {
+            include_deleted: matches!(self, BulkAttendanceAction::Restore),
+            ..filter.clone()
+        };
+        let (mut conditions, mut values) = filter.to_sql(timezone);
+
+        match self {
+            BulkAttendanceAction::SetPurpose { purpose_label } => {
+                conditions.push_str(" AND a.purpose_label IS NOT ?");
+                values.push(purpose_label.clone().map(Value::Text).unwrap_or(Value::Null));
+            },
+            BulkAttendanceAction::SetClassification { classification } => {
+                conditions.push_str(" AND a.classification IS NOT ?");
+                values.push(Value::Text(classification.clone()));
+            },
+            BulkAttendanceAction::Delete => {},
+            BulkAttendanceAction::Restore => conditions.push_str(" AND a.is_deleted = 1"),
+        }
+
+        (conditions, values)
+    }
+
+    fn apply_to(&self, conn: &Connection, attendance: &Attendance) -> Result<AttendanceAction> {
+        let id = attendance.id.to_string();
+        match self {
+            BulkAttendanceAction::SetPurpose { purpose_label } => {
+                conn.execute(
+                    "UPDATE attendance SET purpose_label = ?1 WHERE id = ?2",
+                    params![purpose_label, id],
+                )?;
+                Ok(AttendanceAction::Updated)
+            },
+            BulkAttendanceAction::SetClassification { classification } => {
+                conn.execute(
+                    "UPDATE attendance SET classification = ?1 WHERE id = ?2",
+                    params![classification, id],
+                )?;
+                Ok(AttendanceAction::Updated)
+            },
+            BulkAttendanceAction::Delete => {
+                conn.execute("UPDATE attendance SET is_deleted = 1 WHERE id = ?1", params![id])?;
+                Ok(AttendanceAction::Deleted)
+            },
+            BulkAttendanceAction::Restore => {
+                conn.execute("UPDATE attendance SET is_deleted = 0 WHERE id = ?1", params![id])?;
+                Ok(AttendanceAction::Restored)
+            },
+        }
+    }
+}
+
+fn matching_attendances(
+    conn: &Connection,
+    filter: &AttendanceFilter,
+    action: &BulkAttendanceAction,
+    limit: Option<usize>
+) -> Result<Vec<Attendance>> {
+    let (conditions, mut values) = action.to_sql(filter, &LibraryTimezone::load(conn)?);
+    let mut query = format!(
+        "SELECT a.* FROM {} WHERE 1=1{} {}",
+        ATTENDANCE_WITH_ACCOUNT,
+        conditions,
+        AttendanceSort::default().to_sql()
+    );
+    if let Some(limit) = limit {
+        query.push_str(" LIMIT ?");
+        values.push(Value::Integer(limit as i64));
+    }
+
+    let mut stmt = conn.prepare(&query)?;
+    let attendances = stmt.query_map(rusqlite::params_from_iter(values.iter()), row_to_attendance)?;
+    attendances.collect()
+}
+
+pub fn preview_bulk_action(
+    conn: &Connection,
+    filter: &AttendanceFilter,
+    action: &BulkAttendanceAction
+) -> Result<BulkPreview> {
+    action.validate(conn)?;
+
+    let (conditions, values) = action.to_sql(filter, &LibraryTimezone::load(conn)?);
+    let matched: u64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {} WHERE 1=1{}", ATTENDANCE_WITH_ACCOUNT, conditions),
+        rusqlite::params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    Ok(BulkPreview {
+        matched,
+        sample: matching_attendances(conn, filter, action, Some(PREVIEW_SAMPLE_SIZE))?,
+    })
+}
+
+// Applies the action to every matching record in one transaction. Nothing is
+// changed unless the match count still equals the previewed `expected_count`
+pub fn apply_bulk_action(
+    conn: &Connection,
+    filter: &AttendanceFilter,
+    action: &BulkAttendanceAction,
+    expected_count: u64,
+    changed_by: &str,
+    reason: Option<&str>
+) -> Result<u64> {
+    action.validate(conn)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let targets = matching_attendances(&tx, filter, action, None)?;
+    if targets.len() as u64 != expected_count {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "{} records now match instead of the {} previewed; preview again before applying",
+            targets.len(),
+            expected_count
+        )));
+    }
+
+    let repo = SqliteAttendanceRepository;
+    for before in &targets {
+        let history_action = action.apply_to(&tx, before)?;
+        let after = repo.get_attendance(&tx, before.id)?;
+        SqliteAttendanceHistoryRepository.record_change(
+            &tx,
+            history_action,
+            Some(before),
+            Some(&after),
+            changed_by,
+            reason
+        )?;
+    }
+    tx.commit()?;
+
+    info!("Bulk {:?} applied to {} attendance records by {}", action, targets.len(), changed_by);
+    Ok(targets.len() as u64)
+}
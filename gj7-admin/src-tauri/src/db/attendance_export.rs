@@ -0,0 +1,164 @@
+// src/db/attendance_export.rs
+
+use rusqlite::{Connection, Result};
+use serde::{Serialize, Deserialize};
+
+use crate::db::attendance::{
+    row_to_attendance,
+    Attendance,
+    AttendanceFilter,
+    AttendanceSort,
+    ACCOUNT_COURSE,
+    ACCOUNT_DEPARTMENT,
+    ACCOUNT_YEAR_LEVEL,
+    ATTENDANCE_WITH_ACCOUNT
+};
+use crate::db::library_timezone::LibraryTimezone;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ExportColumn {
+    Id,
+    SchoolId,
+    FullName,
+    TimeIn,
+    TimeOut,
+    DurationMinutes,
+    Classification,
+    Purpose,
+    Course,
+    Department,
+    YearLevel,
+    EntryMethod,
+    EnteredBy,
+    Location,
+}
+
+impl ExportColumn {
+    // Columns used when the caller does not pick any
+    pub fn defaults() -> Vec<ExportColumn> {
+        vec![
+            ExportColumn::SchoolId,
+            ExportColumn::FullName,
+            ExportColumn::TimeIn,
+            ExportColumn::TimeOut,
+            ExportColumn::DurationMinutes,
+            ExportColumn::Classification,
+            ExportColumn::Purpose,
+            ExportColumn::Course,
+            ExportColumn::Department,
+            ExportColumn::YearLevel,
+            ExportColumn::EntryMethod,
+        ]
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            ExportColumn::Id => "ID",
+            ExportColumn::SchoolId => "School ID",
+            ExportColumn::FullName => "Full Name",
+            ExportColumn::TimeIn => "Time In",
+            ExportColumn::TimeOut => "Time Out",
+            ExportColumn::DurationMinutes => "Duration (minutes)",
+            ExportColumn::Classification => "Classification",
+            ExportColumn::Purpose => "Purpose",
+            ExportColumn::Course => "Course",
+            ExportColumn::Department => "Department",
+            ExportColumn::YearLevel => "Year Level",
+            ExportColumn::EntryMethod => "Entry",
+            ExportColumn::EnteredBy => "Entered By",
+            ExportColumn::Location => "Location",
+        }
+    }
+}
+
+// An attendance together with the account details reports group by
+#[derive(Debug, Serialize, Clone)]
+pub struct AttendanceExportRow {
+    #[serde(flatten)]
+    pub attendance: Attendance,
+    pub course: Option<String>,
+    pub department: Option<String>,
+    pub year_level: Option<String>,
+    // Times are written out in library time
+    #[serde(skip)]
+    pub timezone: LibraryTimezone,
+}
+
+impl AttendanceExportRow {
+    pub fn duration_minutes(&self) -> Option<i64> {
+        self.attendance.time_out_date
+            .map(|time_out| (time_out - self.attendance.time_in_date).num_minutes())
+    }
+
+    // Text value of a column, empty when the value is missing
+    pub fn value(&self, column: ExportColumn) -> String {
+        let attendance = &self.attendance;
+        match column {
+            ExportColumn::Id => attendance.id.to_string(),
+            ExportColumn::SchoolId => attendance.school_id.clone(),
+            ExportColumn::FullName => attendance.full_name.clone(),
+            ExportColumn::TimeIn => self.timezone.local(attendance.time_in_date).format("%Y-%m-%d %H:%M:%S").to_string(),
+            ExportColumn::TimeOut => attendance.time_out_date
+                .map(|time_out| self.timezone.local(time_out).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            ExportColumn::DurationMinutes => self.duration_minutes()
+                .map(|minutes| minutes.to_string())
+                .unwrap_or_default(),
+            ExportColumn::Classification => attendance.classification.clone(),
+            ExportColumn::Purpose => attendance.purpose_label.clone().unwrap_or_default(),
+            ExportColumn::Course => self.course.clone().unwrap_or_default(),
+            ExportColumn::Department => self.department.clone().unwrap_or_default(),
+            ExportColumn::YearLevel => self.year_level.clone().unwrap_or_default(),
+            ExportColumn::EntryMethod => if attendance.is_manual { "Manual" } else { "Kiosk" }.to_string(),
+            ExportColumn::EnteredBy => attendance.entered_by.clone().unwrap_or_default(),
+            ExportColumn::Location => attendance.location.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// Runs the filtered query and hands each row to `f` as it is read,
+// so large exports never hold the whole result in memory
+pub fn for_each_export_row<F, E>(
+    conn: &Connection,
+    filter: &AttendanceFilter,
+    sort: AttendanceSort,
+    mut f: F
+) -> Result<usize, E>
+where
+    F: FnMut(&AttendanceExportRow) -> Result<(), E>,
+    E: From<rusqlite::Error>
+{
+    let timezone = LibraryTimezone::load(conn)?;
+    let (conditions, values) = filter.to_sql(&timezone);
+    let query = format!(
+        "SELECT a.*,
+            {} AS account_course,
+            {} AS account_department,
+            {} AS account_year_level
+         FROM {} WHERE 1=1{} {}",
+        ACCOUNT_COURSE,
+        ACCOUNT_DEPARTMENT,
+        ACCOUNT_YEAR_LEVEL,
+        ATTENDANCE_WITH_ACCOUNT,
+        conditions,
+        sort.to_sql()
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(values.iter()))?;
+
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        let export_row = AttendanceExportRow {
+            attendance: row_to_attendance(row)?,
+            course: row.get("account_course")?,
+            department: row.get("account_department")?,
+            year_level: row.get("account_year_level")?,
+            timezone,
+        };
+        f(&export_row)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
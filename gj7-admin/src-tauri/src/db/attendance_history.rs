@@ -0,0 +1,178 @@
+// src/db/attendance_history.rs
+
+use uuid::Uuid;
+use rusqlite::{params, Connection, Result, Row};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+
+use crate::db::attendance::Attendance;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum AttendanceAction {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl AttendanceAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AttendanceAction::Created => "Created",
+            AttendanceAction::Updated => "Updated",
+            AttendanceAction::Deleted => "Deleted",
+            AttendanceAction::Restored => "Restored",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Created" => Some(AttendanceAction::Created),
+            "Updated" => Some(AttendanceAction::Updated),
+            "Deleted" => Some(AttendanceAction::Deleted),
+            "Restored" => Some(AttendanceAction::Restored),
+            _ => None,
+        }
+    }
+}
+
+// One admin change to an attendance record, with the record before and after it
+#[derive(Debug, Serialize, Clone)]
+pub struct AttendanceChange {
+    pub id: Uuid,
+    pub attendance_id: Uuid,
+    pub action: AttendanceAction,
+    pub before: Option<Attendance>,
+    pub after: Option<Attendance>,
+    pub changed_by: String,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+pub trait AttendanceHistoryRepository: Send + Sync {
+    fn record_change(
+        &self,
+        conn: &Connection,
+        action: AttendanceAction,
+        before: Option<&Attendance>,
+        after: Option<&Attendance>,
+        changed_by: &str,
+        reason: Option<&str>
+    ) -> Result<()>;
+    fn get_attendance_history(&self, conn: &Connection, attendance_id: Uuid) -> Result<Vec<AttendanceChange>>;
+}
+
+pub struct SqliteAttendanceHistoryRepository;
+
+// A malformed row fails the query instead of panicking inside the row mapper
+fn conversion_failure<E>(column: usize) -> impl FnOnce(E) -> rusqlite::Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    move |e| rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, Box::new(e))
+}
+
+fn to_json(attendance: Option<&Attendance>) -> Result<Option<String>> {
+    attendance
+        .map(|attendance| serde_json::to_string(attendance)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))))
+        .transpose()
+}
+
+fn from_json(value: Option<String>, column: usize) -> Result<Option<Attendance>> {
+    value
+        .map(|value| serde_json::from_str(&value).map_err(conversion_failure(column)))
+        .transpose()
+}
+
+fn row_to_change(row: &Row) -> Result<AttendanceChange> {
+    let action: String = row.get(2)?;
+
+    Ok(AttendanceChange {
+        id: Uuid::parse_str(&row.get::<_, String>(0)?).map_err(conversion_failure(0))?,
+        attendance_id: Uuid::parse_str(&row.get::<_, String>(1)?).map_err(conversion_failure(1))?,
+        action: AttendanceAction::parse(&action).ok_or_else(|| rusqlite::Error::InvalidColumnType(
+            2,
+            action.clone(),
+            rusqlite::types::Type::Text
+        ))?,
+        before: from_json(row.get(3)?, 3)?,
+        after: from_json(row.get(4)?, 4)?,
+        changed_by: row.get(5)?,
+        reason: row.get(6)?,
+        changed_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?)
+            .map_err(conversion_failure(7))?
+            .with_timezone(&Utc),
+    })
+}
+
+impl AttendanceHistoryRepository for SqliteAttendanceHistoryRepository {
+    fn record_change(
+        &self,
+        conn: &Connection,
+        action: AttendanceAction,
+        before: Option<&Attendance>,
+        after: Option<&Attendance>,
+        changed_by: &str,
+        reason: Option<&str>
+    ) -> Result<()> {
+        let attendance_id = after.or(before)
+            .map(|attendance| attendance.id)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("A change needs a before or after record".to_string()))?;
+
+        conn.execute(
+            "INSERT INTO attendance_history (
+                id, attendance_id, action, before_json, after_json, changed_by, reason, changed_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                Uuid::new_v4().to_string(),
+                attendance_id.to_string(),
+                action.as_str(),
+                to_json(before)?,
+                to_json(after)?,
+                changed_by,
+                reason.filter(|reason| !reason.trim().is_empty()),
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_attendance_history(&self, conn: &Connection, attendance_id: Uuid) -> Result<Vec<AttendanceChange>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, attendance_id, action, before_json, after_json, changed_by, reason, changed_at
+             FROM attendance_history
+             WHERE attendance_id = ?1
+             ORDER BY changed_at DESC"
+        )?;
+
+        let changes = stmt.query_map(params![attendance_id.to_string()], row_to_change)?;
+        changes.collect()
+    }
+}
+
+pub fn create_attendance_history_table(conn: &Connection) -> Result<()> {
+    // Kept without a foreign key so the trail outlives the record it describes
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS attendance_history (
+            id TEXT PRIMARY KEY,
+            attendance_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            before_json TEXT,
+            after_json TEXT,
+            changed_by TEXT NOT NULL,
+            reason TEXT,
+            changed_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_attendance_history_attendance
+         ON attendance_history (attendance_id, changed_at)",
+        [],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// src/db/attendance_report.rs
+
+use rusqlite::{params, Connection, Result};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::db::attendance::{from_millis, AttendanceFilter, AttendanceSort, AttendanceSortField, SortOrder};
+use crate::db::attendance_analytics::{
+    AnalyticsDimension,
+    AttendanceAnalyticsRepository,
+    SeriesPoint,
+    SqliteAttendanceAnalyticsRepository
+};
+use crate::db::attendance_export::{for_each_export_row, AttendanceExportRow};
+use crate::db::library_timezone::LibraryTimezone;
+use crate::db::semester::{SemesterRepository, SqliteSemesterRepository};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ReportPeriod {
+    Daily { date: NaiveDate },
+    // Any date inside the week; the report starts on that week's Monday
+    Weekly { date: NaiveDate },
+    Semester { semester_id: Uuid },
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceReport {
+    pub title: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_visits: u64,
+    pub unique_visitors: u64,
+    // Visits entered by hand from the paper logbook
+    pub manual_visits: u64,
+    pub by_purpose: Vec<SeriesPoint>,
+    pub by_course: Vec<SeriesPoint>,
+    pub rows: Vec<AttendanceExportRow>,
+    #[serde(skip)]
+    pub timezone: LibraryTimezone,
+}
+
+// The visits a report covers: [from, to), optionally limited to one semester
+pub struct ReportRange {
+    pub title: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub semester_id: Option<Uuid>,
+}
+
+// Days and weeks start at midnight library time
+pub fn resolve_period(conn: &Connection, period: &ReportPeriod) -> Result<ReportRange> {
+    let timezone = LibraryTimezone::load(conn)?;
+
+    match period {
+        ReportPeriod::Daily { date } => {
+            let from = timezone.start_of_day(*date);
+            Ok(ReportRange {
+                title: format!("Daily Attendance Report - {}", date.format("%B %d, %Y")),
+                from,
+                to: timezone.start_of_day(*date + Duration::days(1)),
+                semester_id: None,
+            })
+        },
+        ReportPeriod::Weekly { date } => {
+            let monday = *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let sunday = monday + Duration::days(6);
+            let from = timezone.start_of_day(monday);
+            Ok(ReportRange {
+                title: format!(
+                    "Weekly Attendance Report - {} to {}",
+                    monday.format("%B %d, %Y"),
+                    sunday.format("%B %d, %Y")
+                ),
+                from,
+                to: timezone.start_of_day(monday + Duration::days(7)),
+                semester_id: None,
+            })
+        },
+        ReportPeriod::Semester { semester_id } => {
+            let semester = SqliteSemesterRepository.get_semester(conn, *semester_id)?;
+
+            // The range spans the semester's own visits; empty semesters collapse to their creation
+            let (first, last): (Option<i64>, Option<i64>) = conn.query_row(
+                "SELECT MIN(time_in_date), MAX(time_in_date) FROM attendance WHERE semester_id = ?1 AND is_deleted = 0",
+                params![semester_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            let from = first.map(|first| from_millis(first, 0)).transpose()?.unwrap_or(semester.created_at);
+            let to = last.map(|last| from_millis(last + 1, 1)).transpose()?.unwrap_or(from);
+
+            Ok(ReportRange {
+                title: format!("Semester Attendance Report - {}", semester.label),
+                from,
+                to,
+                semester_id: Some(*semester_id),
+            })
+        },
+    }
+}
+
+pub fn build_attendance_report(conn: &Connection, period: &ReportPeriod) -> Result<AttendanceReport> {
+    let ReportRange { title, from, to, semester_id } = resolve_period(conn, period)?;
+
+    let analytics = SqliteAttendanceAnalyticsRepository;
+    let by_purpose = analytics.get_series(conn, AnalyticsDimension::Purpose, from, to, semester_id)?.points;
+    let by_course = analytics.get_series(conn, AnalyticsDimension::Course, from, to, semester_id)?.points;
+
+    let filter = AttendanceFilter {
+        from: Some(from),
+        to: Some(to),
+        semester_id,
+        ..Default::default()
+    };
+    let sort = AttendanceSort {
+        field: AttendanceSortField::TimeIn,
+        order: SortOrder::Asc,
+    };
+
+    let mut rows = Vec::new();
+    for_each_export_row(conn, &filter, sort, |row| {
+        rows.push(row.clone());
+        Ok::<(), rusqlite::Error>(())
+    })?;
+
+    let mut visitors: Vec<&str> = rows.iter().map(|row| row.attendance.school_id.as_str()).collect();
+    visitors.sort_unstable();
+    visitors.dedup();
+
+    Ok(AttendanceReport {
+        title,
+        from,
+        to,
+        total_visits: rows.len() as u64,
+        unique_visitors: visitors.len() as u64,
+        manual_visits: rows.iter().filter(|row| row.attendance.is_manual).count() as u64,
+        by_purpose,
+        by_course,
+        rows,
+        timezone: LibraryTimezone::load(conn)?,
+    })
+}
@@ -0,0 +1,306 @@
+// src/db/audit_log.rs
+
+use log::info;
+use rusqlite::{params, Connection, Result, Row};
+use rusqlite::types::Value;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+
+use crate::db::attendance::from_millis;
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 500;
+
+// The table only accepts inserts; updates and deletes are refused by triggers
+const CREATE_AUDIT_LOG: &str = "
+    CREATE TABLE IF NOT EXISTS audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        occurred_at INTEGER NOT NULL,
+        username TEXT NOT NULL,
+        action TEXT NOT NULL,
+        target TEXT,
+        summary TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_audit_log_occurred_at ON audit_log (occurred_at);
+    CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log (action, occurred_at);
+
+    CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log BEGIN
+        SELECT RAISE(ABORT, 'The audit log is append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log BEGIN
+        SELECT RAISE(ABORT, 'The audit log is append-only');
+    END;
+";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    CsvImported,
+    SemesterCreated,
+    SemesterUpdated,
+    SemesterDeleted,
+    SemesterActivated,
+    PurposeCreated,
+    PurposeUpdated,
+    PurposeDeleted,
+    PurposeRestored,
+    AccountSemesterChanged,
+    AttendanceCreated,
+    AttendanceUpdated,
+    AttendanceDeleted,
+    AttendanceRestored,
+    AttendanceBulkAction,
+    UserCreated,
+    UserRoleChanged,
+    UserActivated,
+    UserDeactivated,
+    UserDeleted,
+    PasswordChanged,
+    PasswordReset,
+    SettingsChanged,
+    KioskPairingCodeCreated,
+    KioskPaired,
+    KioskRevoked,
+}
+
+impl AuditAction {
+    const ALL: [AuditAction; 26] = [
+        AuditAction::CsvImported,
+        AuditAction::SemesterCreated,
+        AuditAction::SemesterUpdated,
+        AuditAction::SemesterDeleted,
+        AuditAction::SemesterActivated,
+        AuditAction::PurposeCreated,
+        AuditAction::PurposeUpdated,
+        AuditAction::PurposeDeleted,
+        AuditAction::PurposeRestored,
+        AuditAction::AccountSemesterChanged,
+        AuditAction::AttendanceCreated,
+        AuditAction::AttendanceUpdated,
+        AuditAction::AttendanceDeleted,
+        AuditAction::AttendanceRestored,
+        AuditAction::AttendanceBulkAction,
+        AuditAction::UserCreated,
+        AuditAction::UserRoleChanged,
+        AuditAction::UserActivated,
+        AuditAction::UserDeactivated,
+        AuditAction::UserDeleted,
+        AuditAction::PasswordChanged,
+        AuditAction::PasswordReset,
+        AuditAction::SettingsChanged,
+        AuditAction::KioskPairingCodeCreated,
+        AuditAction::KioskPaired,
+        AuditAction::KioskRevoked,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::CsvImported => "CsvImported",
+            AuditAction::SemesterCreated => "SemesterCreated",
+            AuditAction::SemesterUpdated => "SemesterUpdated",
+            AuditAction::SemesterDeleted => "SemesterDeleted",
+            AuditAction::SemesterActivated => "SemesterActivated",
+            AuditAction::PurposeCreated => "PurposeCreated",
+            AuditAction::PurposeUpdated => "PurposeUpdated",
+            AuditAction::PurposeDeleted => "PurposeDeleted",
+            AuditAction::PurposeRestored => "PurposeRestored",
+            AuditAction::AccountSemesterChanged => "AccountSemesterChanged",
+            AuditAction::AttendanceCreated => "AttendanceCreated",
+            AuditAction::AttendanceUpdated => "AttendanceUpdated",
+            AuditAction::AttendanceDeleted => "AttendanceDeleted",
+            AuditAction::AttendanceRestored => "AttendanceRestored",
+            AuditAction::AttendanceBulkAction => "AttendanceBulkAction",
+            AuditAction::UserCreated => "UserCreated",
+            AuditAction::UserRoleChanged => "UserRoleChanged",
+            AuditAction::UserActivated => "UserActivated",
+            AuditAction::UserDeactivated => "UserDeactivated",
+            AuditAction::UserDeleted => "UserDeleted",
+            AuditAction::PasswordChanged => "PasswordChanged",
+            AuditAction::PasswordReset => "PasswordReset",
+            AuditAction::SettingsChanged => "SettingsChanged",
+            AuditAction::KioskPairingCodeCreated => "KioskPairingCodeCreated",
+            AuditAction::KioskPaired => "KioskPaired",
+            AuditAction::KioskRevoked => "KioskRevoked",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        AuditAction::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+}
+
+// Compact JSON of a submitted request, for payloads that carry no secrets
+pub fn payload_summary<T: Serialize>(payload: &T) -> Option<String> {
+    serde_json::to_string(payload).ok()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    // The user who authorized the action
+    pub username: String,
+    pub action: AuditAction,
+    // What was acted on, e.g. a semester id or a CSV file name
+    pub target: Option<String>,
+    // Short description of what was submitted; never contains secrets
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AuditLogFilter {
+    pub username: Option<String>,
+    pub action: Option<AuditAction>,
+    // Inclusive start of the range
+    pub from: Option<DateTime<Utc>>,
+    // Exclusive end of the range
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditLogFilter {
+    // Builds the conditions to append after `WHERE 1=1` on audit_log
+    fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut conditions = String::new();
+        let mut values = Vec::new();
+
+        if let Some(username) = self.username.as_ref().filter(|username| !username.is_empty()) {
+            conditions.push_str(" AND username = ?");
+            values.push(Value::Text(username.clone()));
+        }
+
+        if let Some(action) = self.action {
+            conditions.push_str(" AND action = ?");
+            values.push(Value::Text(action.as_str().to_string()));
+        }
+
+        if let Some(from) = self.from {
+            conditions.push_str(" AND occurred_at >= ?");
+            values.push(Value::Integer(from.timestamp_millis()));
+        }
+
+        if let Some(to) = self.to {
+            conditions.push_str(" AND occurred_at < ?");
+            values.push(Value::Integer(to.timestamp_millis()));
+        }
+
+        (conditions, values)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedAuditLog {
+    pub entries: Vec<AuditEntry>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+    pub total_pages: u64,
+}
+
+const AUDIT_COLUMNS: &str = "id, occurred_at, username, action, target, summary";
+
+fn row_to_audit_entry(row: &Row) -> Result<AuditEntry> {
+    let action: String = row.get(3)?;
+
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        occurred_at: from_millis(row.get(1)?, 1)?,
+        username: row.get(2)?,
+        action: AuditAction::parse(&action).ok_or_else(|| rusqlite::Error::InvalidColumnType(
+            3,
+            action.clone(),
+            rusqlite::types::Type::Text
+        ))?,
+        target: row.get(4)?,
+        summary: row.get(5)?,
+    })
+}
+
+#[derive(Clone)]
+pub struct AuditLogDatabase;
+
+impl AuditLogDatabase {
+    pub fn init(conn: &Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_AUDIT_LOG)?;
+        Ok(AuditLogDatabase)
+    }
+
+    pub fn record(
+        &self,
+        conn: &Connection,
+        username: &str,
+        action: AuditAction,
+        target: Option<&str>,
+        summary: Option<&str>
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO audit_log (occurred_at, username, action, target, summary)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![Utc::now().timestamp_millis(), username, action.as_str(), target, summary],
+        )?;
+
+        info!("Audit: {} {} {}", username, action.as_str(), target.unwrap_or(""));
+        Ok(())
+    }
+
+    // Newest first
+    pub fn get_audit_log(
+        &self,
+        conn: &Connection,
+        filter: &AuditLogFilter,
+        page: u64,
+        page_size: u64
+    ) -> Result<PaginatedAuditLog> {
+        let page = page.max(1);
+        let page_size = if page_size == 0 { DEFAULT_PAGE_SIZE } else { page_size.min(MAX_PAGE_SIZE) };
+        let (conditions, mut values) = filter.to_sql();
+
+        let total_count: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM audit_log WHERE 1=1{}", conditions),
+            rusqlite::params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push(Value::Integer(page_size as i64));
+        values.push(Value::Integer(((page - 1) * page_size) as i64));
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM audit_log WHERE 1=1{} ORDER BY occurred_at DESC, id DESC LIMIT ? OFFSET ?",
+            AUDIT_COLUMNS,
+            conditions
+        ))?;
+        let entries = stmt
+            .query_map(rusqlite::params_from_iter(values.iter()), row_to_audit_entry)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PaginatedAuditLog {
+            entries,
+            total_count,
+            page,
+            page_size,
+            total_pages: total_count.div_ceil(page_size),
+        })
+    }
+
+    // Hands each matching entry to `f`, oldest first, without holding them all in memory
+    pub fn for_each_audit_entry<F, E>(&self, conn: &Connection, filter: &AuditLogFilter, mut f: F) -> Result<usize, E>
+    where
+        F: FnMut(&AuditEntry) -> Result<(), E>,
+        E: From<rusqlite::Error>
+    {
+        let (conditions, values) = filter.to_sql();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM audit_log WHERE 1=1{} ORDER BY occurred_at, id",
+            AUDIT_COLUMNS,
+            conditions
+        ))?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(values.iter()))?;
+
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            f(&row_to_audit_entry(row)?)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
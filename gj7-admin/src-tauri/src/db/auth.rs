@@ -1,7 +1,30 @@
 // src/db/auth.rs
 use log::info;
-use rusqlite::{Connection, Result as SqliteResult, params};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Row, params};
 use serde::{Serialize, Deserialize};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+
+use crate::db::add_column_if_missing;
+use crate::db::attendance::from_millis;
+use crate::db::roles::Role;
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+// Number of recovery codes issued at a time
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LENGTH: usize = 10;
+// Leaves out characters that are easy to misread (0/O, 1/I/L)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const PIN_LENGTH: usize = 4;
+// Wrong PINs allowed before the first lockout
+const PIN_FREE_ATTEMPTS: u32 = 3;
+// Wrong passwords allowed before the first lockout
+const LOGIN_FREE_ATTEMPTS: u32 = 5;
+// The first lockout; each further wrong PIN or password doubles it, up to the maximum
+const BASE_LOCKOUT_SECS: i64 = 30;
+const MAX_LOCKOUT_SECS: i64 = 60 * 60;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Credentials {
@@ -9,12 +32,125 @@ pub struct Credentials {
     pub password: String,
 }
 
+// A login as shown to the UI; the password never leaves the database
+#[derive(Debug, Serialize, Clone)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    pub is_active: bool,
+    pub recovery_codes_remaining: u32,
+    pub has_pin: bool,
+}
+
+const SELECT_USER: &str =
+    "SELECT u.id, u.username, u.role, u.is_active,
+            (SELECT COUNT(*) FROM recovery_codes r WHERE r.user_id = u.id AND r.used_at IS NULL),
+            u.pin_hash IS NOT NULL
+     FROM users u";
+
+fn row_to_user(row: &Row) -> SqliteResult<User> {
+    let role: String = row.get(2)?;
+
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        role: Role::parse(&role).ok_or_else(|| rusqlite::Error::InvalidColumnType(
+            2,
+            role.clone(),
+            rusqlite::types::Type::Text
+        ))?,
+        is_active: row.get(3)?,
+        recovery_codes_remaining: row.get(4)?,
+        has_pin: row.get(5)?,
+    })
+}
+
+// Outcome of a PIN check, also used to show the lockout before a new attempt
+#[derive(Debug, Serialize, Clone)]
+pub struct PinCheck {
+    pub verified: bool,
+    pub attempts_remaining: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+fn validate_pin(pin: &str) -> SqliteResult<()> {
+    if pin.len() != PIN_LENGTH || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(rusqlite::Error::InvalidParameterName(format!("PIN must be {} digits", PIN_LENGTH)));
+    }
+
+    Ok(())
+}
+
+// Lockout after the given number of consecutive wrong PINs or passwords, if any
+fn lockout_secs(failed_attempts: u32, free_attempts: u32) -> Option<i64> {
+    let doublings = failed_attempts.checked_sub(free_attempts)?;
+    Some(BASE_LOCKOUT_SECS.saturating_mul(1 << doublings.min(16)).min(MAX_LOCKOUT_SECS))
+}
+
+// Once locked out, every further wrong PIN locks again
+fn pin_attempts_remaining(failed_attempts: u32, locked: bool) -> u32 {
+    if locked {
+        0
+    } else {
+        PIN_FREE_ATTEMPTS.saturating_sub(failed_attempts).max(1)
+    }
+}
+
+// Hashes a password or recovery code as an argon2id PHC string
+pub(crate) fn hash_secret(secret: &str) -> SqliteResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(format!("Failed to hash secret: {}", e).into()))
+}
+
+pub(crate) fn verify_secret(secret: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(secret.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+// Rows written before hashing hold the plaintext password
+fn is_hashed(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
+
+fn validate_new_password(password: &str) -> SqliteResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+// Codes are shown as XXXXX-XXXXX but compared without separators or case
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let code: String = (0..RECOVERY_CODE_LENGTH)
+        .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+        .collect();
+
+    format!("{}-{}", &code[..RECOVERY_CODE_LENGTH / 2], &code[RECOVERY_CODE_LENGTH / 2..])
+}
+
 #[derive(Clone)]
 pub struct AuthDatabase;
 
 impl AuthDatabase {
     pub fn init(conn: &Connection) -> SqliteResult<Self> {
-        // Create users table
+        // Create users table; password holds an argon2id PHC string
         conn.execute(
             "CREATE TABLE IF NOT EXISTS users (
              id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -23,52 +159,455 @@ impl AuthDatabase {
              )",
              [],
         )?;
+        // Logins from before roles keep full access
+        add_column_if_missing(conn, "users", "role", "TEXT NOT NULL DEFAULT 'Librarian'")?;
+        add_column_if_missing(conn, "users", "is_active", "INTEGER NOT NULL DEFAULT 1")?;
+        // Optional PIN for unlocking the app, hashed like the password
+        add_column_if_missing(conn, "users", "pin_hash", "TEXT")?;
+        add_column_if_missing(conn, "users", "failed_pin_attempts", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(conn, "users", "pin_locked_until", "INTEGER")?;
+        add_column_if_missing(conn, "users", "failed_login_attempts", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(conn, "users", "login_locked_until", "INTEGER")?;
+
+        // Single-use codes for resetting a forgotten password, stored hashed
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS recovery_codes (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+             code_hash TEXT NOT NULL,
+             used_at INTEGER
+             )",
+             [],
+        )?;
         Ok(AuthDatabase)
     }
 
+    // Deactivated users are treated as unknown everywhere a password is checked
+    fn find_user(&self, conn: &Connection, username: &str) -> SqliteResult<Option<(i64, String)>> {
+        conn.query_row(
+            "SELECT id, password FROM users WHERE username = ?1 AND is_active = 1",
+            params![username],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()
+    }
+
+    fn login_state(&self, conn: &Connection, user_id: i64) -> SqliteResult<(u32, Option<DateTime<Utc>>)> {
+        let (failed_attempts, locked_until) = conn.query_row(
+            "SELECT failed_login_attempts, login_locked_until FROM users WHERE id = ?1",
+            params![user_id],
+            |row| Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, Option<i64>>(1)?.map(|value| from_millis(value, 1)).transpose()?,
+            )),
+        )?;
+
+        // A lockout that has run out no longer counts
+        Ok((failed_attempts, locked_until.filter(|until| *until > Utc::now())))
+    }
+
+    // When the user's password can next be tried, if wrong passwords have locked it
+    pub fn login_locked_until(&self, conn: &Connection, username: &str) -> SqliteResult<Option<DateTime<Utc>>> {
+        let Some((user_id, _)) = self.find_user(conn, username)? else {
+            return Ok(None);
+        };
+        Ok(self.login_state(conn, user_id)?.1)
+    }
+
+    fn check_password(&self, conn: &Connection, user_id: i64, stored: &str, password: &str) -> SqliteResult<bool> {
+        if is_hashed(stored) {
+            return Ok(verify_secret(password, stored));
+        }
+
+        // Plaintext left by an older version; hash it now that the password is known
+        if stored != password {
+            return Ok(false);
+        }
+        conn.execute(
+            "UPDATE users SET password = ?1 WHERE id = ?2",
+            params![hash_secret(password)?, user_id],
+        )?;
+        info!("Migrated password for user {} to argon2id", user_id);
+        Ok(true)
+    }
+
+    // Every password check goes through here, so signing in and confirming an action
+    // share the lockout. Wrong passwords lock further attempts for a time that doubles
+    // with each failure, as for PINs; attempts during a lockout are not checked.
     pub fn authenticate(&self, conn: &Connection, username: &str, password: &str) -> SqliteResult<bool> {
         info!("Authenticating user: {}", username);
-        let mut stmt = conn.prepare(
-            "SELECT COUNT(*) FROM users WHERE username = ? AND password = ?"
-        )?;
-        
-        let count: i64 = stmt.query_row(params![username, password], |row| row.get(0))?;
-        Ok(count > 0)
+        let Some((user_id, stored)) = self.find_user(conn, username)? else {
+            return Ok(false);
+        };
+
+        let (failed_attempts, locked_until) = self.login_state(conn, user_id)?;
+        if locked_until.is_some() {
+            return Ok(false);
+        }
+
+        if self.check_password(conn, user_id, &stored, password)? {
+            if failed_attempts > 0 {
+                conn.execute(
+                    "UPDATE users SET failed_login_attempts = 0, login_locked_until = NULL WHERE id = ?1",
+                    params![user_id],
+                )?;
+            }
+            return Ok(true);
+        }
+
+        self.record_login_failure(conn, user_id, username, failed_attempts)?;
+        Ok(false)
     }
 
-    pub fn get_credentials(&self, conn: &Connection) -> SqliteResult<Credentials> {
-        info!("Fetching credentials");
-        let mut stmt = conn.prepare(
-            "SELECT username, password FROM users LIMIT 1"
+    // Counts a wrong password or recovery code towards the user's lockout
+    fn record_login_failure(&self, conn: &Connection, user_id: i64, username: &str, failed_attempts: u32) -> SqliteResult<()> {
+        let failed_attempts = failed_attempts + 1;
+        let locked_until = lockout_secs(failed_attempts, LOGIN_FREE_ATTEMPTS)
+            .map(|secs| Utc::now() + Duration::seconds(secs));
+        conn.execute(
+            "UPDATE users SET failed_login_attempts = ?1, login_locked_until = ?2 WHERE id = ?3",
+            params![failed_attempts, locked_until.map(|until| until.timestamp_millis()), user_id],
         )?;
-        
-        stmt.query_row([], |row| {
-            Ok(Credentials {
-                username: row.get(0)?,
-                password: row.get(1)?,
-            })
-        })
+
+        if let Some(until) = locked_until {
+            info!("Sign-in locked for {} until {} after {} failed attempts", username, until, failed_attempts);
+        }
+        Ok(())
+    }
+
+    // Checks the password and returns the user it belongs to
+    pub fn login(&self, conn: &Connection, username: &str, password: &str) -> SqliteResult<Option<User>> {
+        if !self.authenticate(conn, username, password)? {
+            return Ok(None);
+        }
+
+        conn.query_row(
+            &format!("{} WHERE u.username = ?1", SELECT_USER),
+            params![username],
+            row_to_user,
+        ).optional()
     }
 
-    pub fn create_user(&self, conn: &Connection, credentials: &Credentials) -> SqliteResult<()> {
-        info!("Creating new user: {}", credentials.username);
-        
+    pub fn get_user(&self, conn: &Connection, user_id: i64) -> SqliteResult<User> {
+        conn.query_row(&format!("{} WHERE u.id = ?1", SELECT_USER), params![user_id], row_to_user)
+    }
+
+    pub fn list_users(&self, conn: &Connection) -> SqliteResult<Vec<User>> {
+        let mut stmt = conn.prepare(&format!("{} ORDER BY u.username", SELECT_USER))?;
+        let users = stmt.query_map([], row_to_user)?;
+        users.collect()
+    }
+
+    pub fn create_user(&self, conn: &Connection, credentials: &Credentials, role: Role) -> SqliteResult<User> {
+        info!("Creating new {} user: {}", role.as_str(), credentials.username);
+        if credentials.username.trim().is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("Username cannot be empty".to_string()));
+        }
+        validate_new_password(&credentials.password)?;
+
         // Log the current users before insertion
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?;
         info!("Current user count before insertion: {}", count);
-        
+
         conn.execute(
-            "INSERT INTO users (username, password) VALUES (?, ?)",
-            params![credentials.username, credentials.password],
+            "INSERT INTO users (username, password, role) VALUES (?, ?, ?)",
+            params![credentials.username.trim(), hash_secret(&credentials.password)?, role.as_str()],
         )?;
-        
+
         info!("Successfully created user in database");
+        self.get_user(conn, conn.last_insert_rowid())
+    }
+
+    // Refuses changes that would leave nobody able to manage users
+    fn ensure_other_librarian(&self, conn: &Connection, user_id: i64) -> SqliteResult<()> {
+        let others: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM users WHERE role = 'Librarian' AND is_active = 1 AND id != ?1",
+            params![user_id],
+            |row| row.get(0),
+        )?;
+        if others == 0 && self.get_user(conn, user_id)?.role == Role::Librarian {
+            return Err(rusqlite::Error::InvalidParameterName(
+                "At least one active librarian must remain".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_user_role(&self, conn: &Connection, user_id: i64, role: Role) -> SqliteResult<User> {
+        if role != Role::Librarian {
+            self.ensure_other_librarian(conn, user_id)?;
+        }
+
+        conn.execute("UPDATE users SET role = ?1 WHERE id = ?2", params![role.as_str(), user_id])?;
+        info!("User {} is now a {}", user_id, role.as_str());
+        self.get_user(conn, user_id)
+    }
+
+    pub fn set_user_active(&self, conn: &Connection, user_id: i64, is_active: bool) -> SqliteResult<User> {
+        if !is_active {
+            self.ensure_other_librarian(conn, user_id)?;
+        }
+
+        conn.execute("UPDATE users SET is_active = ?1 WHERE id = ?2", params![is_active, user_id])?;
+        info!("User {} {}", user_id, if is_active { "activated" } else { "deactivated" });
+        self.get_user(conn, user_id)
+    }
+
+    pub fn delete_user(&self, conn: &Connection, user_id: i64) -> SqliteResult<()> {
+        self.ensure_other_librarian(conn, user_id)?;
+
+        conn.execute("DELETE FROM users WHERE id = ?1", params![user_id])?;
+        info!("Deleted user {}", user_id);
+        Ok(())
+    }
+
+    // Lets a librarian set a new password for someone who lost theirs
+    pub fn reset_user_password(&self, conn: &Connection, user_id: i64, new_password: &str) -> SqliteResult<()> {
+        let user = self.get_user(conn, user_id)?;
+        self.set_password(conn, &user.username, new_password)
+    }
+
+    // Callers verify the current password or a recovery code first. A new password
+    // also lifts any sign-in lockout.
+    pub fn set_password(&self, conn: &Connection, username: &str, new_password: &str) -> SqliteResult<()> {
+        validate_new_password(new_password)?;
+
+        let updated = conn.execute(
+            "UPDATE users SET password = ?1, failed_login_attempts = 0, login_locked_until = NULL
+             WHERE username = ?2",
+            params![hash_secret(new_password)?, username],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+
+        info!("Password changed for user: {}", username);
         Ok(())
     }
 
+    // Replaces any unused codes with a new set; the plaintext codes are only returned here
+    pub fn generate_recovery_codes(&self, conn: &Connection, username: &str) -> SqliteResult<Vec<String>> {
+        let (user_id, _) = self.find_user(conn, username)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM recovery_codes WHERE user_id = ?1", params![user_id])?;
+        for code in &codes {
+            tx.execute(
+                "INSERT INTO recovery_codes (user_id, code_hash) VALUES (?1, ?2)",
+                params![user_id, hash_secret(&normalize_recovery_code(code))?],
+            )?;
+        }
+        tx.commit()?;
+
+        info!("Generated {} recovery codes for user: {}", codes.len(), username);
+        Ok(codes)
+    }
+
+    // Spends one recovery code to set a new password. Wrong codes count towards the
+    // same lockout as wrong passwords, so codes cannot be guessed without limit.
+    pub fn reset_password_with_recovery_code(
+        &self,
+        conn: &Connection,
+        username: &str,
+        recovery_code: &str,
+        new_password: &str
+    ) -> SqliteResult<()> {
+        validate_new_password(new_password)?;
+        let invalid = || rusqlite::Error::InvalidParameterName("Invalid username or recovery code".to_string());
+
+        let (user_id, _) = self.find_user(conn, username)?.ok_or_else(invalid)?;
+        let (failed_attempts, locked_until) = self.login_state(conn, user_id)?;
+        if let Some(until) = locked_until {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Too many failed attempts; try again in {} minute(s)",
+                (until - Utc::now()).num_minutes() + 1
+            )));
+        }
+        let code = normalize_recovery_code(recovery_code);
+
+        let unused_codes = {
+            let mut stmt = conn.prepare("SELECT id, code_hash FROM recovery_codes WHERE user_id = ?1 AND used_at IS NULL")?;
+            let rows = stmt.query_map(params![user_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+            rows.collect::<SqliteResult<Vec<_>>>()?
+        };
+        let Some((code_id, _)) = unused_codes
+            .into_iter()
+            .find(|(_, hash)| verify_secret(&code, hash))
+        else {
+            self.record_login_failure(conn, user_id, username, failed_attempts)?;
+            return Err(invalid());
+        };
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE recovery_codes SET used_at = ?1 WHERE id = ?2",
+            params![Utc::now().timestamp_millis(), code_id],
+        )?;
+        self.set_password(&tx, username, new_password)?;
+        tx.commit()?;
+
+        info!("Password reset with a recovery code for user: {}", username);
+        Ok(())
+    }
+
+    // Callers verify the password first
+    pub fn set_pin(&self, conn: &Connection, username: &str, pin: &str) -> SqliteResult<User> {
+        validate_pin(pin)?;
+        let (user_id, _) = self.find_user(conn, username)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        conn.execute(
+            "UPDATE users SET pin_hash = ?1, failed_pin_attempts = 0, pin_locked_until = NULL WHERE id = ?2",
+            params![hash_secret(pin)?, user_id],
+        )?;
+        info!("PIN set for user: {}", username);
+        self.get_user(conn, user_id)
+    }
+
+    pub fn clear_pin(&self, conn: &Connection, username: &str) -> SqliteResult<User> {
+        let (user_id, _) = self.find_user(conn, username)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        conn.execute(
+            "UPDATE users SET pin_hash = NULL, failed_pin_attempts = 0, pin_locked_until = NULL WHERE id = ?1",
+            params![user_id],
+        )?;
+        info!("PIN cleared for user: {}", username);
+        self.get_user(conn, user_id)
+    }
+
+    fn pin_state(&self, conn: &Connection, user_id: i64) -> SqliteResult<(String, u32, Option<DateTime<Utc>>)> {
+        let (pin_hash, failed_attempts, locked_until) = conn.query_row(
+            "SELECT pin_hash, failed_pin_attempts, pin_locked_until FROM users WHERE id = ?1 AND is_active = 1",
+            params![user_id],
+            |row| Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, Option<i64>>(2)?.map(|value| from_millis(value, 2)).transpose()?,
+            )),
+        )?;
+        let pin_hash = pin_hash
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("No PIN has been set".to_string()))?;
+
+        // A lockout that has run out no longer counts
+        Ok((pin_hash, failed_attempts, locked_until.filter(|until| *until > Utc::now())))
+    }
+
+    pub fn get_pin_status(&self, conn: &Connection, user_id: i64) -> SqliteResult<PinCheck> {
+        let (_, failed_attempts, locked_until) = self.pin_state(conn, user_id)?;
+
+        Ok(PinCheck {
+            verified: false,
+            attempts_remaining: pin_attempts_remaining(failed_attempts, locked_until.is_some()),
+            locked_until,
+        })
+    }
+
+    // Wrong PINs lock further attempts for a time that doubles with each failure;
+    // a correct PIN clears the count. Attempts during a lockout are not checked.
+    pub fn verify_pin(&self, conn: &Connection, user_id: i64, pin: &str) -> SqliteResult<PinCheck> {
+        let (pin_hash, failed_attempts, locked_until) = self.pin_state(conn, user_id)?;
+        if locked_until.is_some() {
+            return Ok(PinCheck { verified: false, attempts_remaining: 0, locked_until });
+        }
+
+        if verify_secret(pin, &pin_hash) {
+            conn.execute(
+                "UPDATE users SET failed_pin_attempts = 0, pin_locked_until = NULL WHERE id = ?1",
+                params![user_id],
+            )?;
+            return Ok(PinCheck { verified: true, attempts_remaining: PIN_FREE_ATTEMPTS, locked_until: None });
+        }
+
+        let failed_attempts = failed_attempts + 1;
+        let locked_until = lockout_secs(failed_attempts, PIN_FREE_ATTEMPTS)
+            .map(|secs| Utc::now() + Duration::seconds(secs));
+        conn.execute(
+            "UPDATE users SET failed_pin_attempts = ?1, pin_locked_until = ?2 WHERE id = ?3",
+            params![failed_attempts, locked_until.map(|until| until.timestamp_millis()), user_id],
+        )?;
+
+        if let Some(until) = locked_until {
+            info!("PIN locked for user {} until {} after {} wrong attempts", user_id, until, failed_attempts);
+        }
+        Ok(PinCheck {
+            verified: false,
+            attempts_remaining: pin_attempts_remaining(failed_attempts, locked_until.is_some()),
+            locked_until,
+        })
+    }
+
     pub fn user_exists(&self, conn: &Connection) -> SqliteResult<bool> {
         let mut stmt = conn.prepare("SELECT COUNT(*) FROM users")?;
         let count: i64 = stmt.query_row([], |row| row.get(0))?;
         Ok(count > 0)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (Connection, AuthDatabase) {
+        let conn = Connection::open_in_memory().unwrap();
+        let auth = AuthDatabase::init(&conn).unwrap();
+        let credentials = Credentials { username: "librarian".to_string(), password: "correct-horse".to_string() };
+        auth.create_user(&conn, &credentials, Role::Librarian).unwrap();
+        (conn, auth)
+    }
+
+    #[test]
+    fn create_user_rejects_short_passwords() {
+        let (conn, auth) = setup();
+        let credentials = Credentials { username: "assistant".to_string(), password: "short".to_string() };
+
+        assert!(auth.create_user(&conn, &credentials, Role::Assistant).is_err());
+        assert_eq!(auth.list_users(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn wrong_passwords_lock_sign_in() {
+        let (conn, auth) = setup();
+        for _ in 0..LOGIN_FREE_ATTEMPTS - 1 {
+            assert!(!auth.authenticate(&conn, "librarian", "wrong-password").unwrap());
+        }
+        assert!(auth.login_locked_until(&conn, "librarian").unwrap().is_none());
+
+        assert!(!auth.authenticate(&conn, "librarian", "wrong-password").unwrap());
+        assert!(auth.login_locked_until(&conn, "librarian").unwrap().is_some());
+        // The right password is not even checked during a lockout
+        assert!(!auth.authenticate(&conn, "librarian", "correct-horse").unwrap());
+    }
+
+    #[test]
+    fn expired_lockout_allows_sign_in_and_resets_the_count() {
+        let (conn, auth) = setup();
+        for _ in 0..LOGIN_FREE_ATTEMPTS {
+            auth.authenticate(&conn, "librarian", "wrong-password").unwrap();
+        }
+        conn.execute("UPDATE users SET login_locked_until = 1", []).unwrap();
+
+        assert!(auth.authenticate(&conn, "librarian", "correct-horse").unwrap());
+        let failed: u32 = conn.query_row("SELECT failed_login_attempts FROM users", [], |row| row.get(0)).unwrap();
+        assert_eq!(failed, 0);
+    }
+
+    #[test]
+    fn wrong_recovery_codes_share_the_lockout() {
+        let (conn, auth) = setup();
+        let codes = auth.generate_recovery_codes(&conn, "librarian").unwrap();
+        for _ in 0..LOGIN_FREE_ATTEMPTS {
+            assert!(auth.reset_password_with_recovery_code(&conn, "librarian", "AAAAA-AAAAA", "new-password").is_err());
+        }
+        assert!(auth.login_locked_until(&conn, "librarian").unwrap().is_some());
+
+        // Even a valid code is refused until the lockout ends
+        assert!(auth.reset_password_with_recovery_code(&conn, "librarian", &codes[0], "new-password").is_err());
+        conn.execute("UPDATE users SET login_locked_until = 1", []).unwrap();
+        auth.reset_password_with_recovery_code(&conn, "librarian", &codes[0], "new-password").unwrap();
+        assert!(auth.authenticate(&conn, "librarian", "new-password").unwrap());
+        assert!(auth.login_locked_until(&conn, "librarian").unwrap().is_none());
+    }
+}
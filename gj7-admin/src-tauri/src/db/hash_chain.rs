@@ -0,0 +1,622 @@
+// src/db/hash_chain.rs
+
+use log::info;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// Every insert, edit and delete of a chained record appends an entry here from
+// a trigger. Each entry stores the previous entry's hash and a hash over that
+// plus its own content, so changing or removing any entry breaks the links after it.
+const CREATE_HASH_CHAIN: &str = "
+    CREATE TABLE IF NOT EXISTS hash_chain (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        source TEXT NOT NULL,
+        record_id TEXT NOT NULL,
+        event TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        content TEXT NOT NULL,
+        prev_hash TEXT NOT NULL,
+        hash TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_hash_chain_record ON hash_chain (source, record_id, seq);
+
+    CREATE TRIGGER IF NOT EXISTS hash_chain_no_update BEFORE UPDATE ON hash_chain BEGIN
+        SELECT RAISE(ABORT, 'The hash chain is append-only');
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS hash_chain_no_delete BEFORE DELETE ON hash_chain BEGIN
+        SELECT RAISE(ABORT, 'The hash chain is append-only');
+    END;
+";
+
+// Name of the SQL function the triggers use to hash an entry
+const CHAIN_HASH_FUNCTION: &str = "chain_hash";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChainSource {
+    Attendance,
+    AuditLog,
+}
+
+impl ChainSource {
+    const ALL: [ChainSource; 2] = [ChainSource::Attendance, ChainSource::AuditLog];
+
+    // Also the name of the chained table
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChainSource::Attendance => "attendance",
+            ChainSource::AuditLog => "audit_log",
+        }
+    }
+
+    // The hashed columns, in order. Changing this list makes every existing
+    // record look altered, since its content no longer matches its last entry;
+    // new columns go in `later_columns` instead.
+    fn columns(&self) -> &'static [&'static str] {
+        match self {
+            ChainSource::Attendance => &[
+                "id", "school_id", "full_name", "time_in_date", "classification", "purpose_label",
+                "time_out_date", "semester_id", "is_manual", "entered_by", "is_deleted",
+            ],
+            ChainSource::AuditLog => &["id", "occurred_at", "username", "action", "target", "summary"],
+        }
+    }
+
+    // Columns added after the chain was introduced. They are only hashed once one
+    // of them is set, so records chained before they existed still verify.
+    fn later_columns(&self) -> &'static [&'static str] {
+        match self {
+            ChainSource::Attendance => &["device_id", "location"],
+            ChainSource::AuditLog => &[],
+        }
+    }
+
+    // Canonical JSON of the row referred to by `row`, e.g. `new` inside a trigger
+    fn content_sql(&self, row: &str) -> String {
+        let qualify = |columns: &[&str]| columns
+            .iter()
+            .map(|column| format!("{}.{}", row, column))
+            .collect::<Vec<_>>();
+
+        let columns = qualify(self.columns());
+        let later_columns = qualify(self.later_columns());
+        if later_columns.is_empty() {
+            return format!("json_array({})", columns.join(", "));
+        }
+
+        let unset = later_columns
+            .iter()
+            .map(|column| format!("{} IS NULL", column))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        format!(
+            "CASE WHEN {} THEN json_array({}) ELSE json_array({}, {}) END",
+            unset,
+            columns.join(", "),
+            columns.join(", "),
+            later_columns.join(", ")
+        )
+    }
+
+    // Matches the record with the id stored in `hash_chain.record_id` as text
+    fn id_matches_sql(&self, row: &str, record_id: &str) -> String {
+        match self {
+            ChainSource::Attendance => format!("{}.id = {}", row, record_id),
+            ChainSource::AuditLog => format!("{}.id = CAST({} AS INTEGER)", row, record_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChainEvent {
+    // Records that already existed when the chain was started
+    Snapshot,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChainEvent {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChainEvent::Snapshot => "Snapshot",
+            ChainEvent::Insert => "Insert",
+            ChainEvent::Update => "Update",
+            ChainEvent::Delete => "Delete",
+        }
+    }
+}
+
+// Appends an entry for `row`; `from` selects it when it is not a trigger row
+fn append_entry_sql(source: ChainSource, event: ChainEvent, row: &str, from: &str) -> String {
+    format!(
+        "INSERT INTO hash_chain (source, record_id, event, recorded_at, content, prev_hash, hash)
+         SELECT '{source}', record_id, '{event}', recorded_at, content, prev_hash,
+                {function}(prev_hash, '{source}', record_id, '{event}', recorded_at, content)
+         FROM (
+             SELECT CAST({row}.id AS TEXT) AS record_id,
+                    {content} AS content,
+                    CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER) AS recorded_at,
+                    COALESCE((SELECT hash FROM hash_chain ORDER BY seq DESC LIMIT 1), '') AS prev_hash
+             {from}
+         );",
+        source = source.as_str(),
+        event = event.as_str(),
+        function = CHAIN_HASH_FUNCTION,
+        row = row,
+        content = source.content_sql(row),
+        from = from,
+    )
+}
+
+// Recreated on every start so the triggers always hash the current column list
+fn create_triggers_sql(source: ChainSource) -> String {
+    let table = source.as_str();
+    let append_insert = append_entry_sql(source, ChainEvent::Insert, "new", "");
+    let append_update = append_entry_sql(source, ChainEvent::Update, "new", "");
+    let append_delete = append_entry_sql(source, ChainEvent::Delete, "old", "");
+
+    format!(
+        "DROP TRIGGER IF EXISTS hash_chain_{table}_insert;
+         DROP TRIGGER IF EXISTS hash_chain_{table}_update;
+         DROP TRIGGER IF EXISTS hash_chain_{table}_delete;
+
+         CREATE TRIGGER hash_chain_{table}_insert AFTER INSERT ON {table} BEGIN
+             {append_insert}
+         END;
+
+         CREATE TRIGGER hash_chain_{table}_update AFTER UPDATE ON {table}
+         WHEN {old_content} IS NOT {new_content} BEGIN
+             {append_update}
+         END;
+
+         CREATE TRIGGER hash_chain_{table}_delete AFTER DELETE ON {table} BEGIN
+             {append_delete}
+         END;",
+        old_content = source.content_sql("old"),
+        new_content = source.content_sql("new"),
+    )
+}
+
+// Fields are separated so that shifting text between them changes the hash
+fn chain_hash(
+    prev_hash: &str,
+    source: &str,
+    record_id: &str,
+    event: &str,
+    recorded_at: i64,
+    content: &str
+) -> String {
+    let mut hasher = Sha256::new();
+    for field in [prev_hash, source, record_id, event, &recorded_at.to_string(), content] {
+        hasher.update(field.as_bytes());
+        hasher.update([0x1f]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// Must be called on every connection that may write attendance or the audit
+// log, since the chain triggers call this function
+pub fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        CHAIN_HASH_FUNCTION,
+        6,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            Ok(chain_hash(
+                &ctx.get::<String>(0)?,
+                &ctx.get::<String>(1)?,
+                &ctx.get::<String>(2)?,
+                &ctx.get::<String>(3)?,
+                ctx.get::<i64>(4)?,
+                &ctx.get::<String>(5)?,
+            ))
+        },
+    )
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BrokenLink {
+    // The entry where the chain breaks; None for a record with no entry at all
+    pub seq: Option<i64>,
+    pub source: String,
+    pub record_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ChainVerification {
+    pub is_intact: bool,
+    pub entries_checked: u64,
+    pub records_checked: u64,
+    // Hash of the newest entry; comparing it with an earlier copy also shows
+    // whether entries were cut off the end
+    pub head_hash: Option<String>,
+    pub first_broken: Option<BrokenLink>,
+}
+
+struct ChainEntry {
+    seq: i64,
+    source: String,
+    record_id: String,
+    event: String,
+    recorded_at: i64,
+    content: String,
+    prev_hash: String,
+    hash: String,
+}
+
+fn row_to_chain_entry(row: &Row) -> Result<ChainEntry> {
+    Ok(ChainEntry {
+        seq: row.get(0)?,
+        source: row.get(1)?,
+        record_id: row.get(2)?,
+        event: row.get(3)?,
+        recorded_at: row.get(4)?,
+        content: row.get(5)?,
+        prev_hash: row.get(6)?,
+        hash: row.get(7)?,
+    })
+}
+
+#[derive(Clone)]
+pub struct HashChainDatabase;
+
+impl HashChainDatabase {
+    // Runs after the chained tables exist; the first run chains their current rows
+    pub fn init(conn: &Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_HASH_CHAIN)?;
+
+        let is_new: bool = conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM hash_chain)", [], |row| row.get(0))?;
+        let tx = conn.unchecked_transaction()?;
+        if is_new {
+            for source in ChainSource::ALL {
+                let snapshotted = Self::snapshot(&tx, source)?;
+                if snapshotted > 0 {
+                    info!("Started hash chain with {} existing {} rows", snapshotted, source.as_str());
+                }
+            }
+        }
+        for source in ChainSource::ALL {
+            tx.execute_batch(&create_triggers_sql(source))?;
+        }
+        tx.commit()?;
+
+        Ok(HashChainDatabase)
+    }
+
+    // One statement per row, so each entry links to the one appended before it
+    fn snapshot(conn: &Connection, source: ChainSource) -> Result<usize> {
+        let table = source.as_str();
+        let rowids = conn
+            .prepare(&format!("SELECT rowid FROM {} ORDER BY rowid", table))?
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut append = conn.prepare(&append_entry_sql(
+            source,
+            ChainEvent::Snapshot,
+            table,
+            &format!("FROM {} WHERE rowid = ?1", table),
+        ))?;
+        for rowid in &rowids {
+            append.execute(params![rowid])?;
+        }
+
+        Ok(rowids.len())
+    }
+
+    // Walks the chain oldest first, then checks every record against its latest entry
+    pub fn verify(&self, conn: &Connection) -> Result<ChainVerification> {
+        let mut verification = ChainVerification {
+            is_intact: true,
+            entries_checked: 0,
+            records_checked: 0,
+            head_hash: None,
+            first_broken: None,
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT seq, source, record_id, event, recorded_at, content, prev_hash, hash
+             FROM hash_chain ORDER BY seq"
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut expected_prev = String::new();
+
+        while let Some(row) = rows.next()? {
+            let entry = row_to_chain_entry(row)?;
+            verification.entries_checked += 1;
+
+            let reason = if entry.prev_hash != expected_prev {
+                Some("The entry does not link to the one before it; entries were removed or rewritten")
+            } else if entry.hash != chain_hash(
+                &entry.prev_hash,
+                &entry.source,
+                &entry.record_id,
+                &entry.event,
+                entry.recorded_at,
+                &entry.content
+            ) {
+                Some("The entry's contents do not match its hash")
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                verification.first_broken = Some(BrokenLink {
+                    seq: Some(entry.seq),
+                    source: entry.source,
+                    record_id: entry.record_id,
+                    reason: reason.to_string(),
+                });
+                break;
+            }
+            expected_prev = entry.hash;
+        }
+
+        if verification.first_broken.is_none() {
+            verification.head_hash = Some(expected_prev).filter(|hash| !hash.is_empty());
+            for source in ChainSource::ALL {
+                verification.records_checked += conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {}", source.as_str()),
+                    [],
+                    |row| row.get::<_, u64>(0),
+                )?;
+
+                verification.first_broken = Self::find_unchained_change(conn, source)?;
+                if verification.first_broken.is_some() {
+                    break;
+                }
+            }
+        }
+
+        verification.is_intact = verification.first_broken.is_none();
+        Ok(verification)
+    }
+
+    // A record that differs from its latest entry, or was deleted without one,
+    // was changed with the triggers bypassed
+    fn find_unchained_change(conn: &Connection, source: ChainSource) -> Result<Option<BrokenLink>> {
+        let table = source.as_str();
+        let latest_entry = format!(
+            "c.seq = (SELECT MAX(seq) FROM hash_chain WHERE source = '{}' AND record_id = c.record_id)",
+            table
+        );
+
+        let changed = conn.query_row(
+            &format!(
+                "SELECT c.seq, CAST(t.id AS TEXT)
+                 FROM {table} t
+                 LEFT JOIN hash_chain c
+                   ON c.source = '{table}'
+                  AND c.record_id = CAST(t.id AS TEXT)
+                  AND {latest_entry}
+                 WHERE c.seq IS NULL OR c.event = 'Delete' OR c.content IS NOT {content}
+                 ORDER BY c.seq IS NOT NULL, c.seq, t.rowid
+                 LIMIT 1",
+                table = table,
+                latest_entry = latest_entry,
+                content = source.content_sql("t"),
+            ),
+            [],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, String>(1)?)),
+        ).optional()?;
+        if let Some((seq, record_id)) = changed {
+            let reason = if seq.is_some() {
+                "The record no longer matches its latest chain entry"
+            } else {
+                "The record was added without a chain entry"
+            };
+            return Ok(Some(BrokenLink { seq, source: table.to_string(), record_id, reason: reason.to_string() }));
+        }
+
+        let removed = conn.query_row(
+            &format!(
+                "SELECT c.seq, c.record_id
+                 FROM hash_chain c
+                 WHERE c.source = '{table}'
+                   AND c.event != 'Delete'
+                   AND {latest_entry}
+                   AND NOT EXISTS (SELECT 1 FROM {table} t WHERE {id_matches})
+                 ORDER BY c.seq
+                 LIMIT 1",
+                table = table,
+                latest_entry = latest_entry,
+                id_matches = source.id_matches_sql("t", "c.record_id"),
+            ),
+            [],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+        ).optional()?;
+
+        Ok(removed.map(|(seq, record_id)| BrokenLink {
+            seq: Some(seq),
+            source: table.to_string(),
+            record_id,
+            reason: "The record was deleted without a chain entry".to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the hashed columns of the chained tables
+    const CREATE_CHAINED_TABLES: &str = "
+        CREATE TABLE attendance (
+            id TEXT PRIMARY KEY,
+            school_id TEXT NOT NULL,
+            full_name TEXT NOT NULL,
+            time_in_date INTEGER NOT NULL,
+            classification TEXT NOT NULL,
+            purpose_label TEXT,
+            time_out_date INTEGER,
+            semester_id TEXT,
+            is_manual INTEGER NOT NULL DEFAULT 0,
+            entered_by TEXT,
+            is_deleted INTEGER NOT NULL DEFAULT 0,
+            device_id TEXT,
+            location TEXT
+        );
+
+        CREATE TABLE audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            occurred_at INTEGER NOT NULL,
+            username TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target TEXT,
+            summary TEXT
+        );
+    ";
+
+    fn setup() -> (Connection, HashChainDatabase) {
+        let conn = Connection::open_in_memory().unwrap();
+        register_functions(&conn).unwrap();
+        conn.execute_batch(CREATE_CHAINED_TABLES).unwrap();
+        let chain = HashChainDatabase::init(&conn).unwrap();
+        (conn, chain)
+    }
+
+    fn insert_attendance(conn: &Connection, id: &str, full_name: &str) {
+        conn.execute(
+            "INSERT INTO attendance (id, school_id, full_name, time_in_date, classification)
+             VALUES (?1, '2024-0001', ?2, 1700000000000, 'Student')",
+            params![id, full_name],
+        ).unwrap();
+    }
+
+    fn events(conn: &Connection) -> Vec<(String, String, String)> {
+        conn.prepare("SELECT source, record_id, event FROM hash_chain ORDER BY seq")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    fn entry(source: &str, record_id: &str, event: &str) -> (String, String, String) {
+        (source.to_string(), record_id.to_string(), event.to_string())
+    }
+
+    #[test]
+    fn triggers_chain_inserts_updates_and_deletes() {
+        let (conn, chain) = setup();
+        insert_attendance(&conn, "a", "Juan Dela Cruz");
+        conn.execute("UPDATE attendance SET full_name = 'Juan D. Cruz' WHERE id = 'a'", []).unwrap();
+        // An update that changes no hashed column adds no entry
+        conn.execute("UPDATE attendance SET full_name = 'Juan D. Cruz' WHERE id = 'a'", []).unwrap();
+        conn.execute("DELETE FROM attendance WHERE id = 'a'", []).unwrap();
+        conn.execute(
+            "INSERT INTO audit_log (occurred_at, username, action) VALUES (1700000000000, 'librarian', 'Login')",
+            [],
+        ).unwrap();
+
+        assert_eq!(events(&conn), vec![
+            entry("attendance", "a", "Insert"),
+            entry("attendance", "a", "Update"),
+            entry("attendance", "a", "Delete"),
+            entry("audit_log", "1", "Insert"),
+        ]);
+
+        let verification = chain.verify(&conn).unwrap();
+        assert!(verification.is_intact);
+        assert_eq!(verification.entries_checked, 4);
+        let head: String = conn.query_row("SELECT hash FROM hash_chain ORDER BY seq DESC LIMIT 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(verification.head_hash, Some(head));
+    }
+
+    #[test]
+    fn init_snapshots_existing_rows() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_functions(&conn).unwrap();
+        conn.execute_batch(CREATE_CHAINED_TABLES).unwrap();
+        insert_attendance(&conn, "a", "Juan Dela Cruz");
+        insert_attendance(&conn, "b", "Maria Clara");
+
+        let chain = HashChainDatabase::init(&conn).unwrap();
+        assert_eq!(events(&conn), vec![entry("attendance", "a", "Snapshot"), entry("attendance", "b", "Snapshot")]);
+        assert!(chain.verify(&conn).unwrap().is_intact);
+
+        // A restart neither snapshots again nor breaks the chain
+        HashChainDatabase::init(&conn).unwrap();
+        assert_eq!(events(&conn).len(), 2);
+    }
+
+    #[test]
+    fn verify_reports_a_row_edited_past_the_triggers() {
+        let (conn, chain) = setup();
+        insert_attendance(&conn, "a", "Juan Dela Cruz");
+        insert_attendance(&conn, "b", "Maria Clara");
+        conn.execute_batch(
+            "DROP TRIGGER hash_chain_attendance_update;
+             UPDATE attendance SET classification = 'Faculty' WHERE id = 'b';"
+        ).unwrap();
+
+        let verification = chain.verify(&conn).unwrap();
+        assert!(!verification.is_intact);
+        let broken = verification.first_broken.unwrap();
+        assert_eq!(broken.source, "attendance");
+        assert_eq!(broken.record_id, "b");
+        assert_eq!(broken.seq, Some(2));
+        assert_eq!(broken.reason, "The record no longer matches its latest chain entry");
+    }
+
+    #[test]
+    fn verify_reports_a_row_deleted_past_the_triggers() {
+        let (conn, chain) = setup();
+        insert_attendance(&conn, "a", "Juan Dela Cruz");
+        conn.execute_batch(
+            "DROP TRIGGER hash_chain_attendance_delete;
+             DELETE FROM attendance WHERE id = 'a';"
+        ).unwrap();
+
+        let broken = chain.verify(&conn).unwrap().first_broken.unwrap();
+        assert_eq!(broken.record_id, "a");
+        assert_eq!(broken.reason, "The record was deleted without a chain entry");
+    }
+
+    #[test]
+    fn chain_entries_cannot_be_edited_or_removed() {
+        let (conn, _) = setup();
+        insert_attendance(&conn, "a", "Juan Dela Cruz");
+
+        assert!(conn.execute("UPDATE hash_chain SET content = '[]'", []).is_err());
+        assert!(conn.execute("DELETE FROM hash_chain", []).is_err());
+    }
+
+    #[test]
+    fn verify_reports_the_first_edited_chain_entry() {
+        let (conn, chain) = setup();
+        for (id, full_name) in [("a", "Juan Dela Cruz"), ("b", "Maria Clara"), ("c", "Jose Rizal")] {
+            insert_attendance(&conn, id, full_name);
+        }
+        conn.execute_batch(
+            "DROP TRIGGER hash_chain_no_update;
+             UPDATE hash_chain SET content = replace(content, 'Maria Clara', 'Someone Else') WHERE seq = 2;"
+        ).unwrap();
+
+        let broken = chain.verify(&conn).unwrap().first_broken.unwrap();
+        assert_eq!(broken.seq, Some(2));
+        assert_eq!(broken.record_id, "b");
+        assert_eq!(broken.reason, "The entry's contents do not match its hash");
+    }
+
+    #[test]
+    fn verify_reports_a_removed_chain_entry() {
+        let (conn, chain) = setup();
+        for (id, full_name) in [("a", "Juan Dela Cruz"), ("b", "Maria Clara"), ("c", "Jose Rizal")] {
+            insert_attendance(&conn, id, full_name);
+        }
+        conn.execute_batch(
+            "DROP TRIGGER hash_chain_no_delete;
+             DELETE FROM hash_chain WHERE seq = 2;"
+        ).unwrap();
+
+        let broken = chain.verify(&conn).unwrap().first_broken.unwrap();
+        assert_eq!(broken.seq, Some(3));
+        assert_eq!(broken.reason, "The entry does not link to the one before it; entries were removed or rewritten");
+    }
+}
@@ -0,0 +1,45 @@
+// src/db/idle_lock.rs
+
+use std::time::Duration;
+use rusqlite::{Connection, Result};
+use serde::{Serialize, Deserialize};
+use log::info;
+
+use crate::db::app_settings::AppSettingsDatabase;
+
+const IDLE_LOCK_MINUTES_KEY: &str = "security.idle_lock_minutes";
+const DEFAULT_IDLE_LOCK_MINUTES: u32 = 10;
+const MAX_IDLE_LOCK_MINUTES: u32 = 24 * 60;
+
+// How long the admin app may sit unused before it locks
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct IdleLockSettings {
+    // 0 turns the idle lock off
+    pub idle_minutes: u32,
+}
+
+impl IdleLockSettings {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let idle_minutes = AppSettingsDatabase.get_parsed_setting::<u32>(conn, IDLE_LOCK_MINUTES_KEY)?
+            .unwrap_or(DEFAULT_IDLE_LOCK_MINUTES);
+
+        Ok(IdleLockSettings { idle_minutes })
+    }
+
+    pub fn save(conn: &Connection, settings: IdleLockSettings) -> Result<Self> {
+        if settings.idle_minutes > MAX_IDLE_LOCK_MINUTES {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Idle lock cannot be longer than {} minutes",
+                MAX_IDLE_LOCK_MINUTES
+            )));
+        }
+
+        AppSettingsDatabase.set_setting(conn, IDLE_LOCK_MINUTES_KEY, &settings.idle_minutes.to_string())?;
+        info!("Idle lock set to {} minutes", settings.idle_minutes);
+        Ok(settings)
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        (self.idle_minutes > 0).then(|| Duration::from_secs(u64::from(self.idle_minutes) * 60))
+    }
+}
@@ -0,0 +1,424 @@
+// src/db/kiosk_devices.rs
+
+use log::{info, warn};
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::db::add_column_if_missing;
+use crate::db::attendance::from_millis;
+
+const PAIRING_CODE_LENGTH: usize = 8;
+// Leaves out characters that are easy to misread (0/O, 1/I/L)
+const PAIRING_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const PAIRING_CODE_TTL_MINUTES: i64 = 10;
+// Wrong codes allowed before the outstanding code is thrown away
+const PAIRING_MAX_FAILED_ATTEMPTS: u32 = 5;
+const DEVICE_TOKEN_BYTES: usize = 32;
+const MAX_DEVICE_NAME_LENGTH: usize = 64;
+const MAX_LOCATION_LENGTH: usize = 64;
+const MAX_APP_VERSION_LENGTH: usize = 32;
+// Kiosks send a heartbeat over their websocket this often
+pub const HEARTBEAT_INTERVAL_SECS: i64 = 30;
+// A kiosk that has sent no heartbeat for this long is shown as offline
+const OFFLINE_AFTER_SECS: i64 = HEARTBEAT_INTERVAL_SECS * 5 / 2;
+
+// Only one pairing code is outstanding at a time. Device tokens are stored as
+// SHA-256 hashes; the token itself is only handed to the kiosk when it pairs.
+const CREATE_KIOSK_TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS kiosk_pairing_codes (
+        code TEXT PRIMARY KEY,
+        expires_at INTEGER NOT NULL,
+        failed_attempts INTEGER NOT NULL DEFAULT 0,
+        created_by TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS kiosk_devices (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        token_hash TEXT NOT NULL UNIQUE,
+        paired_at INTEGER NOT NULL,
+        paired_by TEXT NOT NULL,
+        revoked_at INTEGER,
+        location TEXT,
+        app_version TEXT,
+        last_heartbeat_at INTEGER,
+        disconnected_at INTEGER
+    );
+";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KioskDevice {
+    pub id: i64,
+    pub name: String,
+    pub paired_at: DateTime<Utc>,
+    // The user who generated the pairing code
+    pub paired_by: String,
+    pub revoked_at: Option<DateTime<Utc>>,
+    // Location and version are reported by the kiosk in its heartbeats
+    pub location: Option<String>,
+    pub app_version: Option<String>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    // Connected, with a recent heartbeat
+    pub is_online: bool,
+}
+
+impl KioskDevice {
+    // Recorded on the visits it scans; the kiosk's name until it reports a location
+    pub fn location_label(&self) -> &str {
+        self.location.as_deref().unwrap_or(&self.name)
+    }
+}
+
+// Sent by a kiosk over its websocket every HEARTBEAT_INTERVAL_SECS
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct KioskHeartbeat {
+    pub app_version: Option<String>,
+    pub location: Option<String>,
+}
+
+// Sent by a kiosk to the network server's /pair route
+#[derive(Debug, Deserialize)]
+pub struct PairRequest {
+    pub code: String,
+    pub device_name: String,
+}
+
+// The token is only ever returned here
+#[derive(Debug, Serialize)]
+pub struct PairedDevice {
+    pub device: KioskDevice,
+    pub token: String,
+}
+
+const KIOSK_DEVICE_COLUMNS: &str =
+    "id, name, paired_at, paired_by, revoked_at, location, app_version, last_heartbeat_at, disconnected_at";
+
+fn row_to_kiosk_device(row: &Row) -> Result<KioskDevice> {
+    let revoked_at = row.get::<_, Option<i64>>(4)?;
+    let last_heartbeat_at = row.get::<_, Option<i64>>(7)?;
+    let disconnected_at = row.get::<_, Option<i64>>(8)?;
+
+    let is_online = match last_heartbeat_at {
+        Some(last_heartbeat_at) => revoked_at.is_none()
+            && !matches!(disconnected_at, Some(disconnected_at) if disconnected_at >= last_heartbeat_at)
+            && Utc::now().timestamp_millis() - last_heartbeat_at < OFFLINE_AFTER_SECS * 1000,
+        None => false,
+    };
+
+    Ok(KioskDevice {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        paired_at: from_millis(row.get(2)?, 2)?,
+        paired_by: row.get(3)?,
+        revoked_at: revoked_at.map(|millis| from_millis(millis, 4)).transpose()?,
+        location: row.get(5)?,
+        app_version: row.get(6)?,
+        last_heartbeat_at: last_heartbeat_at.map(|millis| from_millis(millis, 7)).transpose()?,
+        is_online,
+    })
+}
+
+// Blank values leave the stored one unchanged
+fn heartbeat_field(value: &Option<String>, field: &str, max_length: usize) -> Result<Option<String>> {
+    let value = value.as_deref().map(str::trim).filter(|value| !value.is_empty());
+    if value.is_some_and(|value| value.chars().count() > max_length) {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "{} must be at most {} characters",
+            field,
+            max_length
+        )));
+    }
+    Ok(value.map(str::to_string))
+}
+
+fn normalize_pairing_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn generate_pairing_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..PAIRING_CODE_LENGTH)
+        .map(|_| PAIRING_CODE_ALPHABET[rng.gen_range(0..PAIRING_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+fn generate_device_token() -> String {
+    let bytes: [u8; DEVICE_TOKEN_BYTES] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hash_device_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Clone)]
+pub struct KioskDeviceDatabase;
+
+impl KioskDeviceDatabase {
+    pub fn init(conn: &Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_KIOSK_TABLES)?;
+        add_column_if_missing(conn, "kiosk_devices", "location", "TEXT")?;
+        add_column_if_missing(conn, "kiosk_devices", "app_version", "TEXT")?;
+        add_column_if_missing(conn, "kiosk_devices", "last_heartbeat_at", "INTEGER")?;
+        add_column_if_missing(conn, "kiosk_devices", "disconnected_at", "INTEGER")?;
+        Ok(KioskDeviceDatabase)
+    }
+
+    // Replaces any outstanding code
+    pub fn create_pairing_code(&self, conn: &Connection, created_by: &str) -> Result<PairingCode> {
+        let code = generate_pairing_code();
+        let expires_at = Utc::now() + Duration::minutes(PAIRING_CODE_TTL_MINUTES);
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM kiosk_pairing_codes", [])?;
+        tx.execute(
+            "INSERT INTO kiosk_pairing_codes (code, expires_at, created_by) VALUES (?1, ?2, ?3)",
+            params![code, expires_at.timestamp_millis(), created_by],
+        )?;
+        tx.commit()?;
+
+        info!("Pairing code created by {}", created_by);
+        Ok(PairingCode {
+            code: format!("{}-{}", &code[..PAIRING_CODE_LENGTH / 2], &code[PAIRING_CODE_LENGTH / 2..]),
+            expires_at,
+        })
+    }
+
+    // Spends the pairing code and registers the kiosk under a new token
+    pub fn pair(&self, conn: &Connection, request: &PairRequest) -> Result<PairedDevice> {
+        let name = request.device_name.trim();
+        if name.is_empty() || name.chars().count() > MAX_DEVICE_NAME_LENGTH {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Device name must be 1 to {} characters",
+                MAX_DEVICE_NAME_LENGTH
+            )));
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM kiosk_pairing_codes WHERE expires_at <= ?1", params![now])?;
+
+        let code = normalize_pairing_code(&request.code);
+        let paired_by: Option<String> = tx.query_row(
+            "SELECT created_by FROM kiosk_pairing_codes WHERE code = ?1",
+            params![code],
+            |row| row.get(0),
+        ).optional()?;
+
+        let Some(paired_by) = paired_by else {
+            // Guessing is cut short by discarding the code after a few misses
+            tx.execute("UPDATE kiosk_pairing_codes SET failed_attempts = failed_attempts + 1", [])?;
+            let discarded = tx.execute(
+                "DELETE FROM kiosk_pairing_codes WHERE failed_attempts >= ?1",
+                params![PAIRING_MAX_FAILED_ATTEMPTS],
+            )?;
+            tx.commit()?;
+
+            if discarded > 0 {
+                warn!("Pairing code discarded after {} wrong attempts", PAIRING_MAX_FAILED_ATTEMPTS);
+            }
+            return Err(rusqlite::Error::InvalidParameterName("Invalid or expired pairing code".to_string()));
+        };
+
+        tx.execute("DELETE FROM kiosk_pairing_codes WHERE code = ?1", params![code])?;
+        let token = generate_device_token();
+        tx.execute(
+            "INSERT INTO kiosk_devices (name, token_hash, paired_at, paired_by) VALUES (?1, ?2, ?3, ?4)",
+            params![name, hash_device_token(&token), now, paired_by],
+        )?;
+        let device = Self::get_device(&tx, tx.last_insert_rowid())?;
+        tx.commit()?;
+
+        info!("Kiosk {} paired (device {})", device.name, device.id);
+        Ok(PairedDevice { device, token })
+    }
+
+    // The device a token belongs to, unless it was revoked
+    pub fn authenticate(&self, conn: &Connection, token: &str) -> Result<Option<KioskDevice>> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM kiosk_devices WHERE token_hash = ?1 AND revoked_at IS NULL",
+                KIOSK_DEVICE_COLUMNS
+            ),
+            params![hash_device_token(token)],
+            row_to_kiosk_device,
+        ).optional()
+    }
+
+    // For connections that outlive a single request, such as websockets.
+    // None when the device has been revoked since it connected.
+    pub fn get_active_device(&self, conn: &Connection, device_id: i64) -> Result<Option<KioskDevice>> {
+        conn.query_row(
+            &format!(
+                "SELECT {} FROM kiosk_devices WHERE id = ?1 AND revoked_at IS NULL",
+                KIOSK_DEVICE_COLUMNS
+            ),
+            params![device_id],
+            row_to_kiosk_device,
+        ).optional()
+    }
+
+    // Also called when a kiosk connects. None when the device has been revoked.
+    pub fn record_heartbeat(
+        &self,
+        conn: &Connection,
+        device_id: i64,
+        heartbeat: &KioskHeartbeat
+    ) -> Result<Option<KioskDevice>> {
+        let location = heartbeat_field(&heartbeat.location, "Location", MAX_LOCATION_LENGTH)?;
+        let app_version = heartbeat_field(&heartbeat.app_version, "App version", MAX_APP_VERSION_LENGTH)?;
+
+        let updated = conn.execute(
+            "UPDATE kiosk_devices
+             SET last_heartbeat_at = ?1,
+                 location = COALESCE(?2, location),
+                 app_version = COALESCE(?3, app_version)
+             WHERE id = ?4 AND revoked_at IS NULL",
+            params![Utc::now().timestamp_millis(), location, app_version, device_id],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+
+        Self::get_device(conn, device_id).map(Some)
+    }
+
+    // Called when the kiosk's last connection closes, so it shows as offline
+    // right away instead of after missing heartbeats
+    pub fn mark_disconnected(&self, conn: &Connection, device_id: i64) -> Result<()> {
+        conn.execute(
+            "UPDATE kiosk_devices SET disconnected_at = ?1 WHERE id = ?2",
+            params![Utc::now().timestamp_millis(), device_id],
+        )?;
+        Ok(())
+    }
+
+    // Active devices first, newest first
+    pub fn list_devices(&self, conn: &Connection) -> Result<Vec<KioskDevice>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM kiosk_devices ORDER BY revoked_at IS NOT NULL, paired_at DESC",
+            KIOSK_DEVICE_COLUMNS
+        ))?;
+        let devices = stmt.query_map([], row_to_kiosk_device)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(devices)
+    }
+
+    pub fn revoke_device(&self, conn: &Connection, device_id: i64) -> Result<KioskDevice> {
+        let updated = conn.execute(
+            "UPDATE kiosk_devices SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![Utc::now().timestamp_millis(), device_id],
+        )?;
+        if updated == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+
+        let device = Self::get_device(conn, device_id)?;
+        info!("Kiosk {} revoked (device {})", device.name, device.id);
+        Ok(device)
+    }
+
+    fn get_device(conn: &Connection, device_id: i64) -> Result<KioskDevice> {
+        conn.query_row(
+            &format!("SELECT {} FROM kiosk_devices WHERE id = ?1", KIOSK_DEVICE_COLUMNS),
+            params![device_id],
+            row_to_kiosk_device,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (Connection, KioskDeviceDatabase) {
+        let conn = Connection::open_in_memory().unwrap();
+        let kiosks = KioskDeviceDatabase::init(&conn).unwrap();
+        (conn, kiosks)
+    }
+
+    fn pair_request(code: &str) -> PairRequest {
+        PairRequest { code: code.to_string(), device_name: "North gate".to_string() }
+    }
+
+    #[test]
+    fn pairing_code_pairs_one_kiosk() {
+        let (conn, kiosks) = setup();
+        let pairing_code = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+
+        // Codes are shown as XXXX-XXXX but typed any which way
+        let paired = kiosks.pair(&conn, &pair_request(&pairing_code.code.to_lowercase())).unwrap();
+        assert_eq!(paired.device.name, "North gate");
+        assert_eq!(paired.device.paired_by, "librarian");
+        let authenticated = kiosks.authenticate(&conn, &paired.token).unwrap().unwrap();
+        assert_eq!(authenticated.id, paired.device.id);
+
+        assert!(kiosks.pair(&conn, &pair_request(&pairing_code.code)).is_err());
+    }
+
+    #[test]
+    fn new_pairing_code_replaces_the_outstanding_one() {
+        let (conn, kiosks) = setup();
+        let first = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+        let second = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+
+        assert!(kiosks.pair(&conn, &pair_request(&first.code)).is_err());
+        assert!(kiosks.pair(&conn, &pair_request(&second.code)).is_ok());
+    }
+
+    #[test]
+    fn expired_pairing_code_is_refused() {
+        let (conn, kiosks) = setup();
+        let pairing_code = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+        conn.execute("UPDATE kiosk_pairing_codes SET expires_at = ?1", params![Utc::now().timestamp_millis()]).unwrap();
+
+        assert!(kiosks.pair(&conn, &pair_request(&pairing_code.code)).is_err());
+        let outstanding: i64 = conn.query_row("SELECT COUNT(*) FROM kiosk_pairing_codes", [], |row| row.get(0)).unwrap();
+        assert_eq!(outstanding, 0);
+    }
+
+    #[test]
+    fn pairing_code_survives_fewer_wrong_attempts_than_the_limit() {
+        let (conn, kiosks) = setup();
+        let pairing_code = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+        for _ in 0..PAIRING_MAX_FAILED_ATTEMPTS - 1 {
+            assert!(kiosks.pair(&conn, &pair_request("WRONG-CODE")).is_err());
+        }
+
+        assert!(kiosks.pair(&conn, &pair_request(&pairing_code.code)).is_ok());
+    }
+
+    #[test]
+    fn pairing_code_is_discarded_after_too_many_wrong_attempts() {
+        let (conn, kiosks) = setup();
+        let pairing_code = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+        for _ in 0..PAIRING_MAX_FAILED_ATTEMPTS {
+            assert!(kiosks.pair(&conn, &pair_request("WRONG-CODE")).is_err());
+        }
+
+        assert!(kiosks.pair(&conn, &pair_request(&pairing_code.code)).is_err());
+    }
+
+    #[test]
+    fn revoked_token_no_longer_authenticates() {
+        let (conn, kiosks) = setup();
+        let pairing_code = kiosks.create_pairing_code(&conn, "librarian").unwrap();
+        let paired = kiosks.pair(&conn, &pair_request(&pairing_code.code)).unwrap();
+
+        kiosks.revoke_device(&conn, paired.device.id).unwrap();
+        assert!(kiosks.authenticate(&conn, &paired.token).unwrap().is_none());
+        assert!(kiosks.get_active_device(&conn, paired.device.id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,107 @@
+// src/db/library_timezone.rs
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset, TimeZone, Utc};
+use rusqlite::{Connection, Result};
+use log::info;
+
+use crate::db::app_settings::AppSettingsDatabase;
+
+const UTC_OFFSET_KEY: &str = "library.utc_offset";
+
+// The library's wall clock, used for every day and week boundary. Kept as a
+// fixed UTC offset ("+08:00") so SQLite date functions can apply it directly
+#[derive(Debug, Clone, Copy)]
+pub struct LibraryTimezone {
+    offset: FixedOffset,
+}
+
+impl LibraryTimezone {
+    pub fn new(offset: FixedOffset) -> Self {
+        LibraryTimezone { offset }
+    }
+
+    // The configured offset. Until one is set, this machine's offset on first start
+    // is saved and used, so day boundaries stay put when daylight saving changes the clock.
+    pub fn load(conn: &Connection) -> Result<Self> {
+        if let Some(offset) = AppSettingsDatabase.get_parsed_setting::<FixedOffset>(conn, UTC_OFFSET_KEY)? {
+            return Ok(LibraryTimezone::new(offset));
+        }
+
+        let offset = Local::now().offset().fix();
+        AppSettingsDatabase.set_setting(conn, UTC_OFFSET_KEY, &offset.to_string())?;
+        info!("Library timezone defaulted to this machine's UTC{}", offset);
+        Ok(LibraryTimezone::new(offset))
+    }
+
+    pub fn save(conn: &Connection, utc_offset: &str) -> Result<Self> {
+        let offset: FixedOffset = utc_offset.trim().parse()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("UTC offset must look like +08:00".to_string()))?;
+
+        AppSettingsDatabase.set_setting(conn, UTC_OFFSET_KEY, &offset.to_string())?;
+        info!("Library timezone set to UTC{}", offset);
+        Ok(LibraryTimezone::new(offset))
+    }
+
+    pub fn utc_offset(&self) -> String {
+        self.offset.to_string()
+    }
+
+    pub fn local(&self, value: DateTime<Utc>) -> DateTime<FixedOffset> {
+        value.with_timezone(&self.offset)
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.local(Utc::now()).date_naive()
+    }
+
+    // Library midnight at the start of `date`
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.offset
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    // Arguments for strftime() that read an epoch-millisecond column as library time
+    pub fn sql_local_time(&self, column: &str) -> String {
+        format!("{} / 1000, 'unixepoch', '{:+} seconds'", column, self.offset.local_minus_utc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        AppSettingsDatabase::init(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn first_load_saves_the_machine_offset() {
+        let conn = setup();
+        let timezone = LibraryTimezone::load(&conn).unwrap();
+
+        let stored = AppSettingsDatabase.get_setting(&conn, UTC_OFFSET_KEY).unwrap();
+        assert_eq!(stored, Some(timezone.utc_offset()));
+    }
+
+    #[test]
+    fn stored_offset_is_used_as_it_is() {
+        let conn = setup();
+        AppSettingsDatabase.set_setting(&conn, UTC_OFFSET_KEY, "-03:30").unwrap();
+
+        assert_eq!(LibraryTimezone::load(&conn).unwrap().utc_offset(), "-03:30");
+    }
+
+    #[test]
+    fn days_start_at_library_midnight() {
+        let conn = setup();
+        let timezone = LibraryTimezone::save(&conn, "+08:00").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        assert_eq!(timezone.start_of_day(date), Utc.with_ymd_and_hms(2024, 2, 29, 16, 0, 0).unwrap());
+        assert!(LibraryTimezone::save(&conn, "8 hours").is_err());
+    }
+}
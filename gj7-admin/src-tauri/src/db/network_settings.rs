@@ -0,0 +1,89 @@
+// src/db/network_settings.rs
+
+use std::net::{IpAddr, SocketAddr};
+use rusqlite::{Connection, Result};
+use serde::{Serialize, Deserialize};
+use log::info;
+
+use crate::db::app_settings::AppSettingsDatabase;
+
+const BIND_ADDRESS_KEY: &str = "network.bind_address";
+const PORT_KEY: &str = "network.port";
+const TLS_ENABLED_KEY: &str = "network.tls_enabled";
+const SERVER_ID_KEY: &str = "network.server_id";
+
+// All network interfaces, as the server always listened on before
+const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8080;
+
+// Where the kiosk network server listens and whether it uses HTTPS/WSS
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkServerSettings {
+    pub bind_address: String,
+    pub port: u16,
+    // Serves a self-signed certificate; kiosks pin its key when they pair
+    pub tls_enabled: bool,
+}
+
+impl NetworkServerSettings {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let bind_address = AppSettingsDatabase.get_setting(conn, BIND_ADDRESS_KEY)?
+            .filter(|address| address.parse::<IpAddr>().is_ok())
+            .unwrap_or_else(|| DEFAULT_BIND_ADDRESS.to_string());
+        let port = AppSettingsDatabase.get_parsed_setting::<u16>(conn, PORT_KEY)?
+            .filter(|port| *port != 0)
+            .unwrap_or(DEFAULT_PORT);
+        let tls_enabled = AppSettingsDatabase.get_parsed_setting::<bool>(conn, TLS_ENABLED_KEY)?
+            .unwrap_or(false);
+
+        Ok(NetworkServerSettings { bind_address, port, tls_enabled })
+    }
+
+    pub fn save(conn: &Connection, settings: NetworkServerSettings) -> Result<Self> {
+        let bind_address = settings.bind_address.trim().parse::<IpAddr>()
+            .map_err(|_| rusqlite::Error::InvalidParameterName(format!(
+                "{} is not an IP address; use 0.0.0.0 to listen on every interface",
+                settings.bind_address.trim()
+            )))?;
+        if settings.port == 0 {
+            return Err(rusqlite::Error::InvalidParameterName("Port must be between 1 and 65535".to_string()));
+        }
+
+        let settings = NetworkServerSettings {
+            bind_address: bind_address.to_string(),
+            ..settings
+        };
+
+        let tx = conn.unchecked_transaction()?;
+        AppSettingsDatabase.set_setting(&tx, BIND_ADDRESS_KEY, &settings.bind_address)?;
+        AppSettingsDatabase.set_setting(&tx, PORT_KEY, &settings.port.to_string())?;
+        AppSettingsDatabase.set_setting(&tx, TLS_ENABLED_KEY, &settings.tls_enabled.to_string())?;
+        tx.commit()?;
+
+        info!(
+            "Network server set to {} ({})",
+            settings.socket_addr(),
+            if settings.tls_enabled { "HTTPS" } else { "HTTP" }
+        );
+        Ok(settings)
+    }
+
+    // Falls back to every interface if the stored address no longer parses
+    pub fn socket_addr(&self) -> SocketAddr {
+        let address = self.bind_address.parse()
+            .unwrap_or(IpAddr::from([0, 0, 0, 0]));
+        SocketAddr::new(address, self.port)
+    }
+}
+
+// Sent in discovery beacons so kiosks can tell this server apart from others on
+// the same network. Created the first time it is needed and kept from then on.
+pub fn load_server_id(conn: &Connection) -> Result<String> {
+    if let Some(server_id) = AppSettingsDatabase.get_setting(conn, SERVER_ID_KEY)? {
+        return Ok(server_id);
+    }
+
+    let server_id = uuid::Uuid::new_v4().to_string();
+    AppSettingsDatabase.set_setting(conn, SERVER_ID_KEY, &server_id)?;
+    Ok(server_id)
+}
@@ -5,6 +5,8 @@ use log::info;
 use rusqlite::{Connection, Result as SqliteResult, params, Row};
 use serde::{Serialize, Deserialize};
 
+use crate::db::search::fts_query;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Note {
     pub id: Option<i64>,
@@ -143,17 +145,20 @@ impl NotesDatabase {
 
     pub fn search_notes(&self, conn: &Connection, query: &str) -> Result<Vec<Note>, String> {
         info!("Searching notes with query: {}", query);
-        let search_pattern = format!("%{}%", query);
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
         
         let mut stmt = conn.prepare(
-            "SELECT id, title, content, created_at, updated_at 
-             FROM notes 
-             WHERE title LIKE ?1 OR content LIKE ?1 
-             ORDER BY updated_at DESC"
+            "SELECT n.id, n.title, n.content, n.created_at, n.updated_at 
+             FROM notes_fts 
+             JOIN notes n ON n.id = notes_fts.id 
+             WHERE notes_fts MATCH ?1 
+             ORDER BY bm25(notes_fts), n.updated_at DESC"
         ).map_err(|e| e.to_string())?;
 
         let notes = stmt.query_map(
-            params![search_pattern],
+            params![fts_query],
             Self::row_to_note
         ).map_err(|e| e.to_string())?;
 
@@ -0,0 +1,182 @@
+// src/db/occupancy.rs
+
+use rusqlite::{params, Connection, Result};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use log::info;
+
+use crate::db::app_settings::AppSettingsDatabase;
+use crate::db::attendance::{open_visit_condition, row_to_attendance, Attendance};
+use crate::db::attendance_analytics::SeriesPoint;
+use crate::db::library_timezone::LibraryTimezone;
+
+const AUTO_CHECKOUT_TIME_KEY: &str = "occupancy.auto_checkout_time";
+const MAX_VISIT_HOURS_KEY: &str = "occupancy.max_visit_hours";
+const CAPACITY_KEY: &str = "occupancy.capacity";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OccupancySettings {
+    // Library time of day ("HH:MM") at which every open visit is closed
+    pub auto_checkout_time: Option<String>,
+    // Open visits longer than this are closed at time-in plus this many hours
+    pub max_visit_hours: Option<i64>,
+    // Fire-safety limit shown next to the live count
+    pub capacity: Option<u64>,
+}
+
+impl OccupancySettings {
+    fn auto_checkout_time(&self) -> Option<NaiveTime> {
+        self.auto_checkout_time.as_ref()
+            .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok())
+    }
+
+    // When a visit that started at `time_in` should be closed, if any rule applies
+    pub fn checkout_cutoff(&self, time_in: DateTime<Utc>, timezone: &LibraryTimezone) -> Option<DateTime<Utc>> {
+        let by_duration = self.max_visit_hours.map(|hours| time_in + Duration::hours(hours));
+
+        let by_clock = self.auto_checkout_time().and_then(|time| {
+            let local_in = timezone.local(time_in);
+            let mut date = local_in.date_naive();
+            if local_in.time() >= time {
+                date = date.succ_opt()?;
+            }
+            Some(timezone.start_of_day(date) + (time - NaiveTime::MIN))
+        });
+
+        match (by_duration, by_clock) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Occupancy {
+    pub current: u64,
+    pub capacity: Option<u64>,
+    pub over_capacity: bool,
+    pub by_classification: Vec<SeriesPoint>,
+    pub by_purpose: Vec<SeriesPoint>,
+    pub as_of: DateTime<Utc>,
+}
+
+impl Occupancy {
+    // Whether two snapshots show the same headcount, ignoring when they were taken
+    pub fn same_counts(&self, other: &Occupancy) -> bool {
+        self.current == other.current
+            && self.capacity == other.capacity
+            && self.by_classification == other.by_classification
+            && self.by_purpose == other.by_purpose
+    }
+}
+
+#[derive(Clone)]
+pub struct OccupancyDatabase;
+
+impl OccupancyDatabase {
+    pub fn get_settings(&self, conn: &Connection) -> Result<OccupancySettings> {
+        let settings = AppSettingsDatabase;
+        Ok(OccupancySettings {
+            auto_checkout_time: settings.get_setting(conn, AUTO_CHECKOUT_TIME_KEY)?,
+            max_visit_hours: settings.get_parsed_setting(conn, MAX_VISIT_HOURS_KEY)?,
+            capacity: settings.get_parsed_setting(conn, CAPACITY_KEY)?,
+        })
+    }
+
+    pub fn set_settings(&self, conn: &Connection, occupancy_settings: OccupancySettings) -> Result<OccupancySettings> {
+        if let Some(time) = &occupancy_settings.auto_checkout_time {
+            if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(rusqlite::Error::InvalidParameterName("Auto checkout time must be HH:MM".to_string()));
+            }
+        }
+
+        if occupancy_settings.max_visit_hours.is_some_and(|hours| hours <= 0) {
+            return Err(rusqlite::Error::InvalidParameterName("Maximum visit length must be positive".to_string()));
+        }
+
+        if occupancy_settings.capacity == Some(0) {
+            return Err(rusqlite::Error::InvalidParameterName("Capacity must be positive".to_string()));
+        }
+
+        let settings = AppSettingsDatabase;
+        settings.set_optional_setting(conn, AUTO_CHECKOUT_TIME_KEY, occupancy_settings.auto_checkout_time.clone())?;
+        settings.set_optional_setting(conn, MAX_VISIT_HOURS_KEY, occupancy_settings.max_visit_hours)?;
+        settings.set_optional_setting(conn, CAPACITY_KEY, occupancy_settings.capacity)?;
+
+        info!("Updated occupancy settings: {:?}", occupancy_settings);
+        Ok(occupancy_settings)
+    }
+
+    fn count_open_by(&self, conn: &Connection, group_expr: &str, open_visit: &str) -> Result<Vec<SeriesPoint>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} AS bucket, COUNT(*) AS visits
+             FROM attendance
+             WHERE {}
+             GROUP BY bucket
+             ORDER BY visits DESC, bucket",
+            group_expr, open_visit
+        ))?;
+
+        let points = stmt.query_map([], |row| {
+            Ok(SeriesPoint { label: row.get(0)?, value: row.get(1)? })
+        })?;
+        points.collect()
+    }
+
+    pub fn get_occupancy(&self, conn: &Connection) -> Result<Occupancy> {
+        let open_visit = open_visit_condition(&LibraryTimezone::load(conn)?);
+        let current: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM attendance WHERE {}", open_visit),
+            [],
+            |row| row.get(0),
+        )?;
+        let capacity = self.get_settings(conn)?.capacity;
+
+        Ok(Occupancy {
+            current,
+            capacity,
+            over_capacity: capacity.is_some_and(|capacity| current > capacity),
+            by_classification: self.count_open_by(conn, "classification", &open_visit)?,
+            by_purpose: self.count_open_by(conn, "COALESCE(purpose_label, 'Unspecified')", &open_visit)?,
+            as_of: Utc::now(),
+        })
+    }
+
+    // Closes open visits that passed their cutoff, stamping the cutoff as the time-out
+    pub fn expire_stale_visits(&self, conn: &Connection) -> Result<Vec<Attendance>> {
+        let settings = self.get_settings(conn)?;
+        if settings.auto_checkout_time.is_none() && settings.max_visit_hours.is_none() {
+            return Ok(Vec::new());
+        }
+        let timezone = LibraryTimezone::load(conn)?;
+
+        let open_visits = {
+            let mut stmt = conn.prepare("SELECT * FROM attendance WHERE time_out_date IS NULL AND is_deleted = 0")?;
+            let rows = stmt.query_map([], row_to_attendance)?;
+            rows.collect::<Result<Vec<_>>>()?
+        };
+
+        let now = Utc::now();
+        let mut expired = Vec::new();
+        for mut attendance in open_visits {
+            let Some(cutoff) = settings.checkout_cutoff(attendance.time_in_date, &timezone) else {
+                continue;
+            };
+            if cutoff > now {
+                continue;
+            }
+
+            conn.execute(
+                "UPDATE attendance SET time_out_date = ?1 WHERE id = ?2 AND time_out_date IS NULL",
+                params![cutoff.timestamp_millis(), attendance.id.to_string()],
+            )?;
+            attendance.time_out_date = Some(cutoff);
+            expired.push(attendance);
+        }
+
+        if !expired.is_empty() {
+            info!("Auto checked out {} stale visits", expired.len());
+        }
+        Ok(expired)
+    }
+}
@@ -0,0 +1,85 @@
+// src/db/roles.rs
+
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Role {
+    // Full access, including user management and destructive changes
+    Librarian,
+    // Front desk staff: records visits and keeps notes
+    Assistant,
+    // Read-only access to records and reports
+    Viewer,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Permission {
+    ViewRecords,
+    ExportReports,
+    RecordAttendance,
+    EditAttendance,
+    ManageNotes,
+    ManageAccounts,
+    ImportCsv,
+    ManageSemesters,
+    ManagePurposes,
+    ManageSettings,
+    ManageUsers,
+    ViewAuditLog,
+    ManageDevices,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 13] = [
+        Permission::ViewRecords,
+        Permission::ExportReports,
+        Permission::RecordAttendance,
+        Permission::EditAttendance,
+        Permission::ManageNotes,
+        Permission::ManageAccounts,
+        Permission::ImportCsv,
+        Permission::ManageSemesters,
+        Permission::ManagePurposes,
+        Permission::ManageSettings,
+        Permission::ManageUsers,
+        Permission::ViewAuditLog,
+        Permission::ManageDevices,
+    ];
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Librarian => "Librarian",
+            Role::Assistant => "Assistant",
+            Role::Viewer => "Viewer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Librarian" => Some(Role::Librarian),
+            "Assistant" => Some(Role::Assistant),
+            "Viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            Role::Librarian => true,
+            Role::Assistant => matches!(
+                permission,
+                Permission::ViewRecords
+                    | Permission::ExportReports
+                    | Permission::RecordAttendance
+                    | Permission::ManageNotes
+            ),
+            Role::Viewer => matches!(permission, Permission::ViewRecords | Permission::ExportReports),
+        }
+    }
+
+    pub fn permissions(&self) -> Vec<Permission> {
+        Permission::ALL.into_iter().filter(|permission| self.allows(*permission)).collect()
+    }
+}
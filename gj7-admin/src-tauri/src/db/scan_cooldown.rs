@@ -0,0 +1,122 @@
+// src/db/scan_cooldown.rs
+
+use log::info;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params, Row};
+use serde::{Serialize, Deserialize};
+
+// Classification whose cooldown applies to every one without its own. None is
+// set up front, so repeat scans are only held back once an admin opts in.
+pub const DEFAULT_COOLDOWN_CLASSIFICATION: &str = "*";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum CooldownMode {
+    // Repeat scans inside the window are refused with a duplicate scan error
+    Reject,
+    // Repeat scans inside the window return the existing record unchanged
+    Merge,
+}
+
+impl CooldownMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CooldownMode::Reject => "Reject",
+            CooldownMode::Merge => "Merge",
+        }
+    }
+
+    fn from_str(value: &str) -> Self {
+        match value {
+            "Merge" => CooldownMode::Merge,
+            _ => CooldownMode::Reject,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanCooldown {
+    pub classification: String,
+    pub cooldown_seconds: i64,
+    pub mode: CooldownMode,
+}
+
+#[derive(Clone)]
+pub struct ScanCooldownDatabase;
+
+impl ScanCooldownDatabase {
+    pub fn init(conn: &Connection) -> SqliteResult<Self> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scan_cooldowns (
+                classification TEXT PRIMARY KEY,
+                cooldown_seconds INTEGER NOT NULL,
+                mode TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(ScanCooldownDatabase)
+    }
+
+    fn row_to_scan_cooldown(row: &Row) -> SqliteResult<ScanCooldown> {
+        Ok(ScanCooldown {
+            classification: row.get(0)?,
+            cooldown_seconds: row.get(1)?,
+            mode: CooldownMode::from_str(&row.get::<_, String>(2)?),
+        })
+    }
+
+    pub fn get_all_scan_cooldowns(&self, conn: &Connection) -> SqliteResult<Vec<ScanCooldown>> {
+        let mut stmt = conn.prepare(
+            "SELECT classification, cooldown_seconds, mode FROM scan_cooldowns ORDER BY classification"
+        )?;
+
+        let cooldowns = stmt.query_map([], Self::row_to_scan_cooldown)?;
+        cooldowns.collect()
+    }
+
+    // Returns the cooldown for a classification, falling back to the default row
+    pub fn get_scan_cooldown(&self, conn: &Connection, classification: &str) -> SqliteResult<Option<ScanCooldown>> {
+        conn.query_row(
+            "SELECT classification, cooldown_seconds, mode FROM scan_cooldowns
+             WHERE classification = ?1 OR classification = ?2
+             ORDER BY classification = ?2
+             LIMIT 1",
+            params![classification, DEFAULT_COOLDOWN_CLASSIFICATION],
+            Self::row_to_scan_cooldown,
+        ).optional()
+    }
+
+    pub fn set_scan_cooldown(&self, conn: &Connection, cooldown: ScanCooldown) -> SqliteResult<ScanCooldown> {
+        if cooldown.classification.is_empty() {
+            return Err(rusqlite::Error::InvalidParameterName("Classification cannot be empty".to_string()));
+        }
+
+        if cooldown.cooldown_seconds < 0 {
+            return Err(rusqlite::Error::InvalidParameterName("Cooldown cannot be negative".to_string()));
+        }
+
+        conn.execute(
+            "INSERT INTO scan_cooldowns (classification, cooldown_seconds, mode)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(classification) DO UPDATE SET
+                cooldown_seconds = excluded.cooldown_seconds,
+                mode = excluded.mode",
+            params![cooldown.classification, cooldown.cooldown_seconds, cooldown.mode.as_str()],
+        )?;
+
+        info!(
+            "Set scan cooldown for {}: {}s ({})",
+            cooldown.classification, cooldown.cooldown_seconds, cooldown.mode.as_str()
+        );
+        Ok(cooldown)
+    }
+
+    pub fn delete_scan_cooldown(&self, conn: &Connection, classification: &str) -> SqliteResult<()> {
+        conn.execute(
+            "DELETE FROM scan_cooldowns WHERE classification = ?1",
+            params![classification],
+        )?;
+
+        info!("Deleted scan cooldown for {}", classification);
+        Ok(())
+    }
+}
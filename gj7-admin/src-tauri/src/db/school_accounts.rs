@@ -7,6 +7,8 @@ use serde::Deserializer;
 use log::{info, error};
 use rusqlite::Result as SqlResult;
 
+use crate::db::search::fts_query;
+
 
 // Enum for gender choices
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -467,21 +469,17 @@ impl SchoolAccountRepository for SqliteSchoolAccountRepository {
     }
 
     fn search_school_accounts(&self, conn: &Connection, query: &str) -> Result<Vec<SchoolAccount>> {
-        let sql = "SELECT * FROM school_accounts 
-                   WHERE school_id LIKE ? OR 
-                         first_name LIKE ? OR 
-                         middle_name LIKE ? OR 
-                         last_name LIKE ?";
-        
-        let search_pattern = format!("%{}%", query);
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = "SELECT s.* FROM school_accounts_fts
+                   JOIN school_accounts s ON s.id = school_accounts_fts.id
+                   WHERE school_accounts_fts MATCH ?1
+                   ORDER BY bm25(school_accounts_fts)";
         
         let mut stmt = conn.prepare(sql)?;
-        let account_iter = stmt.query_map(params![
-            &search_pattern, 
-            &search_pattern, 
-            &search_pattern, 
-            &search_pattern
-        ], |row| {
+        let account_iter = stmt.query_map(params![fts_query], |row| {
             Ok(SchoolAccount {
                 id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
                 school_id: row.get(1)?,
@@ -0,0 +1,259 @@
+// src/db/search.rs
+
+use rusqlite::{params, Connection, Result, Row};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use log::info;
+
+use crate::db::attendance::from_millis;
+
+// Default number of results returned by a unified search
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+
+// Markers wrapped around matched terms in snippets
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+// Each index is a standalone FTS5 table whose rowid follows the source row's
+// rowid, so triggers can keep it in sync without scanning it. The source id is
+// kept unindexed to join search hits back to their records.
+const CREATE_SEARCH_TABLES: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS school_accounts_fts USING fts5(
+        id UNINDEXED, school_id, first_name, middle_name, last_name, course, department,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS attendance_fts USING fts5(
+        id UNINDEXED, school_id, full_name, classification, purpose_label,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+        id UNINDEXED, title, content,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS school_accounts_fts_insert AFTER INSERT ON school_accounts BEGIN
+        INSERT INTO school_accounts_fts (rowid, id, school_id, first_name, middle_name, last_name, course, department)
+        VALUES (new.rowid, new.id, new.school_id, new.first_name, new.middle_name, new.last_name, new.course, new.department);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS school_accounts_fts_update
+    AFTER UPDATE OF school_id, first_name, middle_name, last_name, course, department ON school_accounts BEGIN
+        DELETE FROM school_accounts_fts WHERE rowid = old.rowid;
+        INSERT INTO school_accounts_fts (rowid, id, school_id, first_name, middle_name, last_name, course, department)
+        VALUES (new.rowid, new.id, new.school_id, new.first_name, new.middle_name, new.last_name, new.course, new.department);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS school_accounts_fts_delete AFTER DELETE ON school_accounts BEGIN
+        DELETE FROM school_accounts_fts WHERE rowid = old.rowid;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS attendance_fts_insert AFTER INSERT ON attendance BEGIN
+        INSERT INTO attendance_fts (rowid, id, school_id, full_name, classification, purpose_label)
+        VALUES (new.rowid, new.id, new.school_id, new.full_name, new.classification, new.purpose_label);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS attendance_fts_update
+    AFTER UPDATE OF school_id, full_name, classification, purpose_label ON attendance BEGIN
+        DELETE FROM attendance_fts WHERE rowid = old.rowid;
+        INSERT INTO attendance_fts (rowid, id, school_id, full_name, classification, purpose_label)
+        VALUES (new.rowid, new.id, new.school_id, new.full_name, new.classification, new.purpose_label);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS attendance_fts_delete AFTER DELETE ON attendance BEGIN
+        DELETE FROM attendance_fts WHERE rowid = old.rowid;
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS notes_fts_insert AFTER INSERT ON notes BEGIN
+        INSERT INTO notes_fts (rowid, id, title, content) VALUES (new.id, new.id, new.title, new.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS notes_fts_update AFTER UPDATE OF title, content ON notes BEGIN
+        DELETE FROM notes_fts WHERE rowid = old.id;
+        INSERT INTO notes_fts (rowid, id, title, content) VALUES (new.id, new.id, new.title, new.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS notes_fts_delete AFTER DELETE ON notes BEGIN
+        DELETE FROM notes_fts WHERE rowid = old.id;
+    END;
+";
+
+// (index, source table, statement that refills the index from the source)
+const SEARCH_INDEXES: [(&str, &str, &str); 3] = [
+    (
+        "school_accounts_fts",
+        "school_accounts",
+        "INSERT INTO school_accounts_fts (rowid, id, school_id, first_name, middle_name, last_name, course, department)
+         SELECT rowid, id, school_id, first_name, middle_name, last_name, course, department FROM school_accounts",
+    ),
+    (
+        "attendance_fts",
+        "attendance",
+        "INSERT INTO attendance_fts (rowid, id, school_id, full_name, classification, purpose_label)
+         SELECT rowid, id, school_id, full_name, classification, purpose_label FROM attendance",
+    ),
+    (
+        "notes_fts",
+        "notes",
+        "INSERT INTO notes_fts (rowid, id, title, content) SELECT id, id, title, content FROM notes",
+    ),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SearchResultKind {
+    Account,
+    Attendance,
+    Note,
+}
+
+impl SearchResultKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Account" => Some(SearchResultKind::Account),
+            "Attendance" => Some(SearchResultKind::Attendance),
+            "Note" => Some(SearchResultKind::Note),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    // School account or attendance UUID, or the note's numeric id
+    pub id: String,
+    pub title: String,
+    // Best matching excerpt with matches wrapped in HIGHLIGHT_START/HIGHLIGHT_END
+    pub snippet: String,
+    // Visit time for attendance, last edit for notes
+    pub timestamp: Option<DateTime<Utc>>,
+    // BM25 score; lower is a better match
+    pub rank: f64,
+}
+
+// Turns free text into an FTS5 query where every word must match, as a prefix,
+// in any column. Quotes are dropped so user input can never form FTS5 syntax.
+pub(crate) fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn row_to_search_result(row: &Row) -> Result<SearchResult> {
+    let kind: String = row.get(0)?;
+
+    Ok(SearchResult {
+        kind: SearchResultKind::parse(&kind).ok_or_else(|| rusqlite::Error::InvalidColumnType(
+            0,
+            kind.clone(),
+            rusqlite::types::Type::Text
+        ))?,
+        id: row.get(1)?,
+        title: row.get(2)?,
+        snippet: row.get(3)?,
+        timestamp: row.get::<_, Option<i64>>(4)?
+            .map(|value| from_millis(value, 4))
+            .transpose()?,
+        rank: row.get(5)?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SearchDatabase;
+
+impl SearchDatabase {
+    // Needs the school_accounts, attendance and notes tables to exist
+    pub fn init(conn: &Connection) -> Result<Self> {
+        conn.execute_batch(CREATE_SEARCH_TABLES)?;
+
+        for (index, source, _) in SEARCH_INDEXES {
+            let indexed: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", index), [], |row| row.get(0))?;
+            let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", source), [], |row| row.get(0))?;
+            if indexed != rows {
+                info!("Search index {} has {} of {} rows, rebuilding", index, indexed, rows);
+                SearchDatabase.rebuild_index(conn, index)?;
+            }
+        }
+
+        Ok(SearchDatabase)
+    }
+
+    // Refills one index from its source table
+    pub fn rebuild_index(&self, conn: &Connection, index: &str) -> Result<()> {
+        let (_, _, refill) = SEARCH_INDEXES
+            .iter()
+            .find(|(name, _, _)| *name == index)
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("Unknown search index: {}", index)))?;
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(&format!("DELETE FROM {}", index), [])?;
+        tx.execute(refill, [])?;
+        tx.commit()
+    }
+
+    // Ranked matches across accounts, attendance and notes, best first
+    pub fn search(&self, conn: &Connection, query: &str, limit: Option<u32>) -> Result<Vec<SearchResult>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = format!(
+            "SELECT kind, id, title, snippet, timestamp, rank FROM (
+                SELECT 'Account' AS kind,
+                       s.id AS id,
+                       TRIM(COALESCE(s.first_name, '') || ' ' || COALESCE(s.last_name, '')) || ' (' || s.school_id || ')' AS title,
+                       snippet(school_accounts_fts, -1, '{start}', '{end}', '…', 12) AS snippet,
+                       NULL AS timestamp,
+                       bm25(school_accounts_fts) AS rank
+                FROM school_accounts_fts
+                JOIN school_accounts s ON s.id = school_accounts_fts.id
+                WHERE school_accounts_fts MATCH ?1
+
+                UNION ALL
+
+                SELECT 'Attendance',
+                       a.id,
+                       a.full_name || ' (' || a.school_id || ')',
+                       snippet(attendance_fts, -1, '{start}', '{end}', '…', 12),
+                       a.time_in_date,
+                       bm25(attendance_fts)
+                FROM attendance_fts
+                JOIN attendance a ON a.id = attendance_fts.id
+                WHERE attendance_fts MATCH ?1 AND a.is_deleted = 0
+
+                UNION ALL
+
+                SELECT 'Note',
+                       CAST(n.id AS TEXT),
+                       n.title,
+                       snippet(notes_fts, -1, '{start}', '{end}', '…', 12),
+                       n.updated_at * 1000,
+                       bm25(notes_fts)
+                FROM notes_fts
+                JOIN notes n ON n.id = notes_fts.id
+                WHERE notes_fts MATCH ?1
+            )
+            ORDER BY rank, timestamp DESC
+            LIMIT ?2",
+            start = HIGHLIGHT_START,
+            end = HIGHLIGHT_END
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let results = stmt.query_map(
+            params![fts_query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT)],
+            row_to_search_result
+        )?;
+        results.collect()
+    }
+}
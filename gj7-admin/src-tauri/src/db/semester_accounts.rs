@@ -0,0 +1,148 @@
+// src/db/semester_accounts.rs
+
+use uuid::Uuid;
+use rusqlite::{params, Connection, Result, Row};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use log::info;
+
+use crate::db::school_accounts::CreateSchoolAccountRequest;
+
+// What an account looked like in a given semester, as imported from that semester's CSV
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SemesterAccount {
+    pub semester_id: Uuid,
+    pub school_id: String,
+    pub course: Option<String>,
+    pub department: Option<String>,
+    pub position: Option<String>,
+    pub major: Option<String>,
+    pub year_level: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+pub trait SemesterAccountRepository: Send + Sync {
+    fn record_semester_account(
+        &self,
+        conn: &Connection,
+        semester_id: Uuid,
+        account: &CreateSchoolAccountRequest
+    ) -> Result<()>;
+    fn get_semester_accounts(&self, conn: &Connection, semester_id: Uuid) -> Result<Vec<SemesterAccount>>;
+    fn get_account_history(&self, conn: &Connection, school_id: &str) -> Result<Vec<SemesterAccount>>;
+}
+
+pub struct SqliteSemesterAccountRepository;
+
+fn row_to_semester_account(row: &Row) -> Result<SemesterAccount> {
+    Ok(SemesterAccount {
+        semester_id: Uuid::parse_str(&row.get::<_, String>(0)?).unwrap(),
+        school_id: row.get(1)?,
+        course: row.get(2)?,
+        department: row.get(3)?,
+        position: row.get(4)?,
+        major: row.get(5)?,
+        year_level: row.get(6)?,
+        recorded_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(7)?).unwrap().with_timezone(&Utc),
+    })
+}
+
+impl SemesterAccountRepository for SqliteSemesterAccountRepository {
+    // Re-importing a semester replaces that semester's snapshot of the account
+    fn record_semester_account(
+        &self,
+        conn: &Connection,
+        semester_id: Uuid,
+        account: &CreateSchoolAccountRequest
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO semester_accounts (
+                semester_id, school_id, course, department, position, major, year_level, recorded_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(semester_id, school_id) DO UPDATE SET
+                course = excluded.course,
+                department = excluded.department,
+                position = excluded.position,
+                major = excluded.major,
+                year_level = excluded.year_level,
+                recorded_at = excluded.recorded_at",
+            params![
+                semester_id.to_string(),
+                account.school_id,
+                account.course,
+                account.department,
+                account.position,
+                account.major,
+                account.year_level,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_semester_accounts(&self, conn: &Connection, semester_id: Uuid) -> Result<Vec<SemesterAccount>> {
+        let mut stmt = conn.prepare(
+            "SELECT semester_id, school_id, course, department, position, major, year_level, recorded_at
+             FROM semester_accounts
+             WHERE semester_id = ?1
+             ORDER BY school_id"
+        )?;
+
+        let accounts = stmt.query_map(params![semester_id.to_string()], row_to_semester_account)?;
+        accounts.collect()
+    }
+
+    fn get_account_history(&self, conn: &Connection, school_id: &str) -> Result<Vec<SemesterAccount>> {
+        let mut stmt = conn.prepare(
+            "SELECT sem.semester_id, sem.school_id, sem.course, sem.department,
+                    sem.position, sem.major, sem.year_level, sem.recorded_at
+             FROM semester_accounts sem
+             LEFT JOIN semesters s ON s.id = sem.semester_id
+             WHERE sem.school_id = ?1
+             ORDER BY s.created_at DESC"
+        )?;
+
+        let accounts = stmt.query_map(params![school_id], row_to_semester_account)?;
+        accounts.collect()
+    }
+}
+
+pub fn create_semester_accounts_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS semester_accounts (
+            semester_id TEXT NOT NULL,
+            school_id TEXT NOT NULL,
+            course TEXT,
+            department TEXT,
+            position TEXT,
+            major TEXT,
+            year_level TEXT,
+            recorded_at TEXT NOT NULL,
+            PRIMARY KEY (semester_id, school_id),
+            CONSTRAINT fk_semester
+                FOREIGN KEY (semester_id)
+                REFERENCES semesters(id)
+                ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Seed snapshots from the last import of each account so older databases
+    // keep at least the most recent semester's details
+    let seeded = conn.execute(
+        "INSERT OR IGNORE INTO semester_accounts (
+            semester_id, school_id, course, department, position, major, year_level, recorded_at
+        )
+        SELECT last_updated_semester_id, school_id, course, department, position, major, year_level, ?1
+        FROM school_accounts
+        WHERE last_updated_semester_id IS NOT NULL
+          AND last_updated_semester_id IN (SELECT id FROM semesters)",
+        params![Utc::now().to_rfc3339()],
+    )?;
+    if seeded > 0 {
+        info!("Seeded {} semester account snapshots", seeded);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+// src/discovery.rs
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use tokio::net::UdpSocket;
+use log::{info, warn};
+
+use crate::db::network_settings::{load_server_id, NetworkServerSettings};
+use crate::websocket::DatabaseAccessor;
+
+// Kiosks listen on this port for beacons; it must match the client
+pub const DISCOVERY_PORT: u16 = 38080;
+// Tells beacons apart from other broadcasts on the same port
+pub const DISCOVERY_SERVICE: &str = "gj7-attendance";
+// Kiosks wait a few intervals before giving up, so keep this short
+const BEACON_INTERVAL: Duration = Duration::from_secs(2);
+
+// Announces where kiosks can reach the network server. The kiosk takes the
+// server's address from the sender of the packet.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscoveryBeacon {
+    pub service: String,
+    pub server_id: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+// Sends from the configured bind address so the beacon leaves on the same interface
+// kiosks connect to. None when the server only listens on loopback.
+async fn beacon_socket(bind_address: IpAddr) -> std::io::Result<Option<UdpSocket>> {
+    let source = match bind_address {
+        IpAddr::V4(address) if address.is_loopback() => return Ok(None),
+        IpAddr::V4(address) => address,
+        // Broadcasts are IPv4 only
+        IpAddr::V6(address) if address.is_loopback() => return Ok(None),
+        IpAddr::V6(_) => Ipv4Addr::UNSPECIFIED,
+    };
+
+    let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(source), 0)).await?;
+    socket.set_broadcast(true)?;
+    Ok(Some(socket))
+}
+
+// Broadcasts a beacon on the local network for as long as the app runs. Reads the
+// settings each time so address, port and TLS changes are announced straight away.
+pub async fn run_discovery_beacon(db_accessor: DatabaseAccessor) {
+    let mut interval = tokio::time::interval(BEACON_INTERVAL);
+    let mut socket: Option<(IpAddr, Option<UdpSocket>)> = None;
+    let target = SocketAddr::new(IpAddr::V4(Ipv4Addr::BROADCAST), DISCOVERY_PORT);
+
+    loop {
+        interval.tick().await;
+
+        let loaded = {
+            let db_accessor = db_accessor.clone();
+            tokio::task::spawn_blocking(move || {
+                let conn = db_accessor.get_connection()?;
+                Ok::<_, rusqlite::Error>((NetworkServerSettings::load(&conn)?, load_server_id(&conn)?))
+            }).await
+        };
+        let (settings, server_id) = match loaded {
+            Ok(Ok(loaded)) => loaded,
+            _ => continue,
+        };
+
+        let bind_address = settings.socket_addr().ip();
+        if socket.as_ref().map(|(address, _)| *address) != Some(bind_address) {
+            let opened = match beacon_socket(bind_address).await {
+                Ok(opened) => opened,
+                Err(e) => {
+                    warn!("Failed to open the discovery beacon socket: {}", e);
+                    continue;
+                }
+            };
+            match opened {
+                Some(_) => info!("Discovery beacon announcing on UDP port {}", DISCOVERY_PORT),
+                None => info!("Discovery beacon paused while the server only listens on {}", bind_address),
+            }
+            socket = Some((bind_address, opened));
+        }
+        let Some((_, Some(udp))) = socket.as_ref() else {
+            continue;
+        };
+
+        let beacon = DiscoveryBeacon {
+            service: DISCOVERY_SERVICE.to_string(),
+            server_id,
+            port: settings.port,
+            tls: settings.tls_enabled,
+        };
+        let payload = match serde_json::to_vec(&beacon) {
+            Ok(payload) => payload,
+            Err(_) => continue,
+        };
+        if let Err(e) = udp.send_to(&payload, target).await {
+            warn!("Failed to send discovery beacon: {}", e);
+        }
+    }
+}
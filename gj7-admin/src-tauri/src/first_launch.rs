@@ -5,6 +5,7 @@ use log::info;
 use rusqlite::Connection;
 use tauri::AppHandle;
 use crate::db::auth::{AuthDatabase, Credentials as AuthCredentials};
+use crate::db::roles::Role;
 use crate::db::purpose::{PurposeRepository, SqlitePurposeRepository, CreatePurposeRequest};
 use crate::db::settings_styles::{SettingsStylesDatabase, CreateSettingsStyleRequest};
 use crate::config;
@@ -121,7 +122,7 @@ pub fn handle_first_launch(_app_handle: &AppHandle) -> Result<(), String> {
             password: config.password.clone(),
         };
         
-        auth_db.create_user(&conn, &auth_credentials)
+        auth_db.create_user(&conn, &auth_credentials, Role::Librarian)
             .map_err(|e| e.to_string())?;
     }
 
@@ -0,0 +1,109 @@
+// src/kiosk_auth.rs
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use serde::Serialize;
+
+use crate::db::attendance::CreateAttendanceRequest;
+use crate::db::audit_log::{AuditAction, AuditLogDatabase};
+use crate::db::kiosk_devices::{KioskDevice, KioskDeviceDatabase, PairRequest, PairedDevice};
+use crate::db::network_settings::load_server_id;
+use crate::websocket::AppState;
+
+// Browsers cannot set headers on a websocket handshake, so /ws may pass the
+// token as `?token=` instead of `Authorization: Bearer`
+fn device_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let query = || uri
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="));
+
+    bearer.or_else(query)
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+// Scans are attributed to the kiosk whose token sent them, whatever the body says
+pub fn attribute_to_device(mut attendance_req: CreateAttendanceRequest, device: &KioskDevice) -> CreateAttendanceRequest {
+    attendance_req.device_id = Some(device.id);
+    attendance_req.location = Some(device.location_label().to_string());
+    attendance_req
+}
+
+// Tells the kiosk which server it paired with, so discovery keeps it with this one
+#[derive(Debug, Serialize)]
+pub struct PairResponse {
+    #[serde(flatten)]
+    pub paired: PairedDevice,
+    pub server_id: String,
+}
+
+// Exchanges a one-time pairing code from the admin app for a device token
+pub async fn pair_handler(
+    State(state): State<AppState>,
+    Json(pair_req): Json<PairRequest>
+) -> Result<Json<PairResponse>, (StatusCode, String)> {
+    let db_accessor = state.db_accessor.clone();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let paired = KioskDeviceDatabase.pair(&conn, &pair_req)
+            .map_err(|e| match e {
+                rusqlite::Error::InvalidParameterName(message) => (StatusCode::UNAUTHORIZED, message),
+                e => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            })?;
+
+        AuditLogDatabase.record(
+            &conn,
+            &paired.device.paired_by,
+            AuditAction::KioskPaired,
+            Some(&paired.device.name),
+            None
+        ).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let server_id = load_server_id(&conn)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        Ok(PairResponse { paired, server_id })
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    result.map(Json)
+}
+
+// Refuses requests without the token of a paired, unrevoked kiosk. The device
+// is added to the request extensions for the handlers.
+pub async fn require_device_token(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next
+) -> Result<Response, (StatusCode, String)> {
+    let token = device_token(request.headers(), request.uri())
+        .ok_or((StatusCode::UNAUTHORIZED, "This kiosk is not paired".to_string()))?;
+    let db_accessor = state.db_accessor.clone();
+
+    let device = tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()?;
+        KioskDeviceDatabase.authenticate(&conn, &token)
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or((StatusCode::UNAUTHORIZED, "Unknown or revoked device token".to_string()))?;
+
+    request.extensions_mut().insert(device);
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,65 @@
+// src/kiosk_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::audit_log::AuditAction;
+use crate::db::kiosk_devices::{KioskDevice, PairingCode};
+use rusqlite::Result;
+
+// A one-time code a kiosk exchanges for its device token; replaces any earlier code
+#[tauri::command]
+pub async fn create_pairing_code(
+    state: State<'_, DbState>,
+    username: String,
+    password: String
+) -> Result<PairingCode, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let kiosk_devices = db.kiosk_devices.clone();
+    let audit_log = db.audit_log.clone();
+
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let pairing_code = kiosk_devices.create_pairing_code(conn, &username)?;
+            audit_log.record(conn, &username, AuditAction::KioskPairingCodeCreated, None, None)?;
+            Ok(pairing_code)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn list_kiosk_devices(
+    state: State<'_, DbState>
+) -> Result<Vec<KioskDevice>, String> {
+    let db = state.0.clone();
+    let kiosk_devices = db.kiosk_devices.clone();
+
+    db.with_connection(move |conn| {
+        kiosk_devices.list_devices(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+// The device's token stops working immediately, including open websockets
+#[tauri::command]
+pub async fn revoke_kiosk_device(
+    state: State<'_, DbState>,
+    device_id: i64,
+    username: String,
+    password: String
+) -> Result<KioskDevice, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let kiosk_devices = db.kiosk_devices.clone();
+    let audit_log = db.audit_log.clone();
+
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let device = kiosk_devices.revoke_device(conn, device_id)?;
+            audit_log.record(conn, &username, AuditAction::KioskRevoked, Some(&device.name), None)?;
+            Ok(device)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
@@ -5,14 +5,28 @@ mod network;
 mod first_launch;
 mod config;
 mod storage;
+mod tls;
+mod discovery;
 mod notes_commands;
 mod school_account_commands;
 mod csv_commands;
 mod semester_commands;
 mod purpose_commands;
 mod attendance_commands;
+mod attendance_analytics_commands;
+mod attendance_export_commands;
+mod attendance_report_commands;
+mod report_pdf;
+mod occupancy_commands;
+mod app_settings_commands;
+mod search_commands;
+mod audit_log_commands;
+mod kiosk_commands;
+mod auth_commands;
+mod session;
 mod settings_styles_commands;
 mod network_server;
+mod kiosk_auth;
 mod websocket;
 mod logger;
 mod parallel_csv_processor;
@@ -23,13 +37,14 @@ use tauri::Manager;
 use tauri::Emitter;
 use tokio;
 use db::{Database, init_db, DatabaseInfo};
-use db::auth::Credentials;
 use rusqlite::Result;
 use network::check_network;
 use first_launch::handle_first_launch;
-use network_server::start_network_server;
+use network_server::{start_network_server, NetworkServerControl};
 use log::error;
 use storage::AppStorage;
+use session::{start_idle_lock, with_permission_checks, SessionState};
+use db::idle_lock::IdleLockSettings;
 use std::time::Duration;
 
 pub use crate::config::{Config, DatabaseConfig}; 
@@ -40,6 +55,8 @@ pub struct DbState(pub Database);
 unsafe impl Send for DbState {}
 unsafe impl Sync for DbState {}
 
+// Confirms credentials before an action that asks for them again; needs a signed-in
+// session, and wrong passwords count towards the sign-in lockout
 #[tauri::command]
 async fn authenticate(
     state: tauri::State<'_, DbState>,
@@ -51,16 +68,6 @@ async fn authenticate(
     }).await.map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-async fn get_credentials(
-    state: tauri::State<'_, DbState>,
-) -> Result<Credentials, String> {
-    let auth = state.0.auth.clone();
-    state.0.with_connection(move |conn| {
-        auth.get_credentials(conn)
-    }).await.map_err(|e| e.to_string())
-}
-
 #[tauri::command]
 async fn get_database_info(
     state: tauri::State<'_, DbState>
@@ -79,6 +86,12 @@ pub fn run() {
             // Initialize Tauri plugins
             .plugin(tauri_plugin_shell::init())
             .plugin(tauri_plugin_dialog::init())
+
+            // Signed-in user, checked before every command
+            .manage(SessionState::default())
+
+            // Restarts the network server when its settings change
+            .manage(NetworkServerControl::default())
             
             // Setup function for application initialization
             .setup(|app| {
@@ -135,8 +148,16 @@ pub fn run() {
                     // Manage database state
                     app_handle.manage(DbState(db.clone()));
 
+                    // Lock the app after the configured idle time
+                    match db.with_connection(IdleLockSettings::load).await {
+                        Ok(settings) => app_handle.state::<SessionState>().set_idle_timeout(settings.timeout()),
+                        Err(e) => error!("Failed to load idle lock settings: {}", e),
+                    }
+                    start_idle_lock(app_handle.clone());
+
                     // Start network server
-                    if let Err(e) = start_network_server(db).await {
+                    let control = app_handle.state::<NetworkServerControl>().inner().clone();
+                    if let Err(e) = start_network_server(db, app_handle.clone(), control).await {
                         error!("Failed to start network server: {}", e);
                         app_handle.emit("network-server-error", e.to_string()).unwrap();
                     }
@@ -145,12 +166,32 @@ pub fn run() {
                 Ok(())
             })
             
-            // Define invoke handlers for various commands
-            .invoke_handler(tauri::generate_handler![
+            // Define invoke handlers for various commands, each gated by the signed-in user's role
+            .invoke_handler(with_permission_checks(tauri::generate_handler![
                 // Authentication
                 authenticate,
-                get_credentials,
                 get_database_info,
+                auth_commands::login,
+                auth_commands::logout,
+                auth_commands::get_current_user,
+                auth_commands::get_lock_state,
+                auth_commands::record_activity,
+                auth_commands::lock_session,
+                auth_commands::set_pin,
+                auth_commands::clear_pin,
+                auth_commands::get_pin_status,
+                auth_commands::verify_pin,
+                auth_commands::change_password,
+                auth_commands::generate_recovery_codes,
+                auth_commands::reset_password_with_recovery_code,
+
+                // User management commands
+                auth_commands::list_users,
+                auth_commands::create_user,
+                auth_commands::set_user_role,
+                auth_commands::set_user_active,
+                auth_commands::delete_user,
+                auth_commands::reset_user_password,
 
                 // Notes commands
                 notes_commands::create_note,
@@ -182,6 +223,8 @@ pub fn run() {
                 semester_commands::update_semester,
                 semester_commands::delete_semester,
                 semester_commands::set_active_semester,
+                semester_commands::get_semester_accounts,
+                semester_commands::get_account_semester_history,
 
                 // Purpose commands
                 purpose_commands::create_purpose,
@@ -194,14 +237,58 @@ pub fn run() {
 
                 // Attendance commands
                 attendance_commands::create_attendance,
+                attendance_commands::create_manual_attendance,
+                attendance_commands::checkout_attendance,
                 attendance_commands::get_all_attendances,
                 attendance_commands::get_attendance,
                 attendance_commands::update_attendance,
                 attendance_commands::delete_attendance,
+                attendance_commands::restore_attendance,
+                attendance_commands::get_attendance_history,
+                attendance_commands::preview_bulk_attendance_action,
+                attendance_commands::apply_bulk_attendance_action,
                 attendance_commands::get_attendances_by_semester,
                 attendance_commands::get_attendances_by_school_account,
                 attendance_commands::get_filtered_attendances,
                 attendance_commands::get_all_courses,
+                attendance_commands::get_attendance_locations,
+                attendance_commands::get_scan_cooldowns,
+                attendance_commands::set_scan_cooldown,
+                attendance_commands::delete_scan_cooldown,
+
+                // Attendance analytics commands
+                attendance_analytics_commands::get_attendance_analytics,
+                attendance_analytics_commands::get_attendance_series,
+
+                // Attendance export commands
+                attendance_export_commands::export_attendances,
+
+                // Attendance report commands
+                attendance_report_commands::generate_attendance_report,
+
+                // Occupancy commands
+                occupancy_commands::get_occupancy,
+                occupancy_commands::get_occupancy_settings,
+                occupancy_commands::set_occupancy_settings,
+                occupancy_commands::expire_stale_visits,
+                app_settings_commands::get_library_timezone,
+                app_settings_commands::set_library_timezone,
+                app_settings_commands::get_idle_lock_settings,
+                app_settings_commands::set_idle_lock_settings,
+                app_settings_commands::get_network_server_settings,
+                app_settings_commands::set_network_server_settings,
+                app_settings_commands::get_server_certificate_fingerprint,
+                search_commands::search_all,
+
+                // Audit log commands
+                audit_log_commands::get_audit_log,
+                audit_log_commands::export_audit_log,
+                audit_log_commands::verify_hash_chain,
+
+                // Kiosk pairing commands
+                kiosk_commands::create_pairing_code,
+                kiosk_commands::list_kiosk_devices,
+                kiosk_commands::revoke_kiosk_device,
 
                 // Settings Styles commands
                 settings_styles_commands::create_settings_style,
@@ -214,7 +301,7 @@ pub fn run() {
 
                 // Network check
                 check_network
-            ])
+            ]))
             
             // Run the Tauri application
             .run(tauri::generate_context!())
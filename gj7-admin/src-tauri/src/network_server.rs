@@ -3,16 +3,30 @@
 use axum::{
     routing::{get, post},
     Router,
+    middleware,
     extract::{State, Path},
+    Extension,
     Json,
     http::StatusCode,
 };
-use rusqlite::{Connection, params};
-use tokio::net::TcpListener;
+use rusqlite::params;
+use axum_server::Handle;
+use axum_server::tls_rustls::RustlsConfig;
+use futures::future::{BoxFuture, FutureExt};
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use tower_http::cors::CorsLayer;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use log::{error, info, warn};
 use crate::Database;
+use crate::discovery::run_discovery_beacon;
+use crate::kiosk_auth::{attribute_to_device, pair_handler, require_device_token};
+use crate::storage::AppStorage;
+use crate::tls::{certificate_names, ServerCertificate};
 
 // Use the DatabaseAccessor from websocket module
 use crate::websocket::{
@@ -37,37 +51,128 @@ pub struct PurposeLookup {
     pub icon_name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckoutRequest {
+    pub school_id: String,
+}
+
+use crate::db::occupancy::{Occupancy, OccupancyDatabase};
+use crate::db::kiosk_devices::{KioskDevice, KioskDeviceDatabase};
+use crate::db::network_settings::NetworkServerSettings;
 use crate::db::attendance::{
     Attendance, 
     CreateAttendanceRequest, 
     SqliteAttendanceRepository, 
-    AttendanceRepository
+    AttendanceRepository,
+    ScanError,
+    ScanOutcome
 };
 
+// How often stale visits are expired and the headcount re-checked
+const OCCUPANCY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+// How often kiosks are checked for missed heartbeats
+const KIOSK_PRESENCE_INTERVAL: Duration = Duration::from_secs(15);
+// How long open requests get to finish when the server restarts on new settings
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(5);
+// How often the LAN addresses are checked against the HTTPS certificate's names
+const ADDRESS_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Lets the settings commands move the running server to a new address or scheme
+#[derive(Clone, Default)]
+pub struct NetworkServerControl {
+    restart: Arc<Notify>,
+}
+
+impl NetworkServerControl {
+    pub fn restart(&self) {
+        self.restart.notify_one();
+    }
+}
+
 async fn create_attendance_handler(
     State(state): State<AppState>,
+    Extension(device): Extension<KioskDevice>,
     Json(attendance_req): Json<CreateAttendanceRequest>
 ) -> Result<Json<Attendance>, (StatusCode, String)> {
     let db_accessor = state.db_accessor.clone();
+    let attendance_req = attribute_to_device(attendance_req, &device);
     
     // Wrap the entire handler logic in a blocking task
     let result = tokio::task::spawn_blocking(move || {
         // Open database connection
-        let conn = match Connection::open(&db_accessor.db_path) {
+        let conn = match db_accessor.get_connection() {
+            Ok(conn) => conn,
+            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        };
+
+        // Use the repository to record the scan (check-in or check-out)
+        let repo = SqliteAttendanceRepository;
+        repo.scan_attendance(&conn, attendance_req)
+            .map(ScanOutcome::into_attendance)
+            .map_err(|e| match e {
+                ScanError::DuplicateScan { .. } => (StatusCode::TOO_MANY_REQUESTS, e.to_string()),
+                ScanError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            })
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let attendance = result?;
+    state.ws_state.refresh_occupancy(&state.db_accessor).await;
+
+    // Wrap the attendance in Json
+    Ok(Json(attendance))
+}
+
+async fn checkout_attendance_handler(
+    State(state): State<AppState>,
+    Extension(device): Extension<KioskDevice>,
+    Json(checkout_req): Json<CheckoutRequest>
+) -> Result<Json<Attendance>, (StatusCode, String)> {
+    let db_accessor = state.db_accessor.clone();
+    let location = device.location_label().to_string();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = match db_accessor.get_connection() {
             Ok(conn) => conn,
             Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
         };
 
-        // Use the repository to create attendance
         let repo = SqliteAttendanceRepository;
-        repo.create_attendance(&conn, attendance_req)
+        repo.checkout_attendance(&conn, &checkout_req.school_id, Some(device.id), Some(&location))
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => (
+                    StatusCode::NOT_FOUND,
+                    format!("No open visit found for {}", checkout_req.school_id)
+                ),
+                e => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            })
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let attendance = result?;
+    state.ws_state.refresh_occupancy(&state.db_accessor).await;
+
+    Ok(Json(attendance))
+}
+
+async fn occupancy_handler(
+    State(state): State<AppState>
+) -> Result<Json<Occupancy>, (StatusCode, String)> {
+    let db_accessor = state.db_accessor.clone();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        OccupancyDatabase.get_occupancy(&conn)
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
     })
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    // Unwrap the result and wrap it in Json
-    Ok(Json(result?))
+    result.map(Json)
 }
 
 async fn school_id_lookup_handler(
@@ -78,7 +183,7 @@ async fn school_id_lookup_handler(
     // Wrap the entire handler logic in a blocking task
     let result = tokio::task::spawn_blocking(move || {
         // Open database connection
-        let conn = match Connection::open(&db_accessor.db_path) {
+        let conn = match db_accessor.get_connection() {
             Ok(conn) => conn,
             Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
         };
@@ -158,8 +263,131 @@ async fn school_id_lookup_handler(
     result.map(Json)
 }
 
+// Tells the admin app when a kiosk goes online or offline. Runs on a timer to
+// catch missed heartbeats, and straight away when a kiosk connects or disconnects.
+async fn watch_kiosk_presence(db_accessor: DatabaseAccessor, presence_changed: Arc<Notify>, app_handle: AppHandle) {
+    let mut known_online: Option<HashMap<i64, bool>> = None;
+    let mut interval = tokio::time::interval(KIOSK_PRESENCE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = presence_changed.notified() => {},
+        }
+
+        let db_accessor = db_accessor.clone();
+        let devices = tokio::task::spawn_blocking(move || {
+            let conn = db_accessor.get_connection()?;
+            KioskDeviceDatabase.list_devices(&conn)
+        }).await;
+        let devices = match devices {
+            Ok(Ok(devices)) => devices,
+            _ => continue,
+        };
+
+        // The first check only records the starting state
+        let previous = known_online.replace(
+            devices.iter().map(|device| (device.id, device.is_online)).collect()
+        );
+        let Some(previous) = previous else {
+            continue;
+        };
+
+        for device in devices {
+            if previous.get(&device.id).copied().unwrap_or(false) == device.is_online {
+                continue;
+            }
+            if let Err(e) = app_handle.emit("kiosk-status-changed", &device) {
+                warn!("Failed to emit kiosk-status-changed: {}", e);
+            }
+        }
+    }
+}
+
+// Loads the certificate for a server bound to this address, creating or reissuing it
+// when TLS is turned on, the addresses change or its fingerprint is asked for
+pub fn load_server_certificate(bind_address: IpAddr) -> Result<ServerCertificate, String> {
+    let storage = AppStorage::new()
+        .ok_or_else(|| "Failed to resolve the application storage directory".to_string())?;
+
+    ServerCertificate::load_or_create(&storage.get_tls_dir(), &certificate_names(bind_address))
+        .map_err(|e| format!("Failed to load the server certificate: {}", e))
+}
+
+// Resolves once this PC's addresses no longer match the certificate's names, e.g.
+// after DHCP hands out a new lease, so the server restarts with a reissued one
+async fn wait_for_address_change(bind_address: IpAddr, names: Vec<String>) {
+    let mut interval = tokio::time::interval(ADDRESS_CHECK_INTERVAL);
+    // The first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        match tokio::task::spawn_blocking(move || certificate_names(bind_address)).await {
+            Ok(current) if current != names => return,
+            _ => continue,
+        }
+    }
+}
+
+// Serves on the stored settings until they change, then returns Ok so the caller starts again
+async fn serve(
+    db_accessor: &DatabaseAccessor,
+    app: Router,
+    control: &NetworkServerControl
+) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = {
+        let db_accessor = db_accessor.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = db_accessor.get_connection()?;
+            NetworkServerSettings::load(&conn)
+        }).await??
+    };
+
+    let addr = settings.socket_addr();
+    let handle = Handle::new();
+    let service = app.into_make_service();
+
+    let mut address_changed: BoxFuture<'static, ()> = futures::future::pending().boxed();
+    let mut server: BoxFuture<'static, std::io::Result<()>> = if settings.tls_enabled {
+        let bind_address = addr.ip();
+        let names = tokio::task::spawn_blocking(move || certificate_names(bind_address)).await?;
+        let certificate = tokio::task::spawn_blocking(move || load_server_certificate(bind_address)).await??;
+        address_changed = wait_for_address_change(bind_address, names).boxed();
+        let config = RustlsConfig::from_pem(certificate.cert_pem.into_bytes(), certificate.key_pem.into_bytes()).await?;
+
+        info!("Network server started on https://{}", addr);
+        axum_server::bind_rustls(addr, config).handle(handle.clone()).serve(service).boxed()
+    } else {
+        info!("Network server started on http://{}", addr);
+        axum_server::bind(addr).handle(handle.clone()).serve(service).boxed()
+    };
+
+    tokio::select! {
+        result = &mut server => result.map_err(|e| -> Box<dyn std::error::Error> {
+            format!("Network server on {} stopped: {}", addr, e).into()
+        }),
+        _ = control.restart.notified() => {
+            // Kiosks reconnect on their own once the new server is up
+            handle.graceful_shutdown(Some(RESTART_GRACE_PERIOD));
+            let _ = server.await;
+            Ok(())
+        }
+        _ = &mut address_changed => {
+            info!("Network addresses changed; reissuing the server certificate");
+            handle.graceful_shutdown(Some(RESTART_GRACE_PERIOD));
+            let _ = server.await;
+            Ok(())
+        }
+    }
+}
+
 // Network server setup
-pub async fn start_network_server(db: Database) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start_network_server(
+    db: Database,
+    app_handle: AppHandle,
+    control: NetworkServerControl
+) -> Result<(), Box<dyn std::error::Error>> {
     // Configure CORS
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
@@ -171,29 +399,58 @@ pub async fn start_network_server(db: Database) -> Result<(), Box<dyn std::error
 
     let ws_state = WebSocketState::new(&db_accessor);
     let app_state = AppState {
-        ws_state,
+        ws_state: ws_state.clone(),
         db_accessor: db_accessor.clone(),
     };
 
-    let app = Router::new()
+    // Expire stale visits and catch headcount changes made outside the server,
+    // such as checkouts from the admin app
+    {
+        let db_accessor = db_accessor.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(OCCUPANCY_REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                ws_state.expire_stale_visits(&db_accessor).await;
+            }
+        });
+    }
+
+    tokio::spawn(watch_kiosk_presence(
+        db_accessor.clone(),
+        app_state.ws_state.presence_changed.clone(),
+        app_handle.clone()
+    ));
+
+    // Lets kiosks find the server again when the admin PC's address changes
+    tokio::spawn(run_discovery_beacon(db_accessor.clone()));
+
+    // Everything but pairing needs a kiosk's device token
+    let kiosk_routes = Router::new()
         .route("/school_id/:school_id", get(school_id_lookup_handler))
         .route("/attendance", post(create_attendance_handler))
+        .route("/attendance/checkout", post(checkout_attendance_handler))
+        .route("/occupancy", get(occupancy_handler))
         .route("/ws", get(websocket_handler))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), require_device_token));
+
+    let app = Router::new()
+        .route("/pair", post(pair_handler))
+        .merge(kiosk_routes)
         .layer(cors)
         .with_state(app_state);
 
-    // Bind to all network interfaces
-    let listener = TcpListener::bind("0.0.0.0:8080").await
-        .map_err(|e| -> Box<dyn std::error::Error> {
-            format!("Failed to bind TCP listener: {}", e).into()
-        })?;
-
-    println!("Network server started on 0.0.0.0:8080");
-
-    // Serve the application
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| -> Box<dyn std::error::Error> {
-            format!("Server error: {}", e).into()
-        })
+    loop {
+        match serve(&db_accessor, app.clone(), &control).await {
+            Ok(()) => info!("Restarting network server with new settings"),
+            Err(e) => {
+                error!("{}", e);
+                if let Err(e) = app_handle.emit("network-server-error", e.to_string()) {
+                    warn!("Failed to emit network-server-error: {}", e);
+                }
+                // A port already in use or a bad address needs new settings before retrying
+                control.restart.notified().await;
+            }
+        }
+    }
 }
\ No newline at end of file
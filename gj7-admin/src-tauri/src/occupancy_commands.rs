@@ -0,0 +1,66 @@
+// src/occupancy_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::attendance::Attendance;
+use crate::db::audit_log::{payload_summary, AuditAction};
+use crate::db::occupancy::{Occupancy, OccupancySettings};
+use rusqlite::Result;
+
+#[tauri::command]
+pub async fn get_occupancy(
+    state: State<'_, DbState>
+) -> Result<Occupancy, String> {
+    let db = state.0.clone();
+    let occupancy = db.occupancy.clone();
+    
+    db.with_connection(move |conn| {
+        occupancy.get_occupancy(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_occupancy_settings(
+    state: State<'_, DbState>
+) -> Result<OccupancySettings, String> {
+    let db = state.0.clone();
+    let occupancy = db.occupancy.clone();
+    
+    db.with_connection(move |conn| {
+        occupancy.get_settings(conn)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_occupancy_settings(
+    state: State<'_, DbState>,
+    settings: OccupancySettings,
+    username: String,
+    password: String
+) -> Result<OccupancySettings, String> {
+    let db = state.0.clone();
+    let auth = db.auth.clone();
+    let occupancy = db.occupancy.clone();
+    let audit_log = db.audit_log.clone();
+    
+    db.with_connection(move |conn| {
+        if auth.authenticate(conn, &username, &password)? {
+            let settings = occupancy.set_settings(conn, settings)?;
+            audit_log.record(conn, &username, AuditAction::SettingsChanged, Some("Occupancy"), payload_summary(&settings).as_deref())?;
+            Ok(settings)
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn expire_stale_visits(
+    state: State<'_, DbState>
+) -> Result<Vec<Attendance>, String> {
+    let db = state.0.clone();
+    let occupancy = db.occupancy.clone();
+    
+    db.with_connection(move |conn| {
+        occupancy.expire_stale_visits(conn)
+    }).await.map_err(|e| e.to_string())
+}
@@ -4,6 +4,7 @@ use tauri::State;
 use uuid::Uuid;
 use crate::DbState;
 use crate::db::purpose::{Purpose, CreatePurposeRequest};
+use crate::db::audit_log::AuditAction;
 use rusqlite::{Result, Error as RusqliteError};
 
 #[tauri::command]
@@ -16,11 +17,14 @@ pub async fn create_purpose(
     let db = state.0.clone();
     let purpose_repo = db.purpose_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            purpose_repo.create_purpose(conn, purpose)
-                .map_err(|_| RusqliteError::InvalidQuery)
+            let purpose = purpose_repo.create_purpose(conn, purpose)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::PurposeCreated, Some(&purpose.id.to_string()), Some(&purpose.label))?;
+            Ok(purpose)
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -86,11 +90,14 @@ pub async fn update_purpose(
     let db = state.0.clone();
     let purpose_repo = db.purpose_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            purpose_repo.update_purpose(conn, purpose_id, purpose)
-                .map_err(|_| RusqliteError::InvalidQuery)
+            let purpose = purpose_repo.update_purpose(conn, purpose_id, purpose)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::PurposeUpdated, Some(&purpose.id.to_string()), Some(&purpose.label))?;
+            Ok(purpose)
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -110,11 +117,14 @@ pub async fn soft_delete_purpose(
     let db = state.0.clone();
     let purpose_repo = db.purpose_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
             purpose_repo.soft_delete_purpose(conn, purpose_id)
-                .map_err(|_| RusqliteError::InvalidQuery)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            let label = purpose_repo.get_purpose(conn, purpose_id).ok().map(|purpose| purpose.label);
+            audit_log.record(conn, &username, AuditAction::PurposeDeleted, Some(&purpose_id.to_string()), label.as_deref())
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -134,11 +144,14 @@ pub async fn restore_purpose(
     let db = state.0.clone();
     let purpose_repo = db.purpose_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
             purpose_repo.restore_purpose(conn, purpose_id)
-                .map_err(|_| RusqliteError::InvalidQuery)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            let label = purpose_repo.get_purpose(conn, purpose_id).ok().map(|purpose| purpose.label);
+            audit_log.record(conn, &username, AuditAction::PurposeRestored, Some(&purpose_id.to_string()), label.as_deref())
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -0,0 +1,288 @@
+// src/report_pdf.rs
+use printpdf::image_crate::{self, DynamicImage};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont,
+    Image,
+    ImageTransform,
+    IndirectFontRef,
+    Line,
+    Mm,
+    PdfDocument,
+    PdfDocumentReference,
+    PdfLayerReference,
+    Point,
+    Rect
+};
+use std::fs::File;
+use std::io::BufWriter;
+use chrono::Utc;
+
+use crate::db::attendance_analytics::SeriesPoint;
+use crate::db::attendance_report::AttendanceReport;
+
+// Same header the frontend puts on printed attendance records
+const HEADER_IMAGE: &[u8] = include_bytes!("../../public/attendance_records_header.png");
+
+// Philippine legal paper, landscape
+const PAGE_WIDTH: f32 = 330.2;
+const PAGE_HEIGHT: f32 = 215.9;
+const MARGIN: f32 = 12.7;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - (MARGIN * 2.0);
+const ROW_HEIGHT: f32 = 5.0;
+const ROWS_PER_PAGE: usize = 28;
+const FONT_SIZE: f32 = 8.0;
+const SIGNATURE_HEIGHT: f32 = 30.0;
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+}
+
+// Column title and its share of the content width
+const DETAIL_COLUMNS: [(&str, f32); 8] = [
+    ("Date", 0.08),
+    ("Time In", 0.07),
+    ("Time Out", 0.07),
+    ("School ID", 0.10),
+    ("Name", 0.22),
+    ("Classification", 0.19),
+    ("Purpose of Visit", 0.21),
+    ("Entry", 0.06),
+];
+
+// Cuts text so it fits a cell, using Helvetica's average glyph width
+fn fit_text(text: &str, width: f32, font_size: f32) -> String {
+    let char_width = font_size * 0.3528 * 0.5;
+    let max_chars = ((width - 2.0) / char_width).max(1.0) as usize;
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut fitted: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        fitted.push_str("...");
+        fitted
+    }
+}
+
+// Converts a distance from the top of the page into printpdf's bottom-up coordinates
+fn y_from_top(top: f32) -> Mm {
+    Mm(PAGE_HEIGHT - top)
+}
+
+fn draw_cell(layer: &PdfLayerReference, font: &IndirectFontRef, text: &str, x: f32, top: f32, width: f32) {
+    layer.add_rect(
+        Rect::new(Mm(x), y_from_top(top + ROW_HEIGHT), Mm(x + width), y_from_top(top))
+            .with_mode(PaintMode::Stroke)
+    );
+    layer.use_text(
+        fit_text(text, width, FONT_SIZE),
+        FONT_SIZE,
+        Mm(x + 1.0),
+        y_from_top(top + ROW_HEIGHT - 1.5),
+        font
+    );
+}
+
+fn draw_header(
+    layer: &PdfLayerReference,
+    fonts: &Fonts,
+    header: &DynamicImage,
+    title: &str,
+    page: usize,
+    total_pages: usize
+) -> f32 {
+    // Scale the header to the full content width
+    let dpi = header.width() as f32 * 25.4 / CONTENT_WIDTH;
+    let header_height = header.height() as f32 * 25.4 / dpi;
+
+    Image::from_dynamic_image(header).add_to_layer(layer.clone(), ImageTransform {
+        translate_x: Some(Mm(MARGIN)),
+        translate_y: Some(y_from_top(MARGIN + header_height)),
+        dpi: Some(dpi),
+        ..Default::default()
+    });
+
+    let title_top = MARGIN + header_height + 6.0;
+    layer.use_text(title, 11.0, Mm(MARGIN), y_from_top(title_top), &fonts.bold);
+    layer.use_text(
+        format!("Page {} of {}", page, total_pages),
+        FONT_SIZE,
+        Mm(PAGE_WIDTH - MARGIN - 20.0),
+        Mm(MARGIN - 4.0),
+        &fonts.regular
+    );
+
+    title_top + 4.0
+}
+
+// Keeps a series to `max_rows` rows by folding the smallest buckets into "Others"
+fn fold_series(points: &[SeriesPoint], max_rows: usize) -> Vec<SeriesPoint> {
+    if points.len() <= max_rows {
+        return points.to_vec();
+    }
+
+    let keep = max_rows.saturating_sub(1);
+    let mut folded = points[..keep].to_vec();
+    folded.push(SeriesPoint {
+        label: "Others".to_string(),
+        value: points[keep..].iter().map(|point| point.value).sum(),
+    });
+    folded
+}
+
+// Draws a two column "label / visits" table and returns where it ends
+fn draw_totals_table(
+    layer: &PdfLayerReference,
+    fonts: &Fonts,
+    heading: &str,
+    points: &[SeriesPoint],
+    x: f32,
+    top: f32,
+    width: f32
+) -> f32 {
+    // Leave room for the heading and total rows plus the signature block
+    let max_rows = ((PAGE_HEIGHT - MARGIN - SIGNATURE_HEIGHT - top) / ROW_HEIGHT) as usize - 2;
+    let label_width = width * 0.75;
+    let count_width = width - label_width;
+
+    draw_cell(layer, &fonts.bold, heading, x, top, label_width);
+    draw_cell(layer, &fonts.bold, "Visits", x + label_width, top, count_width);
+
+    let mut row_top = top + ROW_HEIGHT;
+    for point in fold_series(points, max_rows) {
+        draw_cell(layer, &fonts.regular, &point.label, x, row_top, label_width);
+        draw_cell(layer, &fonts.regular, &point.value.to_string(), x + label_width, row_top, count_width);
+        row_top += ROW_HEIGHT;
+    }
+
+    let total: u64 = points.iter().map(|point| point.value).sum();
+    draw_cell(layer, &fonts.bold, "Total", x, row_top, label_width);
+    draw_cell(layer, &fonts.bold, &total.to_string(), x + label_width, row_top, count_width);
+
+    row_top + ROW_HEIGHT
+}
+
+fn draw_signatures(layer: &PdfLayerReference, fonts: &Fonts, prepared_by: Option<&str>, top: f32) {
+    let line_width = 80.0;
+    let blocks = [
+        ("Prepared by:", prepared_by.unwrap_or("")),
+        ("Noted by:", ""),
+    ];
+
+    for (index, (caption, name)) in blocks.iter().enumerate() {
+        let x = MARGIN + index as f32 * (CONTENT_WIDTH - line_width);
+        layer.use_text(*caption, FONT_SIZE, Mm(x), y_from_top(top), &fonts.regular);
+        layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x), y_from_top(top + 12.0)), false),
+                (Point::new(Mm(x + line_width), y_from_top(top + 12.0)), false),
+            ],
+            is_closed: false,
+        });
+        layer.use_text(*name, FONT_SIZE, Mm(x), y_from_top(top + 16.0), &fonts.bold);
+    }
+}
+
+fn new_page(doc: &PdfDocumentReference) -> PdfLayerReference {
+    let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    doc.get_page(page).get_layer(layer)
+}
+
+// Renders the summary page followed by the visit listing
+pub fn write_report_pdf(report: &AttendanceReport, prepared_by: Option<&str>, file_path: &str) -> Result<usize, String> {
+    let (doc, first_page, first_layer) = PdfDocument::new(
+        report.title.as_str(),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Layer 1"
+    );
+    let fonts = Fonts {
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?,
+    };
+
+    let header = image_crate::load_from_memory(HEADER_IMAGE)
+        .map_err(|e| format!("Failed to load report header: {}", e))?;
+
+    let detail_pages = report.rows.len().div_ceil(ROWS_PER_PAGE);
+    let total_pages = 1 + detail_pages;
+
+    // Summary page
+    let layer = doc.get_page(first_page).get_layer(first_layer);
+    let mut top = draw_header(&layer, &fonts, &header, &report.title, 1, total_pages);
+
+    top += 4.0;
+    layer.use_text(
+        format!(
+            "Total visits: {}    Unique visitors: {}    Manual entries: {}    Generated: {}",
+            report.total_visits,
+            report.unique_visitors,
+            report.manual_visits,
+            report.timezone.local(Utc::now()).format("%B %d, %Y %I:%M %p")
+        ),
+        FONT_SIZE + 1.0,
+        Mm(MARGIN),
+        y_from_top(top),
+        &fonts.regular
+    );
+    top += 4.0;
+
+    let table_width = (CONTENT_WIDTH - 10.0) / 2.0;
+    let purpose_end = draw_totals_table(&layer, &fonts, "Purpose", &report.by_purpose, MARGIN, top, table_width);
+    let course_end = draw_totals_table(
+        &layer,
+        &fonts,
+        "Course",
+        &report.by_course,
+        MARGIN + table_width + 10.0,
+        top,
+        table_width
+    );
+
+    let signature_top = purpose_end.max(course_end) + 10.0;
+    draw_signatures(&layer, &fonts, prepared_by, signature_top);
+
+    // Visit listing
+    for (index, chunk) in report.rows.chunks(ROWS_PER_PAGE).enumerate() {
+        let layer = new_page(&doc);
+        let mut top = draw_header(&layer, &fonts, &header, &report.title, index + 2, total_pages);
+
+        let mut x = MARGIN;
+        for (title, share) in DETAIL_COLUMNS.iter() {
+            draw_cell(&layer, &fonts.bold, title, x, top, CONTENT_WIDTH * share);
+            x += CONTENT_WIDTH * share;
+        }
+        top += ROW_HEIGHT;
+
+        for row in chunk {
+            let attendance = &row.attendance;
+            let time_in = report.timezone.local(attendance.time_in_date);
+            let values = [
+                time_in.format("%Y-%m-%d").to_string(),
+                time_in.format("%H:%M").to_string(),
+                attendance.time_out_date
+                    .map(|time_out| report.timezone.local(time_out).format("%H:%M").to_string())
+                    .unwrap_or_default(),
+                attendance.school_id.clone(),
+                attendance.full_name.clone(),
+                attendance.classification.clone(),
+                attendance.purpose_label.clone().unwrap_or_default(),
+                if attendance.is_manual { "Manual" } else { "Kiosk" }.to_string(),
+            ];
+
+            let mut x = MARGIN;
+            for (value, (_, share)) in values.iter().zip(DETAIL_COLUMNS.iter()) {
+                draw_cell(&layer, &fonts.regular, value, x, top, CONTENT_WIDTH * share);
+                x += CONTENT_WIDTH * share;
+            }
+            top += ROW_HEIGHT;
+        }
+    }
+
+    let file = File::create(file_path)
+        .map_err(|e| format!("Failed to create report file: {}", e))?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|e| format!("Failed to write report: {}", e))?;
+
+    Ok(total_pages)
+}
@@ -6,6 +6,8 @@ use tauri::State;
 use crate::DbState;
 use crate::db::school_accounts::{PaginatedSchoolAccounts, SchoolAccount, UpdateSchoolAccountRequest, AccountStatusCounts};
 use crate::db::semester::Semester;
+use crate::db::audit_log::AuditAction;
+use crate::session::SessionState;
 use uuid::Uuid;
 use rusqlite::{Result, Error as RusqliteError};
 use serde::{Serialize, Deserialize};
@@ -194,12 +196,15 @@ pub async fn get_school_account_with_semester(
 #[tauri::command]
 pub async fn update_school_account_semester(
     state: State<'_, DbState>,
+    session: State<'_, SessionState>,
     id: String,
     semester_id: String,
 ) -> Result<SchoolAccount, String> {
     let db = state.0.clone();
     let school_accounts = db.school_accounts.clone();
     let semester_repo = db.semester_repository.clone();
+    let audit_log = db.audit_log.clone();
+    let actor = session.actor();
     
     db.with_connection(move |conn| {
         let account_id = Uuid::parse_str(&id)
@@ -208,7 +213,7 @@ pub async fn update_school_account_semester(
             .map_err(|_| RusqliteError::InvalidQuery)?;
 
         // Validate semester exists
-        semester_repo.get_semester(conn, semester_uuid)
+        let semester = semester_repo.get_semester(conn, semester_uuid)
             .map_err(|_| RusqliteError::InvalidQuery)?;
 
         let update = UpdateSchoolAccountRequest {
@@ -216,7 +221,15 @@ pub async fn update_school_account_semester(
             ..Default::default()
         };
 
-        school_accounts.update_school_account(conn, account_id, update)
-            .map_err(|_| RusqliteError::InvalidQuery)
+        let account = school_accounts.update_school_account(conn, account_id, update)
+            .map_err(|_| RusqliteError::InvalidQuery)?;
+        audit_log.record(
+            conn,
+            &actor,
+            AuditAction::AccountSemesterChanged,
+            Some(&account.school_id),
+            Some(&format!("Semester: {}", semester.label))
+        )?;
+        Ok(account)
     }).await.map_err(|e| e.to_string())
 }
\ No newline at end of file
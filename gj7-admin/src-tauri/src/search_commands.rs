@@ -0,0 +1,19 @@
+// src/search_commands.rs
+use tauri::State;
+use crate::DbState;
+use crate::db::search::SearchResult;
+use rusqlite::Result;
+
+#[tauri::command]
+pub async fn search_all(
+    state: State<'_, DbState>,
+    query: String,
+    limit: Option<u32>
+) -> Result<Vec<SearchResult>, String> {
+    let db = state.0.clone();
+    let search = db.search.clone();
+    
+    db.with_connection(move |conn| {
+        search.search(conn, &query, limit)
+    }).await.map_err(|e| e.to_string())
+}
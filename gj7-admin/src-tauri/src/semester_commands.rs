@@ -3,6 +3,9 @@ use tauri::State;
 use uuid::Uuid;
 use crate::DbState;
 use crate::db::semester::{Semester, CreateSemesterRequest};
+use crate::db::semester_accounts::SemesterAccount;
+use crate::db::audit_log::AuditAction;
+use std::sync::Arc;
 use rusqlite::{Result, Error as RusqliteError};
 
 #[tauri::command]
@@ -15,11 +18,14 @@ pub async fn create_semester(
     let db = state.0.clone();
     let semester_repo = db.semester_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            semester_repo.create_semester(conn, semester)
-                .map_err(|_| RusqliteError::InvalidQuery)
+            let semester = semester_repo.create_semester(conn, semester)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::SemesterCreated, Some(&semester.id.to_string()), Some(&semester.label))?;
+            Ok(semester)
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -84,11 +90,14 @@ pub async fn update_semester(
     let db = state.0.clone();
     let semester_repo = db.semester_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            semester_repo.update_semester(conn, semester_id, semester)
-                .map_err(|_| RusqliteError::InvalidQuery)
+            let semester = semester_repo.update_semester(conn, semester_id, semester)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::SemesterUpdated, Some(&semester.id.to_string()), Some(&semester.label))?;
+            Ok(semester)
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -108,11 +117,14 @@ pub async fn delete_semester(
     let db = state.0.clone();
     let semester_repo = db.semester_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
+            let label = semester_repo.get_semester(conn, semester_id).ok().map(|semester| semester.label);
             semester_repo.delete_semester(conn, semester_id)
-                .map_err(|_| RusqliteError::InvalidQuery)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::SemesterDeleted, Some(&semester_id.to_string()), label.as_deref())
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
@@ -132,13 +144,42 @@ pub async fn set_active_semester(
     let db = state.0.clone();
     let semester_repo = db.semester_repository.clone();
     let auth = db.auth.clone();
+    let audit_log = db.audit_log.clone();
     
     db.with_connection(move |conn| {
         if auth.authenticate(conn, &username, &password)? {
-            semester_repo.set_active_semester(conn, semester_id)
-                .map_err(|_| RusqliteError::InvalidQuery)
+            let semester = semester_repo.set_active_semester(conn, semester_id)
+                .map_err(|_| RusqliteError::InvalidQuery)?;
+            audit_log.record(conn, &username, AuditAction::SemesterActivated, Some(&semester.id.to_string()), Some(&semester.label))?;
+            Ok(semester)
         } else {
             Err(RusqliteError::QueryReturnedNoRows)
         }
     }).await.map_err(|e| format!("Authentication failed: {}", e.to_string()))
+}
+
+#[tauri::command]
+pub async fn get_semester_accounts(
+    state: State<'_, DbState>,
+    semester_id: Uuid
+) -> Result<Vec<SemesterAccount>, String> {
+    let db = state.0.clone();
+    let semester_accounts = Arc::clone(&db.semester_accounts);
+    
+    db.with_connection(move |conn| {
+        semester_accounts.get_semester_accounts(conn, semester_id)
+    }).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_account_semester_history(
+    state: State<'_, DbState>,
+    school_id: String
+) -> Result<Vec<SemesterAccount>, String> {
+    let db = state.0.clone();
+    let semester_accounts = Arc::clone(&db.semester_accounts);
+    
+    db.with_connection(move |conn| {
+        semester_accounts.get_account_history(conn, &school_id)
+    }).await.map_err(|e| e.to_string())
 }
\ No newline at end of file
@@ -0,0 +1,408 @@
+// src/session.rs
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use serde::Serialize;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::db::auth::User;
+use crate::db::roles::{Permission, Role};
+
+// The user signed in to this desktop app
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionUser {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+    pub has_pin: bool,
+}
+
+impl From<&User> for SessionUser {
+    fn from(user: &User) -> Self {
+        SessionUser {
+            id: user.id,
+            username: user.username.clone(),
+            role: user.role,
+            permissions: user.role.permissions(),
+            has_pin: user.has_pin,
+        }
+    }
+}
+
+// How often the idle lock looks for an expired session
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum LockState {
+    SignedOut,
+    Unlocked,
+    Locked,
+}
+
+// SignedOut -> Unlocked on login; Unlocked -> Locked when idle or locked by hand,
+// or -> SignedOut when the user has no PIN to unlock with; Locked -> Unlocked
+// with the PIN; any state -> SignedOut on logout
+#[derive(Default)]
+enum Session {
+    #[default]
+    SignedOut,
+    Unlocked { user: SessionUser, last_activity: Instant },
+    Locked { user: SessionUser },
+}
+
+impl Session {
+    fn user(&self) -> Option<&SessionUser> {
+        match self {
+            Session::SignedOut => None,
+            Session::Unlocked { user, .. } | Session::Locked { user } => Some(user),
+        }
+    }
+
+    fn lock_state(&self) -> LockState {
+        match self {
+            Session::SignedOut => LockState::SignedOut,
+            Session::Unlocked { .. } => LockState::Unlocked,
+            Session::Locked { .. } => LockState::Locked,
+        }
+    }
+
+    // Users without a PIN are signed out instead, since they could not unlock
+    fn lock_or_sign_out(&mut self) {
+        if let Session::Unlocked { user, .. } = self {
+            *self = if user.has_pin {
+                Session::Locked { user: user.clone() }
+            } else {
+                Session::SignedOut
+            };
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SessionState {
+    session: Mutex<Session>,
+    // None while the idle lock is turned off
+    idle_timeout: Mutex<Option<Duration>>,
+}
+
+impl SessionState {
+    pub fn sign_in(&self, user: &User) -> SessionUser {
+        let session_user = SessionUser::from(user);
+        *self.session.lock().unwrap() = Session::Unlocked {
+            user: session_user.clone(),
+            last_activity: Instant::now(),
+        };
+        session_user
+    }
+
+    pub fn sign_out(&self) {
+        *self.session.lock().unwrap() = Session::SignedOut;
+    }
+
+    // The signed-in user, locked or not
+    pub fn current(&self) -> Option<SessionUser> {
+        self.session.lock().unwrap().user().cloned()
+    }
+
+    // Name recorded in the audit log for actions that take no credentials
+    pub fn actor(&self) -> String {
+        self.current()
+            .map(|session_user| session_user.username)
+            .unwrap_or_else(|| "system".to_string())
+    }
+
+    pub fn lock_state(&self) -> LockState {
+        self.session.lock().unwrap().lock_state()
+    }
+
+    // Keeps the session in step when the signed-in user's role, PIN or status changes
+    pub fn refresh(&self, user: &User) {
+        let mut session = self.session.lock().unwrap();
+        if !matches!(session.user(), Some(session_user) if session_user.id == user.id) {
+            return;
+        }
+
+        if !user.is_active {
+            *session = Session::SignedOut;
+            return;
+        }
+        match &mut *session {
+            Session::Unlocked { user: session_user, .. } | Session::Locked { user: session_user } => {
+                *session_user = SessionUser::from(user);
+            }
+            Session::SignedOut => {}
+        }
+    }
+
+    pub fn forget(&self, user_id: i64) {
+        let mut session = self.session.lock().unwrap();
+        if session.user().is_some_and(|session_user| session_user.id == user_id) {
+            *session = Session::SignedOut;
+        }
+    }
+
+    // Called for user input in the app; background refreshes do not count
+    pub fn record_activity(&self) {
+        if let Session::Unlocked { last_activity, .. } = &mut *self.session.lock().unwrap() {
+            *last_activity = Instant::now();
+        }
+    }
+
+    pub fn lock(&self) -> LockState {
+        let mut session = self.session.lock().unwrap();
+        session.lock_or_sign_out();
+        session.lock_state()
+    }
+
+    // Only called once the PIN has been verified
+    pub fn unlock(&self) {
+        let mut session = self.session.lock().unwrap();
+        if let Session::Locked { user } = &*session {
+            *session = Session::Unlocked { user: user.clone(), last_activity: Instant::now() };
+        }
+    }
+
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        *self.idle_timeout.lock().unwrap() = timeout;
+    }
+
+    // Locks an unlocked session that has been idle too long, returning the new state
+    fn lock_if_idle(&self) -> Option<LockState> {
+        let timeout = (*self.idle_timeout.lock().unwrap())?;
+        let mut session = self.session.lock().unwrap();
+        match &*session {
+            Session::Unlocked { last_activity, .. } if last_activity.elapsed() >= timeout => {
+                session.lock_or_sign_out();
+                Some(session.lock_state())
+            }
+            _ => None,
+        }
+    }
+
+    fn require_unlocked(&self) -> Result<SessionUser, String> {
+        match &*self.session.lock().unwrap() {
+            Session::SignedOut => Err("Sign in first".to_string()),
+            Session::Locked { .. } => Err("The app is locked; enter your PIN to continue".to_string()),
+            Session::Unlocked { user, .. } => Ok(user.clone()),
+        }
+    }
+
+    pub fn require_permission(&self, permission: Permission) -> Result<SessionUser, String> {
+        let session_user = self.require_unlocked()?;
+        if !session_user.role.allows(permission) {
+            return Err(format!(
+                "{} accounts are not allowed to do this ({:?})",
+                session_user.role.as_str(),
+                permission
+            ));
+        }
+
+        Ok(session_user)
+    }
+}
+
+enum CommandAccess {
+    // Usable before signing in
+    Public,
+    // Any signed-in user, acting on their own account
+    SignedIn,
+    // Any signed-in user, even while the app is locked
+    SignedInOrLocked,
+    Requires(Permission),
+}
+
+// Every command registered in lib.rs must be listed here; unknown commands are refused
+fn command_access(command: &str) -> Option<CommandAccess> {
+    use CommandAccess::*;
+    use Permission::*;
+
+    let access = match command {
+        "login"
+        | "logout"
+        | "get_current_user"
+        | "get_lock_state"
+        | "reset_password_with_recovery_code"
+        | "get_database_info"
+        | "check_network"
+        | "get_all_settings_styles"
+        | "get_settings_style"
+        | "get_settings_style_by_component_name"
+        | "search_settings_styles" => Public,
+
+        "authenticate"
+        | "change_password"
+        | "generate_recovery_codes"
+        | "set_pin"
+        | "clear_pin"
+        | "record_activity"
+        | "lock_session"
+        | "get_idle_lock_settings" => SignedIn,
+
+        "verify_pin" | "get_pin_status" => SignedInOrLocked,
+
+        "list_users"
+        | "create_user"
+        | "set_user_role"
+        | "set_user_active"
+        | "delete_user"
+        | "reset_user_password" => Requires(ManageUsers),
+
+        "get_all_notes"
+        | "get_note"
+        | "search_notes"
+        | "get_all_school_accounts"
+        | "get_paginated_school_accounts"
+        | "get_school_account_with_semester"
+        | "get_dashboard_stats"
+        | "get_school_accounts_by_course"
+        | "get_all_semesters"
+        | "get_semester"
+        | "get_semester_by_label"
+        | "get_semester_accounts"
+        | "get_account_semester_history"
+        | "get_all_purposes"
+        | "get_purpose"
+        | "get_purpose_by_label"
+        | "get_all_attendances"
+        | "get_attendance"
+        | "get_attendance_history"
+        | "get_attendances_by_semester"
+        | "get_attendances_by_school_account"
+        | "get_filtered_attendances"
+        | "get_all_courses"
+        | "get_attendance_locations"
+        | "get_scan_cooldowns"
+        | "get_attendance_analytics"
+        | "get_attendance_series"
+        | "get_occupancy"
+        | "get_occupancy_settings"
+        | "get_library_timezone"
+        | "get_network_server_settings"
+        | "get_server_certificate_fingerprint"
+        | "list_kiosk_devices"
+        | "search_all" => Requires(ViewRecords),
+
+        "export_attendances" | "generate_attendance_report" => Requires(ExportReports),
+
+        "create_attendance" | "checkout_attendance" | "expire_stale_visits" => Requires(RecordAttendance),
+
+        "create_manual_attendance"
+        | "update_attendance"
+        | "delete_attendance"
+        | "restore_attendance"
+        | "preview_bulk_attendance_action"
+        | "apply_bulk_attendance_action" => Requires(EditAttendance),
+
+        "create_note" | "update_note" | "delete_note" => Requires(ManageNotes),
+
+        "update_school_account_semester" => Requires(ManageAccounts),
+
+        "validate_csv_file"
+        | "import_csv_file"
+        | "import_csv_file_parallel"
+        | "check_existing_accounts" => Requires(ImportCsv),
+
+        "create_semester"
+        | "update_semester"
+        | "delete_semester"
+        | "set_active_semester" => Requires(ManageSemesters),
+
+        "create_purpose"
+        | "update_purpose"
+        | "soft_delete_purpose"
+        | "restore_purpose" => Requires(ManagePurposes),
+
+        "set_scan_cooldown"
+        | "delete_scan_cooldown"
+        | "set_occupancy_settings"
+        | "set_library_timezone"
+        | "set_idle_lock_settings"
+        | "set_network_server_settings"
+        | "create_settings_style"
+        | "update_settings_style"
+        | "delete_settings_style" => Requires(ManageSettings),
+
+        "get_audit_log" | "export_audit_log" | "verify_hash_chain" => Requires(ViewAuditLog),
+
+        "create_pairing_code" | "revoke_kiosk_device" => Requires(ManageDevices),
+
+        _ => return None,
+    };
+
+    Some(access)
+}
+
+pub fn authorize_command(session: &SessionState, command: &str) -> Result<(), String> {
+    match command_access(command) {
+        Some(CommandAccess::Public) => Ok(()),
+        Some(CommandAccess::SignedIn) => session.require_unlocked().map(|_| ()),
+        Some(CommandAccess::SignedInOrLocked) => session.current()
+            .map(|_| ())
+            .ok_or_else(|| "Sign in first".to_string()),
+        Some(CommandAccess::Requires(permission)) => session.require_permission(permission).map(|_| ()),
+        None => Err(format!("Command {} has no permission rule", command)),
+    }
+}
+
+// Commands confirmed with a password name the acting user in `username`. It must be
+// the signed-in user, so the action is checked and recorded against the same account.
+fn check_acting_user(session: &SessionState, command: &str, payload: &InvokeBody) -> Result<(), String> {
+    if matches!(command_access(command), Some(CommandAccess::Public)) {
+        return Ok(());
+    }
+    let InvokeBody::Json(args) = payload else {
+        return Ok(());
+    };
+    let Some(username) = args.get("username").and_then(serde_json::Value::as_str) else {
+        return Ok(());
+    };
+
+    match session.current() {
+        Some(session_user) if session_user.username == username => Ok(()),
+        _ => Err("Confirm with the signed-in user's password".to_string()),
+    }
+}
+
+// Wraps the generated invoke handler so every command is checked against the
+// signed-in user's role, and any acting user against the session, before it runs
+pub fn with_permission_checks<R, F>(handler: F) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static
+where
+    R: Runtime,
+    F: Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+{
+    move |invoke| {
+        let webview = invoke.message.webview();
+        let session = webview.state::<SessionState>();
+        let command = invoke.message.command();
+        let authorized = authorize_command(&session, command)
+            .and_then(|_| check_acting_user(&session, command, invoke.message.payload()));
+
+        if let Err(message) = authorized {
+            warn!("Refused command {}: {}", invoke.message.command(), message);
+            invoke.resolver.reject(message);
+            return true;
+        }
+
+        handler(invoke)
+    }
+}
+
+// Checks for an idle session in the background and tells the frontend when it locks
+pub fn start_idle_lock<R: Runtime>(app_handle: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(lock_state) = app_handle.state::<SessionState>().lock_if_idle() else {
+                continue;
+            };
+
+            info!("Session idle, now {:?}", lock_state);
+            if let Err(e) = app_handle.emit("session-locked", lock_state) {
+                warn!("Failed to emit session-locked: {}", e);
+            }
+        }
+    });
+}
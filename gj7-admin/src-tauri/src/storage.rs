@@ -51,4 +51,9 @@ impl AppStorage {
         self.public_storage.join("config.xml")
     }
 
+    // The network server's certificate and private key
+    pub fn get_tls_dir(&self) -> PathBuf {
+        self.safe_storage.join("tls")
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,215 @@
+// src/tls.rs
+
+use std::fs;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use log::{info, warn};
+use rcgen::{Certificate, CertificateParams, DistinguishedName, DnType, KeyPair};
+use sha2::{Digest, Sha256};
+
+const CERTIFICATE_FILE: &str = "server.crt";
+const PRIVATE_KEY_FILE: &str = "server.key";
+// The names the stored certificate was issued for, so address changes are noticed
+const CERTIFICATE_NAMES_FILE: &str = "server.names";
+
+// Always included so the server can be reached from the admin PC itself
+const LOCAL_NAMES: [&str; 2] = ["localhost", "127.0.0.1"];
+const COMMON_NAME: &str = "Library Attendance Server";
+
+// The names a certificate for a server bound to this address needs: the address
+// itself or, when bound to every interface, each of this PC's LAN addresses
+pub fn certificate_names(bind_address: IpAddr) -> Vec<String> {
+    let mut names: Vec<String> = LOCAL_NAMES.iter().map(|name| name.to_string()).collect();
+
+    if bind_address.is_unspecified() {
+        match if_addrs::get_if_addrs() {
+            Ok(interfaces) => names.extend(
+                interfaces.iter()
+                    .filter(|interface| !interface.is_loopback())
+                    // 0.0.0.0 only accepts IPv4 connections
+                    .filter(|interface| bind_address.is_ipv6() || interface.ip().is_ipv4())
+                    .map(|interface| interface.ip().to_string())
+            ),
+            Err(e) => warn!("Failed to list network interfaces: {}", e),
+        }
+    } else {
+        names.push(bind_address.to_string());
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Creates the key file readable by the owner only. On Windows the file inherits
+// the ACL of the per-user app data directory it lives in, which already keeps
+// other users out.
+fn write_private_key(path: &Path, key_pem: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(key_pem.as_bytes())?;
+    // The mode only applies to new files, so tighten one that already existed
+    restrict_to_owner(path)
+}
+
+#[cfg(unix)]
+fn restrict_to_owner(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict_to_owner(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+// The network server's self-signed certificate
+pub struct ServerCertificate {
+    pub cert_pem: String,
+    pub key_pem: String,
+}
+
+impl ServerCertificate {
+    // Reuses the stored certificate while it still covers these names. Otherwise a
+    // new one is issued for the same key, so kiosks that pinned the key keep working.
+    pub fn load_or_create(dir: &Path, names: &[String]) -> io::Result<Self> {
+        let cert_path = dir.join(CERTIFICATE_FILE);
+        let key_path = dir.join(PRIVATE_KEY_FILE);
+        let names_path = dir.join(CERTIFICATE_NAMES_FILE);
+        let names_list = names.join("\n");
+
+        let stored_key = if key_path.exists() {
+            // Keys written by earlier versions were readable by every local user
+            restrict_to_owner(&key_path)?;
+            Some(fs::read_to_string(&key_path)?)
+        } else {
+            None
+        };
+
+        if let Some(key_pem) = &stored_key {
+            let stored_names = fs::read_to_string(&names_path).ok();
+            if cert_path.exists() && stored_names.as_deref() == Some(names_list.as_str()) {
+                return Ok(ServerCertificate {
+                    cert_pem: fs::read_to_string(&cert_path)?,
+                    key_pem: key_pem.clone(),
+                });
+            }
+        }
+
+        let mut params = CertificateParams::new(names.to_vec());
+        let mut distinguished_name = DistinguishedName::new();
+        distinguished_name.push(DnType::CommonName, COMMON_NAME);
+        params.distinguished_name = distinguished_name;
+        params.not_after = rcgen::date_time_ymd(2099, 12, 31);
+        if let Some(key_pem) = &stored_key {
+            params.key_pair = Some(KeyPair::from_pem(key_pem).map_err(io::Error::other)?);
+        }
+
+        let certificate = Certificate::from_params(params).map_err(io::Error::other)?;
+        let generated = ServerCertificate {
+            cert_pem: certificate.serialize_pem().map_err(io::Error::other)?,
+            key_pem: certificate.serialize_private_key_pem(),
+        };
+
+        fs::create_dir_all(dir)?;
+        if stored_key.is_none() {
+            write_private_key(&key_path, &generated.key_pem)?;
+            info!("Generated network server key {}", generated.fingerprint()?);
+        }
+        fs::write(&cert_path, &generated.cert_pem)?;
+        fs::write(&names_path, &names_list)?;
+
+        info!("Issued network server certificate for {}", names.join(", "));
+        Ok(generated)
+    }
+
+    // SHA-256 of the DER public key as colon-separated hex. Kiosks pin this rather
+    // than the certificate, which is reissued whenever the addresses change.
+    pub fn fingerprint(&self) -> io::Result<String> {
+        let key_pair = KeyPair::from_pem(&self.key_pem)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(Sha256::digest(key_pair.public_key_der())
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(":"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn empty_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gj7-tls-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn names(addresses: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = LOCAL_NAMES.iter().chain(addresses).map(|name| name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn certificate_names_cover_a_specific_bind_address() {
+        let bind_address: IpAddr = "192.168.1.20".parse().unwrap();
+        assert_eq!(certificate_names(bind_address), names(&["192.168.1.20"]));
+    }
+
+    #[test]
+    fn stored_certificate_is_reused_for_the_same_names() {
+        let dir = empty_dir("reuse");
+        let first = ServerCertificate::load_or_create(&dir, &names(&["192.168.1.20"])).unwrap();
+        let second = ServerCertificate::load_or_create(&dir, &names(&["192.168.1.20"])).unwrap();
+
+        assert_eq!(first.cert_pem, second.cert_pem);
+        assert_eq!(first.key_pem, second.key_pem);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn changed_addresses_reissue_the_certificate_for_the_same_key() {
+        let dir = empty_dir("reissue");
+        let before = ServerCertificate::load_or_create(&dir, &names(&["192.168.1.20"])).unwrap();
+        let after = ServerCertificate::load_or_create(&dir, &names(&["192.168.1.20", "10.0.0.5"])).unwrap();
+
+        assert_ne!(before.cert_pem, after.cert_pem);
+        assert_eq!(before.key_pem, after.key_pem);
+        assert_eq!(before.fingerprint().unwrap(), after.fingerprint().unwrap());
+        assert_eq!(fs::read_to_string(dir.join(CERTIFICATE_FILE)).unwrap(), after.cert_pem);
+        assert_eq!(
+            fs::read_to_string(dir.join(CERTIFICATE_NAMES_FILE)).unwrap(),
+            names(&["192.168.1.20", "10.0.0.5"]).join("\n")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_key_is_readable_by_its_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = empty_dir("key-mode");
+        ServerCertificate::load_or_create(&dir, &names(&[])).unwrap();
+        let mode = fs::metadata(dir.join(PRIVATE_KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // A key left readable by an earlier version is tightened on the next start
+        fs::set_permissions(dir.join(PRIVATE_KEY_FILE), fs::Permissions::from_mode(0o644)).unwrap();
+        ServerCertificate::load_or_create(&dir, &names(&[])).unwrap();
+        let mode = fs::metadata(dir.join(PRIVATE_KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,24 +4,36 @@ use axum::{
     extract::{
         ws::{WebSocket, WebSocketUpgrade},
         State,
+        Extension,
     },
+    middleware,
     response::Response,
     routing::get,
     Router,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
-use tokio::sync::{mpsc, Mutex};
-use std::{collections::HashMap, sync::Arc, path::PathBuf};
+use tokio::sync::{mpsc, Mutex, Notify};
+use std::{collections::HashMap, sync::Arc, path::PathBuf, time::Duration};
 use serde::{Serialize, Deserialize};
 use serde_json::json;
 use rusqlite::Connection;
+use log::warn;
 
 use crate::db::attendance::{
     Attendance,
     CreateAttendanceRequest,
     SqliteAttendanceRepository,
-    AttendanceRepository
+    AttendanceRepository,
+    ScanError,
+    ScanOutcome
 };
+use crate::db::occupancy::{Occupancy, OccupancyDatabase};
+use crate::db::hash_chain::register_functions;
+use crate::db::kiosk_devices::{KioskDevice, KioskDeviceDatabase, KioskHeartbeat};
+use crate::kiosk_auth::{attribute_to_device, require_device_token};
+
+// How long a scan waits for another kiosk's scan to finish writing
+const SCAN_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Clone)]
 pub struct DatabaseAccessor {
@@ -33,16 +45,24 @@ impl DatabaseAccessor {
         Self { db_path }
     }
 
+    // Writes to attendance need the hash chain functions on the connection
     pub fn get_connection(&self) -> Result<Connection, rusqlite::Error> {
-        Connection::open(&self.db_path)
+        let conn = Connection::open(&self.db_path)?;
+        register_functions(&conn)?;
+        // Concurrent kiosk scans wait for each other's write lock instead of failing
+        conn.busy_timeout(SCAN_BUSY_TIMEOUT)?;
+        Ok(conn)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WebSocketError {
     DatabaseError(String),
+    DuplicateScan { school_id: String, retry_after_seconds: i64 },
     SerializationError(String),
     InvalidMessageFormat(String),
+    // The kiosk's token was revoked while it was connected
+    DeviceRevoked,
 }
 
 #[derive(Clone)]
@@ -50,6 +70,12 @@ pub struct WebSocketState {
     pub sender_tx: mpsc::Sender<(String, AttendanceEvent)>,
     pub connections: Arc<Mutex<HashMap<String, mpsc::Sender<AttendanceEvent>>>>,
     pub recent_attendances: Arc<Mutex<Vec<Attendance>>>,
+    // Last headcount pushed to clients, so unchanged counts are not re-sent
+    pub last_occupancy: Arc<Mutex<Option<Occupancy>>>,
+    // Woken when a kiosk connects or disconnects, so its status is re-checked
+    pub presence_changed: Arc<Notify>,
+    // Open websockets per kiosk; a kiosk that reconnects briefly has two
+    pub device_connections: Arc<Mutex<HashMap<i64, usize>>>,
 }
 
 #[derive(Clone)]
@@ -61,7 +87,9 @@ pub struct AppState {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum AttendanceEvent {
     NewAttendance(CreateAttendanceRequest),
+    CheckOut(Attendance),
     AttendanceList(Vec<Attendance>),
+    Occupancy(Occupancy),
     Error(WebSocketError),
 }
 
@@ -93,7 +121,75 @@ impl WebSocketState {
             sender_tx,
             connections,
             recent_attendances: recent_attendances_clone,
+            last_occupancy: Arc::new(Mutex::new(None)),
+            presence_changed: Arc::new(Notify::new()),
+            device_connections: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Counted before the connecting heartbeat is recorded, so a closing
+    // connection of the same kiosk cannot mark it disconnected after it
+    async fn open_device_connection(&self, device_id: i64) {
+        *self.device_connections.lock().await.entry(device_id).or_insert(0) += 1;
+    }
+
+    // The kiosk is only marked disconnected when its last connection closes. The
+    // lock is held until then so a new connection waits instead of being overwritten.
+    async fn close_device_connection(&self, db_accessor: &DatabaseAccessor, device_id: i64) {
+        let mut device_connections = self.device_connections.lock().await;
+        if let Some(count) = device_connections.get_mut(&device_id) {
+            *count -= 1;
+            if *count > 0 {
+                return;
+            }
+        }
+        device_connections.remove(&device_id);
+        mark_disconnected(db_accessor.clone(), device_id).await;
+    }
+
+    // Recomputes the headcount and pushes it to every client when it changed
+    pub async fn refresh_occupancy(&self, db_accessor: &DatabaseAccessor) {
+        let occupancy = match get_occupancy(db_accessor.clone()).await {
+            Ok(occupancy) => occupancy,
+            Err(_) => return,
+        };
+
+        {
+            let mut last_occupancy = self.last_occupancy.lock().await;
+            if last_occupancy.as_ref().is_some_and(|last| last.same_counts(&occupancy)) {
+                return;
+            }
+            *last_occupancy = Some(occupancy.clone());
+        }
+
+        // No client has an empty id, so nobody is excluded
+        let _ = self.sender_tx.send((String::new(), AttendanceEvent::Occupancy(occupancy))).await;
+    }
+
+    // Closes stale visits and tells clients which ones were closed
+    pub async fn expire_stale_visits(&self, db_accessor: &DatabaseAccessor) {
+        let db_accessor_clone = db_accessor.clone();
+        let expired = tokio::task::spawn_blocking(move || {
+            let conn = db_accessor_clone.get_connection().ok()?;
+            OccupancyDatabase.expire_stale_visits(&conn).ok()
+        })
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+        for attendance in expired {
+            {
+                let mut recent_attendances = self.recent_attendances.lock().await;
+                if let Some(existing) = recent_attendances.iter_mut().find(|a| a.id == attendance.id) {
+                    *existing = attendance.clone();
+                }
+            }
+
+            let _ = self.sender_tx.send((String::new(), AttendanceEvent::CheckOut(attendance))).await;
         }
+
+        self.refresh_occupancy(db_accessor).await;
     }
 }
 
@@ -110,17 +206,41 @@ fn get_last_n_attendances(
         .map_err(|e| WebSocketError::DatabaseError(e.to_string()))
 }
 
-async fn create_attendance(
+async fn get_occupancy(db_accessor: DatabaseAccessor) -> Result<Occupancy, WebSocketError> {
+    tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()
+            .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?;
+
+        OccupancyDatabase.get_occupancy(&conn)
+            .map_err(|e| WebSocketError::DatabaseError(e.to_string()))
+    })
+    .await
+    .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?
+}
+
+async fn scan_attendance(
     db_accessor: DatabaseAccessor,
+    device_id: i64,
     attendance_req: CreateAttendanceRequest,
-) -> Result<Attendance, WebSocketError> {
+) -> Result<ScanOutcome, WebSocketError> {
     let result = tokio::task::spawn_blocking(move || {
         let conn = db_accessor.get_connection()
             .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?;
+
+        // The token was checked once at connect; revocation has to end the socket too.
+        // Loaded fresh so the scan gets the location from the latest heartbeat.
+        let device = KioskDeviceDatabase.get_active_device(&conn, device_id)
+            .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?
+            .ok_or(WebSocketError::DeviceRevoked)?;
         
         let repo = SqliteAttendanceRepository;
-        repo.create_attendance(&conn, attendance_req.clone())
-            .map_err(|e| WebSocketError::DatabaseError(e.to_string()))
+        repo.scan_attendance(&conn, attribute_to_device(attendance_req, &device))
+            .map_err(|e| match e {
+                ScanError::DuplicateScan { school_id, retry_after_seconds } => {
+                    WebSocketError::DuplicateScan { school_id, retry_after_seconds }
+                },
+                ScanError::Database(e) => WebSocketError::DatabaseError(e.to_string()),
+            })
     })
     .await
     .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?;
@@ -128,18 +248,65 @@ async fn create_attendance(
     result
 }
 
+// None when the device was revoked
+async fn record_heartbeat(
+    db_accessor: DatabaseAccessor,
+    device_id: i64,
+    heartbeat: KioskHeartbeat,
+) -> Result<Option<KioskDevice>, WebSocketError> {
+    tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()
+            .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?;
+
+        KioskDeviceDatabase.record_heartbeat(&conn, device_id, &heartbeat)
+            .map_err(|e| match e {
+                rusqlite::Error::InvalidParameterName(message) => WebSocketError::InvalidMessageFormat(message),
+                e => WebSocketError::DatabaseError(e.to_string()),
+            })
+    })
+    .await
+    .map_err(|e| WebSocketError::DatabaseError(e.to_string()))?
+}
+
+async fn mark_disconnected(db_accessor: DatabaseAccessor, device_id: i64) {
+    let result = tokio::task::spawn_blocking(move || {
+        let conn = db_accessor.get_connection()?;
+        KioskDeviceDatabase.mark_disconnected(&conn, device_id)
+    }).await;
+
+    match result {
+        Ok(Ok(())) => {},
+        Ok(Err(e)) => warn!("Failed to mark kiosk {} disconnected: {}", device_id, e),
+        Err(e) => warn!("Failed to mark kiosk {} disconnected: {}", device_id, e),
+    }
+}
+
 #[axum::debug_handler]
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    Extension(device): Extension<KioskDevice>,
 ) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, device))
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState) {
+async fn handle_socket(socket: WebSocket, state: AppState, device: KioskDevice) {
     let (mut sender, mut receiver) = socket.split();
+    let device_id = device.id;
     let client_id = uuid::Uuid::new_v4().to_string();
     let (client_tx, mut client_rx) = mpsc::channel(100);
+    // Kept to answer the scanning kiosk directly, broadcasts skip the sender
+    let reply_tx = client_tx.clone();
+
+    // Connecting counts as the first heartbeat
+    state.ws_state.open_device_connection(device_id).await;
+    match record_heartbeat(state.db_accessor.clone(), device_id, KioskHeartbeat::default()).await {
+        Ok(Some(_)) => state.ws_state.presence_changed.notify_one(),
+        _ => {
+            state.ws_state.close_device_connection(&state.db_accessor, device_id).await;
+            return;
+        }
+    }
     
     {
         let mut connections = state.ws_state.connections.lock().await;
@@ -154,6 +321,12 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
             let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
         }
     }
+
+    // And the current headcount
+    if let Ok(occupancy) = get_occupancy(state.db_accessor.clone()).await {
+        let msg = json!({ "Occupancy": occupancy });
+        let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
+    }
     
     let sender_task = {
         let client_id_clone = client_id.clone();
@@ -164,10 +337,18 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                         let msg = json!({ "NewAttendance": attendance });
                         let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
                     },
+                    AttendanceEvent::CheckOut(attendance) => {
+                        let msg = json!({ "CheckOut": attendance });
+                        let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
+                    },
                     AttendanceEvent::AttendanceList(attendances) => {
                         let msg = json!({ "AttendanceList": attendances });
                         let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
                     },
+                    AttendanceEvent::Occupancy(occupancy) => {
+                        let msg = json!({ "Occupancy": occupancy });
+                        let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
+                    },
                     AttendanceEvent::Error(error) => {
                         let msg = json!({ "Error": error });
                         let _ = sender.send(axum::extract::ws::Message::Text(msg.to_string())).await;
@@ -194,8 +375,27 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                                 match (msg_type, data) {
                                     (Some("NewAttendance"), Some(data)) => {
                                         if let Ok(attendance_req) = serde_json::from_value::<CreateAttendanceRequest>(data.clone()) {
-                                            match create_attendance(db_accessor.clone(), attendance_req.clone()).await {
-                                                Ok(created_attendance) => {
+                                            match scan_attendance(db_accessor.clone(), device_id, attendance_req.clone()).await {
+                                                Ok(ScanOutcome::Merged(_)) => {
+                                                    // Repeat scan folded into the existing record, nothing to broadcast
+                                                },
+                                                Ok(ScanOutcome::CheckedOut(scanned_attendance)) => {
+                                                    // The scan closed an open visit, replace it in recent attendances
+                                                    {
+                                                        let mut recent_attendances = ws_state.recent_attendances.lock().await;
+                                                        if let Some(existing) = recent_attendances.iter_mut()
+                                                            .find(|a| a.id == scanned_attendance.id) {
+                                                            *existing = scanned_attendance.clone();
+                                                        }
+                                                    }
+
+                                                    let _ = ws_state.sender_tx.send((
+                                                        client_id_clone.clone(),
+                                                        AttendanceEvent::CheckOut(scanned_attendance)
+                                                    )).await;
+                                                    ws_state.refresh_occupancy(&db_accessor).await;
+                                                },
+                                                Ok(ScanOutcome::CheckedIn(created_attendance)) => {
                                                     // Update recent attendances
                                                     {
                                                         let mut recent_attendances = ws_state.recent_attendances.lock().await;
@@ -209,16 +409,34 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
                                                         client_id_clone.clone(),
                                                         AttendanceEvent::NewAttendance(attendance_req)
                                                     )).await;
+                                                    ws_state.refresh_occupancy(&db_accessor).await;
+                                                },
+                                                Err(WebSocketError::DeviceRevoked) => {
+                                                    let _ = reply_tx.send(AttendanceEvent::Error(WebSocketError::DeviceRevoked)).await;
+                                                    break;
                                                 },
                                                 Err(e) => {
-                                                    let _ = ws_state.sender_tx.send((
-                                                        client_id_clone.clone(),
-                                                        AttendanceEvent::Error(e)
-                                                    )).await;
+                                                    let _ = reply_tx.send(AttendanceEvent::Error(e)).await;
                                                 }
                                             }
                                         }
                                     },
+                                    (Some("Heartbeat"), data) => {
+                                        let heartbeat = data
+                                            .and_then(|data| serde_json::from_value::<KioskHeartbeat>(data.clone()).ok())
+                                            .unwrap_or_default();
+
+                                        match record_heartbeat(db_accessor.clone(), device_id, heartbeat).await {
+                                            Ok(Some(_)) => {},
+                                            Ok(None) => {
+                                                let _ = reply_tx.send(AttendanceEvent::Error(WebSocketError::DeviceRevoked)).await;
+                                                break;
+                                            },
+                                            Err(e) => {
+                                                let _ = reply_tx.send(AttendanceEvent::Error(e)).await;
+                                            }
+                                        }
+                                    },
                                     _ => {}
                                 }
                             },
@@ -237,8 +455,13 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
         _ = receiver_task => {},
     }
 
-    let mut connections = state.ws_state.connections.lock().await;
-    connections.remove(&client_id);
+    {
+        let mut connections = state.ws_state.connections.lock().await;
+        connections.remove(&client_id);
+    }
+
+    state.ws_state.close_device_connection(&state.db_accessor, device_id).await;
+    state.ws_state.presence_changed.notify_one();
 }
 
 pub fn create_websocket_routes(db_path: PathBuf) -> Router {
@@ -252,5 +475,6 @@ pub fn create_websocket_routes(db_path: PathBuf) -> Router {
     
     Router::new()
         .route("/ws", get(websocket_handler))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), require_device_token))
         .with_state(app_state)
 }
\ No newline at end of file
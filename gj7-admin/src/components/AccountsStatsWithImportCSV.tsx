@@ -8,6 +8,7 @@ import { Loader2 } from 'lucide-react';
 import { SearchModal } from './search-modal';
 import { useToast } from "@/hooks/use-toast"
 import SemesterCard from './SemesterCard';
+import KioskStatusCard from './KioskStatusCard';
 
 const AccountsStatsWithImportCSV: React.FC = () => {
   const [schoolAccounts, setSchoolAccounts] = useState<SchoolAccount[]>([]);
@@ -105,6 +106,10 @@ const AccountsStatsWithImportCSV: React.FC = () => {
                 setIsSearchModalOpen={setIsSearchModalOpen}
               />
               </div>
+
+              <div className='col-span-1 sm:col-span-2'>
+                <KioskStatusCard />
+              </div>
             </div>
             
             <SearchModal
@@ -7,6 +7,8 @@ import { downloadAttendanceTableAsPDF } from '@/utils/pdfUtils'
 import { ToastProvider, ToastViewport } from "@/components/ui/toast"
 import { Button } from '@/components/ui/button'
 import { useToast } from '@/hooks/use-toast'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '../lib/auth'
 import AuthModal from './AuthModal'
 import AttendanceCard from './attendance/AttendanceCard'
 import AttendanceTable from './attendance/AttendanceTable'
@@ -20,23 +22,11 @@ const AttendanceRecords: React.FC = () => {
   const [error, setError] = useState<string | null>(null)
   const [searchQuery, setSearchQuery] = useState('')
   const [searchStatus, setSearchStatus] = useState<string | null>(null)
-  const [isAuthModalOpen, setIsAuthModalOpen] = useState(false)
-  const [authAction, setAuthAction] = useState<'create' | 'delete'>('create')
-  const [credentials, setCredentials] = useState<{ username: string; password: string }>({ username: '', password: '' })
-  const [attendanceToDelete, setAttendanceToDelete] = useState<string | null>(null)
+  const [authAction, setAuthAction] = useState<'update' | 'delete'>('update')
   const [view, setView] = useState<'card' | 'table'>('card')
 
   const { toast } = useToast()
-
-  // Fetch credentials
-  const fetchCredentials = async () => {
-    try {
-      const creds = await AttendanceApi.getCredentials()
-      setCredentials(creds)
-    } catch (err) {
-      console.error('Failed to fetch credentials:', err)
-    }
-  }
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
 
   // Callbacks
   const addToast = useCallback((message: string, level: LogLevel) => {
@@ -81,17 +71,9 @@ const AttendanceRecords: React.FC = () => {
   }
 
   // Handle auth submission
-  const handleAuthSubmit = async (inputCredentials: { username: string; password: string }) => {
+  const handleAuthSubmit = async (inputCredentials: Credentials) => {
     try {
-      if (inputCredentials.username === credentials.username && 
-          inputCredentials.password === credentials.password) {
-        setIsAuthModalOpen(false)
-        if (authAction === 'create') {
-          // Handle create action
-        } else if (authAction === 'delete' && attendanceToDelete) {
-          await deleteAttendance(attendanceToDelete, inputCredentials)
-        }
-      } else {
+      if (!await submitCredentials(inputCredentials)) {
         addToast('Invalid credentials', 'error')
       }
     } catch (err) {
@@ -100,21 +82,24 @@ const AttendanceRecords: React.FC = () => {
   }
 
 
-  // Update attendance
+  // Update attendance after authentication
   const handleUpdateAttendance = async (attendanceId: string, updatedAttendance: UpdateAttendanceRequest) => {
-    try {
-      await AttendanceApi.updateAttendance(attendanceId, updatedAttendance, credentials.username, credentials.password)
-      await fetchAttendances()
-      setError(null)
-      addToast('Attendance record updated successfully', 'success')
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to update attendance')
-      addToast('Failed to update attendance record', 'error')
-    }
+    setAuthAction('update')
+    await withCredentials(async (auth) => {
+      try {
+        await AttendanceApi.updateAttendance(attendanceId, updatedAttendance, auth.username, auth.password)
+        await fetchAttendances()
+        setError(null)
+        addToast('Attendance record updated successfully', 'success')
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Failed to update attendance')
+        addToast('Failed to update attendance record', 'error')
+      }
+    })
   }
 
   // Delete attendance after authentication
-  const deleteAttendance = async (attendanceId: string, auth: { username: string; password: string }) => {
+  const deleteAttendance = async (attendanceId: string, auth: Credentials) => {
     try {
       await AttendanceApi.deleteAttendance(attendanceId, auth.username, auth.password)
       await fetchAttendances()
@@ -123,8 +108,6 @@ const AttendanceRecords: React.FC = () => {
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to delete attendance')
       addToast('Failed to delete attendance record', 'error')
-    } finally {
-      setAttendanceToDelete(null)
     }
   }
 
@@ -148,7 +131,6 @@ const AttendanceRecords: React.FC = () => {
 
   useEffect(() => {
     fetchAttendances()
-    fetchCredentials()
   }, [fetchAttendances])
 
   useEffect(() => {
@@ -197,9 +179,8 @@ const AttendanceRecords: React.FC = () => {
                 attendance={attendance}
                 onUpdate={handleUpdateAttendance}
                 onDelete={(attendanceId) => {
-                  setAttendanceToDelete(attendanceId)
                   setAuthAction('delete')
-                  setIsAuthModalOpen(true)
+                  withCredentials((auth) => deleteAttendance(attendanceId, auth), true)
                 }}
               />
             ))}
@@ -216,9 +197,9 @@ const AttendanceRecords: React.FC = () => {
       <ToastViewport />
       <AuthModal
         isOpen={isAuthModalOpen}
-        onClose={() => setIsAuthModalOpen(false)}
+        onClose={closeAuthModal}
         onSubmit={handleAuthSubmit}
-        action={authAction === 'create' ? 'create a new attendance record' : 'delete the attendance record'}
+        action={authAction === 'update' ? 'update the attendance record' : 'delete the attendance record'}
       />
     </ToastProvider>
   )
@@ -4,6 +4,7 @@ import { Button } from "@/components/ui/button"
 import { Input } from "@/components/ui/input"
 import { Label } from "@/components/ui/label"
 import { Alert, AlertDescription } from "@/components/ui/alert"
+import { useSession } from '@/hooks/useSession'
 
 const AuthModal = ({ 
   isOpen, 
@@ -16,7 +17,9 @@ const AuthModal = ({
   onSubmit: (credentials: { username: string; password: string }) => void;
   action?: string;
 }) => {
-  const [username, setUsername] = useState('');
+  // Actions are confirmed by the signed-in user; the app refuses anyone else's credentials
+  const { user } = useSession();
+  const username = user?.username ?? '';
   const [password, setPassword] = useState('');
   const [error, setError] = useState('');
 
@@ -30,7 +33,6 @@ const AuthModal = ({
     }
 
     onSubmit({ username, password });
-    setUsername('');
     setPassword('');
   };
 
@@ -38,7 +40,6 @@ const AuthModal = ({
     <Dialog open={isOpen} onOpenChange={(open) => {
       if (!open) {
         onClose();
-        setUsername('');
         setPassword('');
         setError('');
       }
@@ -47,7 +48,7 @@ const AuthModal = ({
         <DialogHeader>
           <DialogTitle>Authentication Required</DialogTitle>
           <DialogDescription>
-            Please enter your credentials to {action}
+            Please enter your password to {action}
           </DialogDescription>
         </DialogHeader>
 
@@ -58,8 +59,7 @@ const AuthModal = ({
               id="username"
               type="text"
               value={username}
-              onChange={(e) => setUsername(e.target.value)}
-              placeholder="Enter username"
+              readOnly
             />
           </div>
 
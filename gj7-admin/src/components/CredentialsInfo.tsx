@@ -1,9 +1,5 @@
 import React from 'react';
-
-interface Credentials {
-  username: string;
-  password: string;
-}
+import { User } from '@/lib/auth';
 
 interface DatabaseInfo {
   name: string;
@@ -11,18 +7,18 @@ interface DatabaseInfo {
 }
 
 interface CredentialsInfoProps {
-  credentials: Credentials;
+  account: User;
   dbInfo: DatabaseInfo | null;
 }
 
-const CredentialsInfo: React.FC<CredentialsInfoProps> = ({ credentials, dbInfo }) => {
+const CredentialsInfo: React.FC<CredentialsInfoProps> = ({ account, dbInfo }) => {
   return (
     <div className="p-4 bg-yellow-100">
       <div className="max-w-4xl mx-auto">
         <div className="text-center mb-2">
           <p className="text-gray-700">
-            Test Credentials - Username: <span className="font-mono">{credentials.username}</span>,
-            Password: <span className="font-mono">{credentials.password}</span>
+            Signed in as: <span className="font-mono">{account.username}</span> ({account.role}),
+            Recovery codes left: <span className="font-mono">{account.recovery_codes_remaining}</span>
           </p>
         </div>
         {dbInfo && (
@@ -60,9 +60,6 @@ export const CsvImportComponent: React.FC<CsvImportComponentProps> = ({ onImport
   const [logListener, setLogListener] = useState<UnlistenFn | null>(null);
   const [useParallelImport, setUseParallelImport] = useState(false);
   const [showPinCodeModal, setShowPinCodeModal] = useState(false);
-  const [pinAttempts, setPinAttempts] = useState(3);
-  const [parallelImportLocked, setParallelImportLocked] = useState(false);
-  const [remainingLockTime, setRemainingLockTime] = useState(0);
 
 
   const handleLogMessage = useCallback((message: LogMessage) => {
@@ -250,51 +247,23 @@ export const CsvImportComponent: React.FC<CsvImportComponentProps> = ({ onImport
   const shouldShowCancelButton = (fullFilePath || isFileImported || existingAccountInfo) && !showStatistics;
 
   const handleParallelImportToggle = () => {
-    if (!useParallelImport && !parallelImportLocked) {
+    if (!useParallelImport) {
       setShowPinCodeModal(true);
-    } else if (useParallelImport) {
+    } else {
       setUseParallelImport(false);
     }
   };
 
-
-  const handlePinVerification = (success: boolean) => {
-    if (success) {
-      setUseParallelImport(true);
-      setShowPinCodeModal(false);
-      setPinAttempts(3);
-    } else {
-      setPinAttempts((prev) => {
-        if (prev === 1) {
-          setParallelImportLocked(true);
-          setRemainingLockTime(15 * 60); // 15 minutes in seconds
-          setUseParallelImport(false);
-          setShowPinCodeModal(false);
-          return 3;
-        }
-        return prev - 1;
-      });
-    }
+  const handlePinVerified = () => {
+    setUseParallelImport(true);
+    setShowPinCodeModal(false);
+    toast({
+      title: "Success",
+      description: "Parallel import enabled.",
+      duration: 3000,
+    });
   };
 
-  useEffect(() => {
-    if (parallelImportLocked) {
-      const interval = setInterval(() => {
-        setRemainingLockTime((prev) => {
-          if (prev <= 1) {
-            clearInterval(interval);
-            setParallelImportLocked(false);
-            setPinAttempts(3);
-            return 0;
-          }
-          return prev - 1;
-        });
-      }, 1000);
-
-      return () => clearInterval(interval);
-    }
-  }, [parallelImportLocked]);
-
   return (
     <Card className="w-full max-w-4xl">
       {isShowingImportLoadingState ? (
@@ -374,10 +343,8 @@ export const CsvImportComponent: React.FC<CsvImportComponentProps> = ({ onImport
           <PinCodeModal
               isOpen={showPinCodeModal}
               onClose={() => setShowPinCodeModal(false)}
-              onVerify={handlePinVerification}
-              attemptsRemaining={pinAttempts}
-              locked={parallelImportLocked}
-              remainingTime={remainingLockTime}
+              onVerified={handlePinVerified}
+              description="Please enter your 4-digit PIN to enable parallel import."
             />
 
           {validationResult?.is_valid && showImportSection && !isShowingImportLoadingState && !showStatistics && !showUpdateConfirmation && !showCreateConfirmation && (
@@ -597,7 +564,6 @@ export const CsvImportComponent: React.FC<CsvImportComponentProps> = ({ onImport
                       id="import-method"
                       checked={useParallelImport}
                       onCheckedChange={handleParallelImportToggle}
-                      disabled={parallelImportLocked}
                     />
                     <div className='text-xs absolute bottom-[1px] -left-2'>
                       {useParallelImport ? '(Faster)' : ''} Switch to Pro
@@ -666,7 +632,6 @@ export const CsvImportComponent: React.FC<CsvImportComponentProps> = ({ onImport
                     id="import-method"
                     checked={useParallelImport}
                     onCheckedChange={handleParallelImportToggle}
-                    disabled={parallelImportLocked}
                   />
                   <div className='text-xs absolute bottom-[1px] -left-2'>
                     {useParallelImport ? '(Faster)' : ''} Switch to Pro
@@ -24,11 +24,18 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
 
   // Courses state
   const [availableCourses, setAvailableCourses] = useState<string[]>([]);
+  const [availableLocations, setAvailableLocations] = useState<string[]>([]);
 
   // Filter state
   const [course, setCourse] = useState<string>(initialCourse);
+  const [location, setLocation] = useState<string>("ALL");
   const [date, setDate] = useState<Date | undefined>(initialDate);
 
+  // Pagination state
+  const [page, setPage] = useState<number>(1);
+  const [totalPages, setTotalPages] = useState<number>(0);
+  const [totalCount, setTotalCount] = useState<number>(0);
+
   // Fetch unique courses
   useEffect(() => {
     const fetchCourses = async () => {
@@ -64,17 +71,36 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
     fetchCourses();
   }, [initialCourse]);
 
+  useEffect(() => {
+    AttendanceApi.getAttendanceLocations()
+      .then(setAvailableLocations)
+      .catch((err) => console.error('Failed to fetch locations', err));
+  }, []);
+
   const fetchFilteredAttendances = async () => {
     try {
       setLoading(true);
       setError(null);
       
+      // The selected date covers the whole library day
+      const day = date
+        ? `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, '0')}-${String(date.getDate()).padStart(2, '0')}`
+        : null;
+
       const filteredResults = await AttendanceApi.getFilteredAttendances(
-        course === "ALL" ? undefined : course, 
-        date
+        {
+          course: course === "ALL" ? null : course,
+          location: location === "ALL" ? null : location,
+          from_date: day,
+          to_date: day
+        },
+        undefined,
+        page
       );
       
-      setAttendances(filteredResults);
+      setAttendances(filteredResults.attendances);
+      setTotalPages(filteredResults.total_pages);
+      setTotalCount(filteredResults.total_count);
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
     } finally {
@@ -84,19 +110,28 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
 
   useEffect(() => {
     fetchFilteredAttendances();
-  }, [course, date]);
+  }, [course, location, date, page]);
 
   const handleCourseChange = (selectedCourse: string) => {
     setCourse(selectedCourse);
+    setPage(1);
+  };
+
+  const handleLocationChange = (selectedLocation: string) => {
+    setLocation(selectedLocation);
+    setPage(1);
   };
 
   const handleDateChange = (e: React.ChangeEvent<HTMLInputElement>) => {
-    setDate(e.target.value ? new Date(e.target.value) : undefined);
+    setDate(e.target.value ? new Date(`${e.target.value}T00:00:00`) : undefined);
+    setPage(1);
   };
 
   const handleClearFilters = () => {
     setCourse(availableCourses.length > 0 ? availableCourses[0] : "ALL");
+    setLocation("ALL");
     setDate(undefined);
+    setPage(1);
   };
 
   return (
@@ -116,7 +151,7 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
       <CardContent>
         {/* Filters */}
         <div className="flex space-x-4 mb-4">
-          <div className="w-1/2">
+          <div className="w-1/3">
             <Label htmlFor="course">Course</Label>
             <Select 
               value={course} 
@@ -135,12 +170,31 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
               </SelectContent>
             </Select>
           </div>
-          <div className="w-1/2">
+          <div className="w-1/3">
+            <Label htmlFor="location">Location</Label>
+            <Select 
+              value={location} 
+              onValueChange={handleLocationChange}
+            >
+              <SelectTrigger>
+                <SelectValue placeholder="Select Location" />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="ALL">All Locations</SelectItem>
+                {availableLocations.map((locationName) => (
+                  <SelectItem key={locationName} value={locationName}>
+                    {locationName}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="w-1/3">
             <Label htmlFor="date">Date</Label>
             <Input
               id="date"
               type="date"
-              value={date ? date.toISOString().split('T')[0] : ''}
+              value={date ? `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, '0')}-${String(date.getDate()).padStart(2, '0')}` : ''}
               onChange={handleDateChange}
             />
           </div>
@@ -165,12 +219,13 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
                 <TableHead>Time In</TableHead>
                 <TableHead>Classification</TableHead>
                 <TableHead>Purpose</TableHead>
+                <TableHead>Location</TableHead>
               </TableRow>
             </TableHeader>
             <TableBody>
               {attendances.length === 0 ? (
                 <TableRow>
-                  <TableCell colSpan={5} className="text-center">
+                  <TableCell colSpan={6} className="text-center">
                     No attendances found
                   </TableCell>
                 </TableRow>
@@ -184,12 +239,38 @@ export const FilteredAttendanceTable: React.FC<FilteredAttendanceTableProps> = (
                     </TableCell>
                     <TableCell>{attendance.classification}</TableCell>
                     <TableCell>{attendance.purpose_label || 'N/A'}</TableCell>
+                    <TableCell>{attendance.location || 'N/A'}</TableCell>
                   </TableRow>
                 ))
               )}
             </TableBody>
           </Table>
         )}
+
+        {/* Pagination */}
+        {!loading && !error && totalPages > 1 && (
+          <div className="flex items-center justify-between mt-4">
+            <p className="text-sm text-muted-foreground">
+              Page {page} of {totalPages} ({totalCount} records)
+            </p>
+            <div className="space-x-2">
+              <Button
+                variant="outline"
+                onClick={() => setPage(page - 1)}
+                disabled={page <= 1}
+              >
+                Previous
+              </Button>
+              <Button
+                variant="outline"
+                onClick={() => setPage(page + 1)}
+                disabled={page >= totalPages}
+              >
+                Next
+              </Button>
+            </div>
+          </div>
+        )}
       </CardContent>
     </Card>
   );
@@ -0,0 +1,121 @@
+// components/KioskDevicesSettings.tsx
+
+import React, { useState, useEffect, useCallback } from 'react'
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Button } from "@/components/ui/button"
+import { useSession } from '@/hooks/useSession'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '@/lib/auth'
+import { KioskApi, KioskDevice, PairingCode } from '@/lib/kiosk'
+import AuthModal from './AuthModal'
+
+// Pairs kiosks with the network server and revokes their access
+const KioskDevicesSettings: React.FC = () => {
+  const { can } = useSession()
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
+  const [devices, setDevices] = useState<KioskDevice[]>([])
+  const [pairingCode, setPairingCode] = useState<PairingCode | null>(null)
+
+  const allowed = can('ManageDevices')
+
+  const fetchDevices = useCallback(() => {
+    KioskApi.listDevices()
+      .then(setDevices)
+      .catch((error) => console.error('Failed to fetch kiosk devices', error))
+  }, [])
+
+  useEffect(() => {
+    if (allowed) {
+      fetchDevices()
+    }
+  }, [allowed, fetchDevices])
+
+  const handleCreateCode = () => withCredentials(async (auth) => {
+    try {
+      setPairingCode(await KioskApi.createPairingCode(auth.username, auth.password))
+    } catch (error) {
+      console.error('Failed to create pairing code:', error)
+    }
+  })
+
+  const handleRevoke = (device: KioskDevice) => withCredentials(async (auth) => {
+    try {
+      await KioskApi.revokeDevice(device.id, auth.username, auth.password)
+      fetchDevices()
+    } catch (error) {
+      console.error('Failed to revoke kiosk:', error)
+    }
+  })
+
+  const handleAuthSubmit = async (credentials: Credentials) => {
+    if (!await submitCredentials(credentials)) {
+      console.error('Authentication failed')
+    }
+  }
+
+  if (!allowed) {
+    return null
+  }
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Kiosks</CardTitle>
+        <CardDescription>
+          Only paired kiosks can record attendance over the network.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="space-y-2">
+          <Button onClick={handleCreateCode}>Generate pairing code</Button>
+          {pairingCode && (
+            <div className="text-sm">
+              <div className="font-mono text-2xl tracking-widest">{pairingCode.code}</div>
+              <div className="text-muted-foreground">
+                Enter this on the kiosk before {new Date(pairingCode.expires_at).toLocaleTimeString()}. It works once.
+              </div>
+            </div>
+          )}
+        </div>
+
+        <div className="space-y-2">
+          {devices.length === 0 && (
+            <div className="text-sm text-muted-foreground">No kiosks paired yet.</div>
+          )}
+          {devices.map((device) => (
+            <div key={device.id} className="flex items-center justify-between text-sm">
+              <div>
+                <div className="font-medium">
+                  {device.name}
+                  {!device.revoked_at && (
+                    <span className={`ml-2 ${device.is_online ? 'text-green-600' : 'text-muted-foreground'}`}>
+                      {device.is_online ? 'Online' : 'Offline'}
+                    </span>
+                  )}
+                </div>
+                <div className="text-muted-foreground">
+                  {device.revoked_at
+                    ? `Revoked ${new Date(device.revoked_at).toLocaleString()}`
+                    : `Paired ${new Date(device.paired_at).toLocaleString()} by ${device.paired_by}`}
+                </div>
+              </div>
+              {!device.revoked_at && (
+                <Button variant="outline" size="sm" onClick={() => handleRevoke(device)}>
+                  Revoke
+                </Button>
+              )}
+            </div>
+          ))}
+        </div>
+      </CardContent>
+      <AuthModal
+        isOpen={isAuthModalOpen}
+        onClose={closeAuthModal}
+        onSubmit={handleAuthSubmit}
+        action="manage kiosks"
+      />
+    </Card>
+  )
+}
+
+export default KioskDevicesSettings
@@ -0,0 +1,65 @@
+// components/KioskStatusCard.tsx
+
+import React, { useState, useEffect } from 'react'
+import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card"
+import { KioskApi, KioskDevice } from '@/lib/kiosk'
+
+// Which paired kiosks are connected right now
+const KioskStatusCard: React.FC = () => {
+  const [devices, setDevices] = useState<KioskDevice[]>([])
+
+  useEffect(() => {
+    KioskApi.listDevices()
+      .then((all) => setDevices(all.filter((device) => !device.revoked_at)))
+      .catch((error) => console.error('Failed to fetch kiosk devices', error))
+
+    const unlisten = KioskApi.onStatusChanged((changed) => {
+      setDevices((current) => {
+        const others = current.filter((device) => device.id !== changed.id)
+        return changed.revoked_at ? others : [...others, changed].sort((a, b) => a.name.localeCompare(b.name))
+      })
+    })
+    return () => {
+      unlisten.then((stop) => stop())
+    }
+  }, [])
+
+  const onlineCount = devices.filter((device) => device.is_online).length
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>
+          Kiosks <span className="font-light">{onlineCount}/{devices.length} online</span>
+        </CardTitle>
+      </CardHeader>
+      <CardContent className="space-y-2">
+        {devices.length === 0 && (
+          <div className="text-sm text-muted-foreground">No kiosks paired yet.</div>
+        )}
+        {devices.map((device) => (
+          <div key={device.id} className="flex items-center justify-between text-sm">
+            <div>
+              <div className="font-medium">{device.name}</div>
+              <div className="text-muted-foreground">
+                {[device.location, device.app_version && `v${device.app_version}`].filter(Boolean).join(' · ') || 'No details reported'}
+              </div>
+            </div>
+            <div className="text-right">
+              <div className={device.is_online ? 'text-green-600' : 'text-red-600'}>
+                {device.is_online ? 'Online' : 'Offline'}
+              </div>
+              <div className="text-muted-foreground">
+                {device.last_heartbeat_at
+                  ? `Last seen ${new Date(device.last_heartbeat_at).toLocaleString()}`
+                  : 'Never connected'}
+              </div>
+            </div>
+          </div>
+        ))}
+      </CardContent>
+    </Card>
+  )
+}
+
+export default KioskStatusCard
@@ -0,0 +1,130 @@
+import React, { useState } from 'react';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { Alert, AlertDescription } from "@/components/ui/alert"
+import { useSession } from '@/hooks/useSession';
+import PinCodeModal from './PinCodeModal';
+
+// Shows the sign-in form until a user is signed in, and the lock screen while
+// the session is locked; otherwise the app itself
+const LoginGate: React.FC<{ children: React.ReactNode }> = ({ children }) => {
+  const { user, isLoading, isLocked, login, logout, markUnlocked } = useSession();
+  const [isPinModalOpen, setIsPinModalOpen] = useState(false);
+  const [username, setUsername] = useState('');
+  const [password, setPassword] = useState('');
+  const [error, setError] = useState('');
+  const [isSubmitting, setIsSubmitting] = useState(false);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setError('');
+
+    if (!username || !password) {
+      setError('Please fill in all fields');
+      return;
+    }
+
+    setIsSubmitting(true);
+    try {
+      await login(username, password);
+      setPassword('');
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsSubmitting(false);
+    }
+  };
+
+  if (isLoading) {
+    return null;
+  }
+
+  if (user && isLocked) {
+    return (
+      <div className="flex h-full items-center justify-center p-6">
+        <Card className="w-full max-w-md">
+          <CardHeader>
+            <CardTitle>Locked</CardTitle>
+            <CardDescription>
+              The app was locked for {user.username}. Enter your PIN to continue.
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="flex space-x-2">
+            <Button className="flex-1" onClick={() => setIsPinModalOpen(true)}>
+              Unlock
+            </Button>
+            <Button variant="outline" className="flex-1" onClick={logout}>
+              Sign Out
+            </Button>
+          </CardContent>
+        </Card>
+        <PinCodeModal
+          isOpen={isPinModalOpen}
+          onClose={() => setIsPinModalOpen(false)}
+          onVerified={() => {
+            setIsPinModalOpen(false);
+            markUnlocked();
+          }}
+          description="Please enter your 4-digit PIN to unlock the app."
+        />
+      </div>
+    );
+  }
+
+  if (user) {
+    return <>{children}</>;
+  }
+
+  return (
+    <div className="flex h-full items-center justify-center p-6">
+      <Card className="w-full max-w-md">
+        <CardHeader>
+          <CardTitle>Sign In</CardTitle>
+          <CardDescription>
+            Sign in with your library staff account
+          </CardDescription>
+        </CardHeader>
+        <CardContent>
+          <form onSubmit={handleSubmit} className="space-y-4">
+            <div className="space-y-2">
+              <Label htmlFor="login-username">Username</Label>
+              <Input
+                id="login-username"
+                type="text"
+                value={username}
+                onChange={(e) => setUsername(e.target.value)}
+                placeholder="Enter username"
+                autoFocus
+              />
+            </div>
+
+            <div className="space-y-2">
+              <Label htmlFor="login-password">Password</Label>
+              <Input
+                id="login-password"
+                type="password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                placeholder="Enter password"
+              />
+            </div>
+
+            {error && (
+              <Alert variant="destructive">
+                <AlertDescription>{error}</AlertDescription>
+              </Alert>
+            )}
+
+            <Button type="submit" className="w-full" disabled={isSubmitting}>
+              {isSubmitting ? 'Signing in...' : 'Sign In'}
+            </Button>
+          </form>
+        </CardContent>
+      </Card>
+    </div>
+  );
+};
+
+export default LoginGate;
@@ -2,14 +2,16 @@
 
 import React, { useState } from 'react'
 import { Link } from 'react-router-dom'
-import { Menu, X } from 'lucide-react'
+import { Lock, LogOut, Menu, X } from 'lucide-react'
 import NetworkStatus from './NetworkStatus'
 import { useNavbarSettings } from '@/hooks/useNavbarSettings'
+import { useSession } from '@/hooks/useSession'
 
 const Navbar: React.FC = () => {
   const [isMenuOpen, setIsMenuOpen] = useState(false)
   
   const { navbarColor, brandLabel } = useNavbarSettings()
+  const { user, isLocked, logout, lock } = useSession()
 
   const toggleMenu = () => setIsMenuOpen(!isMenuOpen)
 
@@ -55,6 +57,25 @@ const Navbar: React.FC = () => {
             <div className="hidden md:block">
               <NetworkStatus />
             </div>
+            {user && user.has_pin && !isLocked && (
+              <button
+                onClick={lock}
+                title="Lock"
+                className="ml-4 inline-flex items-center text-gray-300 hover:text-white"
+              >
+                <Lock size={18} />
+              </button>
+            )}
+            {user && (
+              <button
+                onClick={logout}
+                title="Sign out"
+                className="ml-4 inline-flex items-center text-sm text-gray-300 hover:text-white"
+              >
+                <span className="hidden sm:inline mr-2">{user.username} ({user.role})</span>
+                <LogOut size={18} />
+              </button>
+            )}
             <div className="md:hidden">
               <button onClick={toggleMenu} className="text-gray-100">
                 {isMenuOpen ? <X size={24} /> : <Menu size={24} />}
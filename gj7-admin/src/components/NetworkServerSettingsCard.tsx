@@ -0,0 +1,134 @@
+// components/NetworkServerSettingsCard.tsx
+
+import React, { useState, useEffect } from 'react'
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { useSession } from '@/hooks/useSession'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '@/lib/auth'
+import { KioskApi, NetworkServerSettings } from '@/lib/kiosk'
+import AuthModal from './AuthModal'
+
+// Address, port and HTTPS for the server kiosks connect to
+const NetworkServerSettingsCard: React.FC = () => {
+  const { can } = useSession()
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
+  const [bindAddress, setBindAddress] = useState('')
+  const [port, setPort] = useState('')
+  const [tlsEnabled, setTlsEnabled] = useState(false)
+  const [fingerprint, setFingerprint] = useState<string | null>(null)
+  const [serverError, setServerError] = useState<string | null>(null)
+
+  const applySettings = (settings: NetworkServerSettings) => {
+    setBindAddress(settings.bind_address)
+    setPort(String(settings.port))
+    setTlsEnabled(settings.tls_enabled)
+  }
+
+  useEffect(() => {
+    KioskApi.getNetworkSettings()
+      .then(applySettings)
+      .catch((error) => console.error('Failed to fetch network server settings', error))
+
+    const unlisten = KioskApi.onServerError(setServerError)
+    return () => {
+      unlisten.then((stop) => stop())
+    }
+  }, [])
+
+  useEffect(() => {
+    if (!tlsEnabled) {
+      return
+    }
+    KioskApi.getCertificateFingerprint()
+      .then(setFingerprint)
+      .catch((error) => console.error('Failed to fetch certificate fingerprint', error))
+  }, [tlsEnabled])
+
+  const handleSave = () => withCredentials(async (auth) => {
+    try {
+      const saved = await KioskApi.setNetworkSettings(
+        { bind_address: bindAddress.trim(), port: Number(port), tls_enabled: tlsEnabled },
+        auth.username,
+        auth.password
+      )
+      applySettings(saved)
+      setServerError(null)
+    } catch (error) {
+      console.error('Failed to update network server settings:', error)
+    }
+  })
+
+  const handleAuthSubmit = async (credentials: Credentials) => {
+    if (!await submitCredentials(credentials)) {
+      console.error('Authentication failed')
+    }
+  }
+
+  if (!can('ManageSettings')) {
+    return null
+  }
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Network Server</CardTitle>
+        <CardDescription>
+          Saving restarts the server; kiosks reconnect once they are set to the new port.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="space-y-2">
+          <Label htmlFor="bind-address">Bind address (0.0.0.0 = all interfaces)</Label>
+          <Input
+            id="bind-address"
+            value={bindAddress}
+            onChange={(e) => setBindAddress(e.target.value)}
+          />
+        </div>
+        <div className="space-y-2">
+          <Label htmlFor="server-port">Port</Label>
+          <Input
+            id="server-port"
+            type="number"
+            min={1}
+            max={65535}
+            value={port}
+            onChange={(e) => setPort(e.target.value)}
+          />
+        </div>
+        <div className="flex items-center space-x-2">
+          <input
+            id="tls-enabled"
+            type="checkbox"
+            checked={tlsEnabled}
+            onChange={(e) => setTlsEnabled(e.target.checked)}
+          />
+          <Label htmlFor="tls-enabled">Use HTTPS/WSS</Label>
+        </div>
+        {tlsEnabled && fingerprint && (
+          <div className="text-sm">
+            <div className="text-muted-foreground">Server key fingerprint (SHA-256)</div>
+            <div className="font-mono break-all">{fingerprint}</div>
+          </div>
+        )}
+        {serverError && (
+          <div className="text-sm text-red-600">{serverError}</div>
+        )}
+        <Button onClick={handleSave} disabled={bindAddress.trim() === '' || port === ''}>
+          Save
+        </Button>
+      </CardContent>
+      <AuthModal
+        isOpen={isAuthModalOpen}
+        onClose={closeAuthModal}
+        onSubmit={handleAuthSubmit}
+        action="change network server settings"
+      />
+    </Card>
+  )
+}
+
+export default NetworkServerSettingsCard
@@ -6,6 +6,8 @@ import { NotesApi, NoteWithDates, CreateNoteRequest, UpdateNoteRequest } from '.
 import { logger, LogLevel } from '../lib/logger'
 import { ToastProvider, ToastViewport } from "@/components/ui/toast"
 import { useToast } from '@/hooks/use-toast'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '../lib/auth'
 import AuthModal from './AuthModal'
 import NoteCard from './notes/NoteCard'
 import SearchBar from './SearchBar'
@@ -18,22 +20,10 @@ const Notes: React.FC = () => {
   const [error, setError] = useState<string | null>(null)
   const [searchQuery, setSearchQuery] = useState('')
   const [searchStatus, setSearchStatus] = useState<string | null>(null)
-  const [isAuthModalOpen, setIsAuthModalOpen] = useState(false)
-  const [authAction, setAuthAction] = useState<'create' | 'delete'>('create')
-  const [credentials, setCredentials] = useState<{ username: string; password: string }>({ username: '', password: '' })
-  const [noteToDelete, setNoteToDelete] = useState<number | null>(null)
+  const [authAction, setAuthAction] = useState<'create' | 'update' | 'delete'>('create')
 
   const { toast } = useToast()
-
-  // Fetch credentials
-  const fetchCredentials = async () => {
-    try {
-      const creds = await NotesApi.getCredentials()
-      setCredentials(creds)
-    } catch (err) {
-      console.error('Failed to fetch credentials:', err)
-    }
-  }
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
 
   // Callbacks
   const addToast = useCallback((message: string, level: LogLevel) => {
@@ -86,17 +76,9 @@ const Notes: React.FC = () => {
   }
 
   // Handle auth submission
-  const handleAuthSubmit = async (inputCredentials: { username: string; password: string }) => {
+  const handleAuthSubmit = async (inputCredentials: Credentials) => {
     try {
-      if (inputCredentials.username === credentials.username && 
-          inputCredentials.password === credentials.password) {
-        setIsAuthModalOpen(false)
-        if (authAction === 'create') {
-          // Handle create action
-        } else if (authAction === 'delete' && noteToDelete) {
-          await deleteNote(noteToDelete, inputCredentials)
-        }
-      } else {
+      if (!await submitCredentials(inputCredentials)) {
         addToast('Invalid credentials', 'error')
       }
     } catch (err) {
@@ -106,32 +88,38 @@ const Notes: React.FC = () => {
 
   // Create note after authentication
   const createNote = async (newNote: CreateNoteRequest) => {
-    try {
-      await NotesApi.createNote(newNote, credentials.username, credentials.password)
-      await fetchNotes()
-      setError(null)
-      addToast('Note created successfully', 'success')
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to create note')
-      addToast('Failed to create note', 'error')
-    }
+    setAuthAction('create')
+    await withCredentials(async (auth) => {
+      try {
+        await NotesApi.createNote(newNote, auth.username, auth.password)
+        await fetchNotes()
+        setError(null)
+        addToast('Note created successfully', 'success')
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Failed to create note')
+        addToast('Failed to create note', 'error')
+      }
+    })
   }
 
-  // Update note
+  // Update note after authentication
   const handleUpdateNote = async (noteId: number, updatedNote: UpdateNoteRequest) => {
-    try {
-      await NotesApi.updateNote(noteId, updatedNote, credentials.username, credentials.password)
-      await fetchNotes()
-      setError(null)
-      addToast('Note updated successfully', 'success')
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to update note')
-      addToast('Failed to update note', 'error')
-    }
+    setAuthAction('update')
+    await withCredentials(async (auth) => {
+      try {
+        await NotesApi.updateNote(noteId, updatedNote, auth.username, auth.password)
+        await fetchNotes()
+        setError(null)
+        addToast('Note updated successfully', 'success')
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Failed to update note')
+        addToast('Failed to update note', 'error')
+      }
+    })
   }
 
   // Delete note after authentication
-  const deleteNote = async (noteId: number, auth: { username: string; password: string }) => {
+  const deleteNote = async (noteId: number, auth: Credentials) => {
     try {
       await NotesApi.deleteNote(noteId, auth.username, auth.password)
       await fetchNotes()
@@ -140,8 +128,6 @@ const Notes: React.FC = () => {
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to delete note')
       addToast('Failed to delete note', 'error')
-    } finally {
-      setNoteToDelete(null)
     }
   }
 
@@ -157,7 +143,6 @@ const Notes: React.FC = () => {
 
   useEffect(() => {
     fetchNotes()
-    fetchCredentials()
   }, [fetchNotes])
 
   useEffect(() => {
@@ -202,9 +187,8 @@ return <div className="p-4">Loading...</div>
               note={note}
               onUpdate={handleUpdateNote}
               onDelete={(noteId) => {
-                setNoteToDelete(noteId)
                 setAuthAction('delete')
-                setIsAuthModalOpen(true)
+                withCredentials((auth) => deleteNote(noteId, auth), true)
               }}
             />
           ))}
@@ -213,9 +197,9 @@ return <div className="p-4">Loading...</div>
       <ToastViewport />
       <AuthModal
         isOpen={isAuthModalOpen}
-        onClose={() => setIsAuthModalOpen(false)}
+        onClose={closeAuthModal}
         onSubmit={handleAuthSubmit}
-        action={authAction === 'create' ? 'create a new note' : 'delete the note'}
+        action={authAction === 'create' ? 'create a new note' : authAction === 'update' ? 'update the note' : 'delete the note'}
       />
     </ToastProvider>
   )
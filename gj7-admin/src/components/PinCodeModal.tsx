@@ -5,44 +5,94 @@ import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogFooter } from '
 import { Button } from '@/components/ui/button';
 import { InputOTP, InputOTPGroup, InputOTPSlot } from '@/components/ui/input-otp';
 import { toast } from '@/hooks/use-toast';
+import { AuthApi, PinCheck } from '@/lib/auth';
 
 interface PinCodeModalProps {
   isOpen: boolean;
   onClose: () => void;
-  onVerify: (success: boolean) => void;
-  attemptsRemaining: number;
-  locked: boolean;
-  remainingTime: number;
+  onVerified: () => void;
+  description?: string;
 }
 
-const PinCodeModal: React.FC<PinCodeModalProps> = ({ 
-  isOpen, 
-  onClose, 
-  onVerify, 
-  attemptsRemaining,
-  locked,
-  remainingTime
+// Seconds left in a lockout, or 0 when none is active
+const secondsUntil = (lockedUntil: string | null) =>
+  lockedUntil ? Math.max(0, Math.ceil((new Date(lockedUntil).getTime() - Date.now()) / 1000)) : 0;
+
+const PinCodeModal: React.FC<PinCodeModalProps> = ({
+  isOpen,
+  onClose,
+  onVerified,
+  description = 'Please enter your 4-digit PIN.'
 }) => {
   const [pinCode, setPinCode] = useState('');
+  const [pinStatus, setPinStatus] = useState<PinCheck | null>(null);
+  const [remainingTime, setRemainingTime] = useState(0);
+  const [isVerifying, setIsVerifying] = useState(false);
   const verifyButtonRef = useRef<HTMLButtonElement>(null);
 
-  const handlePinSubmit = () => {
-    if (pinCode === '1234') {
-      onVerify(true);
-      toast({
-        title: "Success",
-        description: "Parallel import enabled.",
+  const locked = remainingTime > 0;
+
+  // Picks up a lockout left over from earlier attempts
+  useEffect(() => {
+    if (!isOpen) {
+      return;
+    }
+
+    setPinCode('');
+    AuthApi.getPinStatus()
+      .then(setPinStatus)
+      .catch((error) => toast({
+        title: "Error",
+        description: String(error),
+        variant: "destructive",
         duration: 3000,
-      });
-    } else {
+      }));
+  }, [isOpen]);
+
+  useEffect(() => {
+    const lockedUntil = pinStatus?.locked_until ?? null;
+    setRemainingTime(secondsUntil(lockedUntil));
+    if (!lockedUntil) {
+      return;
+    }
+
+    const interval = setInterval(() => {
+      const seconds = secondsUntil(lockedUntil);
+      setRemainingTime(seconds);
+      if (seconds === 0) {
+        clearInterval(interval);
+      }
+    }, 1000);
+
+    return () => clearInterval(interval);
+  }, [pinStatus]);
+
+  const handlePinSubmit = async () => {
+    setIsVerifying(true);
+    try {
+      const check = await AuthApi.verifyPin(pinCode);
+      setPinStatus(check);
+      setPinCode('');
+      if (check.verified) {
+        onVerified();
+      } else if (!check.locked_until) {
+        toast({
+          title: "Error",
+          description: `Incorrect PIN. ${check.attempts_remaining} attempts remaining.`,
+          variant: "destructive",
+          duration: 3000,
+        });
+      }
+    } catch (error) {
       setPinCode('');
-      onVerify(false);
       toast({
         title: "Error",
-        description: `Incorrect PIN. ${attemptsRemaining - 1} attempts remaining.`,
+        description: String(error),
         variant: "destructive",
         duration: 3000,
       });
+    } finally {
+      setIsVerifying(false);
     }
   };
 
@@ -50,18 +100,20 @@ const PinCodeModal: React.FC<PinCodeModalProps> = ({
     const handleKeyDown = (event: KeyboardEvent) => {
       if (event.key === 'Enter') {
         event.preventDefault();
-        if (!locked && pinCode.length === 4) {
+        if (!locked && !isVerifying && pinCode.length === 4) {
           handlePinSubmit();
         }
       }
     };
 
-    document.addEventListener('keydown', handleKeyDown);
+    if (isOpen) {
+      document.addEventListener('keydown', handleKeyDown);
+    }
 
     return () => {
       document.removeEventListener('keydown', handleKeyDown);
     };
-  }, [pinCode, locked]);
+  }, [isOpen, pinCode, locked, isVerifying]);
 
   return (
     <Dialog open={isOpen} onOpenChange={onClose}>
@@ -69,7 +121,7 @@ const PinCodeModal: React.FC<PinCodeModalProps> = ({
         <DialogHeader className="text-left">
           <DialogTitle className="text-2xl font-semibold">Enter Pincode</DialogTitle>
           <p className="text-sm text-muted-foreground mt-2">
-            Please enter the 4-digit security pincode (contact admin).
+            {description}
           </p>
         </DialogHeader>
         <div className="my-6">
@@ -81,10 +133,10 @@ const PinCodeModal: React.FC<PinCodeModalProps> = ({
           >
             <InputOTPGroup className='space-x-2'>
               {[0, 1, 2, 3].map((index) => (
-                <InputOTPSlot 
+                <InputOTPSlot
                   key={index}
-                  index={index} 
-                  className="w-14 h-14 text-xl border-2 border-blue-500 focus:border-blue-600 rounded-md" 
+                  index={index}
+                  className="w-14 h-14 text-xl border-2 border-blue-500 focus:border-blue-600 rounded-md"
                 />
               ))}
             </InputOTPGroup>
@@ -92,14 +144,14 @@ const PinCodeModal: React.FC<PinCodeModalProps> = ({
         </div>
         {locked && (
           <div className="text-red-500 text-sm text-center mb-4">
-            Parallel import locked. Try again in {Math.floor(remainingTime / 60)}:{(remainingTime % 60).toString().padStart(2, '0')}
+            Too many incorrect PINs. Try again in {Math.floor(remainingTime / 60)}:{(remainingTime % 60).toString().padStart(2, '0')}
           </div>
         )}
         <DialogFooter className="w-full">
-          <Button 
+          <Button
             ref={verifyButtonRef}
-            onClick={handlePinSubmit} 
-            disabled={pinCode.length !== 4 || locked}
+            onClick={handlePinSubmit}
+            disabled={pinCode.length !== 4 || locked || isVerifying}
             className="w-full bg-blue-600 hover:bg-blue-700 text-white py-6"
           >
             Verify
@@ -111,4 +163,3 @@ const PinCodeModal: React.FC<PinCodeModalProps> = ({
 };
 
 export default PinCodeModal;
-
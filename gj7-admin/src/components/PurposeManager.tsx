@@ -3,6 +3,9 @@ import { AttendanceApi, CreateAttendanceRequest } from '../lib/attendance'
 import { logger, LogLevel } from '../lib/logger'
 import { ToastProvider, ToastViewport } from "@/components/ui/toast"
 import { useToast } from '@/hooks/use-toast'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '../lib/auth'
+import AuthModal from './AuthModal'
 import CreateAttendanceForm from './attendance/CreateAttendanceForm'
 import SchoolAccountsByCourse from './SchoolAccountsByCourse'
 
@@ -10,6 +13,7 @@ const PurposeManager: React.FC = () => {
   // States
   const [error, setError] = useState<string | null>(null)
   const { toast } = useToast()
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
 
   // Callbacks
   const addToast = useCallback((message: string, level: LogLevel) => {
@@ -20,27 +24,38 @@ const PurposeManager: React.FC = () => {
     })
   }, [toast])
 
-  // Create attendance
-  const createAttendance = async (
-    newAttendance: CreateAttendanceRequest
-  ) => {
+  // Handle auth submission
+  const handleAuthSubmit = async (inputCredentials: Credentials) => {
     try {
-      // Fetch credentials first
-      const { username, password } = await AttendanceApi.getCredentials()
-      await AttendanceApi.createAttendance(newAttendance, username, password)
-      setError(null)
-      addToast('Attendance record created successfully', 'success')
+      if (!await submitCredentials(inputCredentials)) {
+        addToast('Invalid credentials', 'error')
+      }
     } catch (err) {
-      // Ensure we capture the exact error message
-      const errorMessage = err instanceof Error
-        ? err.message
-        : String(err)
-      // Set the specific error message
-      setError(errorMessage)
-      addToast(errorMessage, 'error')
+      addToast('Authentication failed', 'error')
     }
   }
 
+  // Create attendance after authentication
+  const createAttendance = async (
+    newAttendance: CreateAttendanceRequest
+  ) => {
+    await withCredentials(async ({ username, password }) => {
+      try {
+        await AttendanceApi.createAttendance(newAttendance, username, password)
+        setError(null)
+        addToast('Attendance record created successfully', 'success')
+      } catch (err) {
+        // Ensure we capture the exact error message
+        const errorMessage = err instanceof Error
+          ? err.message
+          : String(err)
+        // Set the specific error message
+        setError(errorMessage)
+        addToast(errorMessage, 'error')
+      }
+    })
+  }
+
   // Effects
   useEffect(() => {
     const handleLog = (log: { message: string; level: LogLevel }) => {
@@ -62,6 +77,12 @@ const PurposeManager: React.FC = () => {
         <CreateAttendanceForm onCreateAttendance={createAttendance} />
       </div>
       <ToastViewport />
+      <AuthModal
+        isOpen={isAuthModalOpen}
+        onClose={closeAuthModal}
+        onSubmit={handleAuthSubmit}
+        action="create a new attendance record"
+      />
     </ToastProvider>
   )
 }
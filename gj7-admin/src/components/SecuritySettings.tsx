@@ -0,0 +1,130 @@
+// components/SecuritySettings.tsx
+
+import React, { useState, useEffect } from 'react'
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Label } from "@/components/ui/label"
+import { useSession } from '@/hooks/useSession'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { AuthApi, Credentials } from '@/lib/auth'
+import AuthModal from './AuthModal'
+
+// The signed-in user's PIN, and for librarians the idle lock timeout
+const SecuritySettings: React.FC = () => {
+  const { user, can, refreshUser } = useSession()
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
+  const [pin, setPin] = useState('')
+  const [idleMinutes, setIdleMinutes] = useState('')
+
+  useEffect(() => {
+    AuthApi.getIdleLockSettings()
+      .then((settings) => setIdleMinutes(String(settings.idle_minutes)))
+      .catch((error) => console.error('Failed to fetch idle lock settings', error))
+  }, [])
+
+  const handleSetPin = () => withCredentials(async (auth) => {
+    try {
+      await AuthApi.setPin(pin, auth.username, auth.password)
+      setPin('')
+      await refreshUser()
+    } catch (error) {
+      console.error('Failed to set PIN:', error)
+    }
+  }, true)
+
+  const handleClearPin = () => withCredentials(async (auth) => {
+    try {
+      await AuthApi.clearPin(auth.username, auth.password)
+      await refreshUser()
+    } catch (error) {
+      console.error('Failed to remove PIN:', error)
+    }
+  }, true)
+
+  const handleSaveIdleLock = () => withCredentials(async (auth) => {
+    try {
+      const saved = await AuthApi.setIdleLockSettings(
+        { idle_minutes: Number(idleMinutes) },
+        auth.username,
+        auth.password
+      )
+      setIdleMinutes(String(saved.idle_minutes))
+    } catch (error) {
+      console.error('Failed to update idle lock:', error)
+    }
+  })
+
+  const handleAuthSubmit = async (credentials: Credentials) => {
+    if (!await submitCredentials(credentials)) {
+      console.error('Authentication failed')
+    }
+  }
+
+  if (!user) {
+    return null
+  }
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Security</CardTitle>
+        <CardDescription>
+          {user.has_pin
+            ? 'Your PIN unlocks the app after it locks.'
+            : 'Without a PIN you are signed out when the app locks.'}
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="space-y-2">
+          <Label htmlFor="new-pin">{user.has_pin ? 'Change PIN' : 'Set PIN'}</Label>
+          <div className="flex space-x-2">
+            <Input
+              id="new-pin"
+              type="password"
+              inputMode="numeric"
+              maxLength={4}
+              value={pin}
+              onChange={(e) => setPin(e.target.value.replace(/\D/g, ''))}
+              placeholder="4 digits"
+            />
+            <Button onClick={handleSetPin} disabled={pin.length !== 4}>
+              Save
+            </Button>
+          </div>
+          {user.has_pin && (
+            <Button variant="outline" size="sm" onClick={handleClearPin}>
+              Remove PIN
+            </Button>
+          )}
+        </div>
+
+        {can('ManageSettings') && (
+          <div className="space-y-2">
+            <Label htmlFor="idle-minutes">Lock after idle minutes (0 = never)</Label>
+            <div className="flex space-x-2">
+              <Input
+                id="idle-minutes"
+                type="number"
+                min={0}
+                value={idleMinutes}
+                onChange={(e) => setIdleMinutes(e.target.value)}
+              />
+              <Button onClick={handleSaveIdleLock} disabled={idleMinutes === ''}>
+                Save
+              </Button>
+            </div>
+          </div>
+        )}
+      </CardContent>
+      <AuthModal
+        isOpen={isAuthModalOpen}
+        onClose={closeAuthModal}
+        onSubmit={handleAuthSubmit}
+        action="change security settings"
+      />
+    </Card>
+  )
+}
+
+export default SecuritySettings
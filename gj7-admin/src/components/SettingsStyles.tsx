@@ -5,10 +5,15 @@ import { SettingsStylesApi, SettingsStyleWithDates, CreateSettingsStyleRequest }
 import { logger, LogLevel } from '../lib/logger'
 import { ToastProvider, ToastViewport } from "@/components/ui/toast"
 import { useToast } from '@/hooks/use-toast'
+import { useAdminCredentials } from '@/hooks/use-admin-credentials'
+import { Credentials } from '../lib/auth'
 import AuthModal from './AuthModal'
 import SettingsStylesCard from './settings_styles/SettingsStylesCard'
 import SearchBar from './SearchBar'
 import CreateSettingsStylesForm from './settings_styles/CreateSettingsStylesForm'
+import SecuritySettings from './SecuritySettings'
+import KioskDevicesSettings from './KioskDevicesSettings'
+import NetworkServerSettingsCard from './NetworkServerSettingsCard'
 
 const SettingsStyles: React.FC = () => {
   // States
@@ -17,22 +22,9 @@ const SettingsStyles: React.FC = () => {
   const [error, setError] = useState<string | null>(null)
   const [searchQuery, setSearchQuery] = useState('')
   const [searchStatus, setSearchStatus] = useState<string | null>(null)
-  const [isAuthModalOpen, setIsAuthModalOpen] = useState(false)
-  const [authAction] = useState<'create' | 'delete'>('create');
-  const [credentials, setCredentials] = useState<{ username: string; password: string }>({ username: '', password: '' })
-  const [styleToDelete, setStyleToDelete] = useState<number | null>(null)
 
   const { toast } = useToast()
-
-  // Fetch credentials
-  const fetchCredentials = async () => {
-    try {
-      const creds = await SettingsStylesApi.getCredentials()
-      setCredentials(creds)
-    } catch (err) {
-      console.error('Failed to fetch credentials:', err)
-    }
-  }
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials()
 
   // Callbacks
   const addToast = useCallback((message: string, level: LogLevel) => {
@@ -85,17 +77,9 @@ const SettingsStyles: React.FC = () => {
   }
 
   // Handle auth submission
-  const handleAuthSubmit = async (inputCredentials: { username: string; password: string }) => {
+  const handleAuthSubmit = async (inputCredentials: Credentials) => {
     try {
-      if (inputCredentials.username === credentials.username && 
-          inputCredentials.password === credentials.password) {
-        setIsAuthModalOpen(false)
-        if (authAction === 'create') {
-          // Handle create action
-        } else if (authAction === 'delete' && styleToDelete) {
-          await deleteStyle(styleToDelete, inputCredentials)
-        }
-      } else {
+      if (!await submitCredentials(inputCredentials)) {
         addToast('Invalid credentials', 'error')
       }
     } catch (err) {
@@ -105,30 +89,17 @@ const SettingsStyles: React.FC = () => {
 
   // Create style after authentication
   const createStyle = async (newStyle: CreateSettingsStyleRequest) => {
-    try {
-      await SettingsStylesApi.createSettingsStyle(newStyle, credentials.username, credentials.password)
-      await fetchStyles()
-      setError(null)
-      addToast('Style created successfully', 'success')
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to create style')
-      addToast('Failed to create style', 'error')
-    }
-  }
-
-  // Delete style after authentication
-  const deleteStyle = async (styleId: number, auth: { username: string; password: string }) => {
-    try {
-      await SettingsStylesApi.deleteSettingsStyle(styleId, auth.username, auth.password)
-      await fetchStyles()
-      setError(null)
-      addToast('Style deleted successfully', 'success')
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to delete style')
-      addToast('Failed to delete style', 'error')
-    } finally {
-      setStyleToDelete(null)
-    }
+    await withCredentials(async (auth) => {
+      try {
+        await SettingsStylesApi.createSettingsStyle(newStyle, auth.username, auth.password)
+        await fetchStyles()
+        setError(null)
+        addToast('Style created successfully', 'success')
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Failed to create style')
+        addToast('Failed to create style', 'error')
+      }
+    })
   }
 
   // Effects
@@ -143,7 +114,6 @@ const SettingsStyles: React.FC = () => {
 
   useEffect(() => {
     fetchStyles()
-    fetchCredentials()
   }, [fetchStyles])
 
   useEffect(() => {
@@ -191,14 +161,23 @@ const SettingsStyles: React.FC = () => {
               <SettingsStylesCard />
             )}
           </div>
+          <div>
+            <SecuritySettings />
+          </div>
+          <div>
+            <KioskDevicesSettings />
+          </div>
+          <div>
+            <NetworkServerSettingsCard />
+          </div>
         </div>
       </div>
       <ToastViewport />
       <AuthModal
         isOpen={isAuthModalOpen}
-        onClose={() => setIsAuthModalOpen(false)}
+        onClose={closeAuthModal}
         onSubmit={handleAuthSubmit}
-        action={authAction === 'create' ? 'create a new style' : 'delete the style'}
+        action="create a new style"
       />
     </ToastProvider>
   )
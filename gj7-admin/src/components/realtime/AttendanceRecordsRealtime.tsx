@@ -6,6 +6,8 @@ import { downloadAttendanceTableAsPDF } from '@/utils/pdfUtils';
 import { ToastProvider, ToastViewport } from "@/components/ui/toast";
 import { Button } from '@/components/ui/button';
 import { useToast } from '@/hooks/use-toast';
+import { useAdminCredentials } from '@/hooks/use-admin-credentials';
+import { Credentials } from '@/lib/auth';
 import AuthModal from '../AuthModal';
 import AttendanceCard from '../attendance/AttendanceCard';
 import AttendanceTable from '../attendance/AttendanceTable';
@@ -16,21 +18,16 @@ import { AttendanceWithDates, convertToAttendanceWithDates } from '@/types/atten
 import FilteredAttendanceTable from '../FilteredAttendanceTable';
 
 const AttendanceRecordsRealtime: React.FC = () => {
-  const { attendances: rawAttendances, isConnected, sendAttendance } = useAttendanceWebSocket();
+  const { attendances: rawAttendances, isConnected } = useAttendanceWebSocket();
   const attendances = rawAttendances.map(convertToAttendanceWithDates);
   const [error, setError] = useState<string | null>(null);
   const [searchQuery, setSearchQuery] = useState('');
   const [searchStatus, setSearchStatus] = useState<string | null>(null);
-  const [isAuthModalOpen, setIsAuthModalOpen] = useState(false);
-  const [authAction, setAuthAction] = useState<'create' | 'delete'>('create');
-  const [credentials, _setCredentials] = useState<{ username: string; password: string }>({ 
-    username: 'admin', 
-    password: 'your_password' 
-  });
-  const [attendanceToDelete, setAttendanceToDelete] = useState<string | null>(null);
+  const [authAction, setAuthAction] = useState<'update' | 'delete'>('update');
   const [view, setView] = useState<'card' | 'table'>('card');
 
   const { toast } = useToast();
+  const { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal } = useAdminCredentials();
 
   const addToast = useCallback((message: string, level: LogLevel) => {
     toast({
@@ -49,21 +46,9 @@ const AttendanceRecordsRealtime: React.FC = () => {
     console.log('WebSocket Connection Status Changed:', isConnected);
   }, [isConnected]);
 
-  const handleAuthSubmit = async (inputCredentials: { username: string; password: string }) => {
+  const handleAuthSubmit = async (inputCredentials: Credentials) => {
     try {
-      if (inputCredentials.username === credentials.username && 
-          inputCredentials.password === credentials.password) {
-        setIsAuthModalOpen(false);
-        if (authAction === 'create') {
-          sendAttendance({
-            school_id: 'test',
-            full_name: 'Test User',
-            classification: 'Student'
-          });
-        } else if (authAction === 'delete' && attendanceToDelete) {
-          await deleteAttendance(attendanceToDelete, inputCredentials);
-        }
-      } else {
+      if (!await submitCredentials(inputCredentials)) {
         addToast('Invalid credentials', 'error');
       }
     } catch (err) {
@@ -72,24 +57,25 @@ const AttendanceRecordsRealtime: React.FC = () => {
   };
 
   const handleUpdateAttendance = async (attendanceId: string, updatedAttendance: UpdateAttendanceRequest) => {
-    try {
-      await AttendanceApi.updateAttendance(attendanceId, updatedAttendance, credentials.username, credentials.password);
-      addToast('Attendance record updated successfully', 'success');
-    } catch (err) {
-      setError(err instanceof Error ? err.message : 'Failed to update attendance');
-      addToast('Failed to update attendance record', 'error');
-    }
+    setAuthAction('update');
+    await withCredentials(async (auth) => {
+      try {
+        await AttendanceApi.updateAttendance(attendanceId, updatedAttendance, auth.username, auth.password);
+        addToast('Attendance record updated successfully', 'success');
+      } catch (err) {
+        setError(err instanceof Error ? err.message : 'Failed to update attendance');
+        addToast('Failed to update attendance record', 'error');
+      }
+    });
   };
 
-  const deleteAttendance = async (attendanceId: string, auth: { username: string; password: string }) => {
+  const deleteAttendance = async (attendanceId: string, auth: Credentials) => {
     try {
       await AttendanceApi.deleteAttendance(attendanceId, auth.username, auth.password);
       addToast('Attendance record deleted successfully', 'success');
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to delete attendance');
       addToast('Failed to delete attendance record', 'error');
-    } finally {
-      setAttendanceToDelete(null);
     }
   };
 
@@ -163,9 +149,8 @@ const AttendanceRecordsRealtime: React.FC = () => {
                 attendance={attendance}
                 onUpdate={handleUpdateAttendance}
                 onDelete={(attendanceId) => {
-                  setAttendanceToDelete(attendanceId);
                   setAuthAction('delete');
-                  setIsAuthModalOpen(true);
+                  withCredentials((auth) => deleteAttendance(attendanceId, auth), true);
                 }}
               />
             ))}
@@ -179,9 +164,9 @@ const AttendanceRecordsRealtime: React.FC = () => {
       <ToastViewport />
       <AuthModal
         isOpen={isAuthModalOpen}
-        onClose={() => setIsAuthModalOpen(false)}
+        onClose={closeAuthModal}
         onSubmit={handleAuthSubmit}
-        action={authAction === 'create' ? 'create a new attendance record' : 'delete the attendance record'}
+        action={authAction === 'update' ? 'update the attendance record' : 'delete the attendance record'}
       />
     </ToastProvider>
   );
@@ -0,0 +1,51 @@
+// src/hooks/use-admin-credentials.ts
+import { useState, useCallback } from "react";
+import { AuthApi, Credentials } from "@/lib/auth";
+
+type CredentialedAction = (credentials: Credentials) => Promise<void>;
+
+/**
+ * Keeps admin credentials once the backend has verified them, so routine edits
+ * only prompt the first time.
+ * @returns withCredentials runs an action, opening the auth modal first when no
+ * credentials are known or when alwaysAsk is set (e.g. deletes).
+ */
+export function useAdminCredentials() {
+  const [credentials, setCredentials] = useState<Credentials | null>(null);
+  const [pendingAction, setPendingAction] = useState<CredentialedAction | null>(null);
+  const [isAuthModalOpen, setIsAuthModalOpen] = useState(false);
+
+  const withCredentials = useCallback(async (action: CredentialedAction, alwaysAsk = false) => {
+    if (credentials && !alwaysAsk) {
+      await action(credentials);
+      return;
+    }
+    setPendingAction(() => action);
+    setIsAuthModalOpen(true);
+  }, [credentials]);
+
+  // Resolves to false when the backend rejects the credentials. Wrong passwords
+  // count towards the same lockout as signing in.
+  const submitCredentials = useCallback(async (input: Credentials) => {
+    const verified = await AuthApi.authenticate(input.username, input.password).catch(() => false);
+    if (!verified) {
+      return false;
+    }
+
+    setCredentials(input);
+    setIsAuthModalOpen(false);
+    const action = pendingAction;
+    setPendingAction(null);
+    if (action) {
+      await action(input);
+    }
+    return true;
+  }, [pendingAction]);
+
+  const closeAuthModal = useCallback(() => {
+    setIsAuthModalOpen(false);
+    setPendingAction(null);
+  }, []);
+
+  return { withCredentials, submitCredentials, isAuthModalOpen, closeAuthModal };
+}
@@ -0,0 +1,116 @@
+// hooks/useSession.tsx
+import React, { createContext, useContext, useState, useEffect, useCallback, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { AuthApi, LockState, Permission, SessionUser } from '../lib/auth';
+
+// Input is reported to the idle lock at most this often
+const ACTIVITY_REPORT_INTERVAL_MS = 30 * 1000;
+
+interface SessionContextType {
+  user: SessionUser | null;
+  isLoading: boolean;
+  isLocked: boolean;
+  login: (username: string, password: string) => Promise<void>;
+  logout: () => Promise<void>;
+  lock: () => Promise<void>;
+  // Re-reads the signed-in user after their account changes, e.g. a new PIN
+  refreshUser: () => Promise<void>;
+  // Called once the PIN has been verified, which unlocks the backend session
+  markUnlocked: () => void;
+  can: (permission: Permission) => boolean;
+}
+
+const SessionContext = createContext<SessionContextType | undefined>(undefined);
+
+export const SessionProvider: React.FC<{ children: React.ReactNode }> = ({ children }) => {
+  const [user, setUser] = useState<SessionUser | null>(null);
+  const [isLoading, setIsLoading] = useState(true);
+  const [isLocked, setIsLocked] = useState(false);
+  const lastActivityReport = useRef(0);
+
+  const applyLockState = useCallback((lockState: LockState) => {
+    setIsLocked(lockState === 'Locked');
+    if (lockState === 'SignedOut') {
+      setUser(null);
+    }
+  }, []);
+
+  // The backend keeps the session, so a reload picks up the signed-in user
+  useEffect(() => {
+    Promise.all([AuthApi.getCurrentUser(), AuthApi.getLockState()])
+      .then(([currentUser, lockState]) => {
+        setUser(currentUser);
+        applyLockState(lockState);
+      })
+      .catch((error) => console.error('Failed to fetch current user', error))
+      .finally(() => setIsLoading(false));
+  }, [applyLockState]);
+
+  // The backend locks the session after the idle timeout
+  useEffect(() => {
+    const unlisten = listen<LockState>('session-locked', (event) => applyLockState(event.payload));
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [applyLockState]);
+
+  useEffect(() => {
+    if (!user || isLocked) {
+      return;
+    }
+
+    const reportActivity = () => {
+      const now = Date.now();
+      if (now - lastActivityReport.current < ACTIVITY_REPORT_INTERVAL_MS) {
+        return;
+      }
+      lastActivityReport.current = now;
+      AuthApi.recordActivity().catch((error) => console.error('Failed to record activity', error));
+    };
+
+    const events = ['mousedown', 'keydown', 'wheel', 'touchstart'];
+    events.forEach((name) => window.addEventListener(name, reportActivity));
+    return () => events.forEach((name) => window.removeEventListener(name, reportActivity));
+  }, [user, isLocked]);
+
+  const login = useCallback(async (username: string, password: string) => {
+    setUser(await AuthApi.login(username, password));
+    setIsLocked(false);
+  }, []);
+
+  const logout = useCallback(async () => {
+    await AuthApi.logout();
+    setUser(null);
+    setIsLocked(false);
+  }, []);
+
+  const lock = useCallback(async () => {
+    applyLockState(await AuthApi.lockSession());
+  }, [applyLockState]);
+
+  const refreshUser = useCallback(async () => {
+    setUser(await AuthApi.getCurrentUser());
+  }, []);
+
+  const markUnlocked = useCallback(() => {
+    setIsLocked(false);
+  }, []);
+
+  const can = useCallback((permission: Permission) => {
+    return user?.permissions.includes(permission) ?? false;
+  }, [user]);
+
+  return (
+    <SessionContext.Provider value={{ user, isLoading, isLocked, login, logout, lock, refreshUser, markUnlocked, can }}>
+      {children}
+    </SessionContext.Provider>
+  );
+};
+
+export const useSession = () => {
+  const context = useContext(SessionContext);
+  if (context === undefined) {
+    throw new Error('useSession must be used within a SessionProvider');
+  }
+  return context;
+};
@@ -12,6 +12,7 @@ export interface AttendanceWithDates {
   time_in_date: Date;
   classification: string;
   purpose_label?: string;  // Changed from purpose_id
+  location?: string | null;
 }
 
 
@@ -22,6 +23,40 @@ export interface CreateAttendanceRequest {
   purpose_label?: string;  // Changed from purpose_id
 }
 
+export interface ManualAttendanceRequest {
+  school_id: string;
+  full_name: string;
+  classification?: string | null;
+  purpose_label?: string | null;
+  time_in_date: string;            // ISO string
+  time_out_date?: string | null;   // ISO string
+  semester_id?: string | null;     // Defaults to the semester the time in falls in
+}
+
+export type AttendanceAction = 'Created' | 'Updated' | 'Deleted' | 'Restored';
+
+export interface AttendanceChange {
+  id: string;
+  attendance_id: string;
+  action: AttendanceAction;
+  before: Attendance | null;
+  after: Attendance | null;
+  changed_by: string;
+  reason: string | null;
+  changed_at: string;
+}
+
+export type BulkAttendanceAction =
+  | { SetPurpose: { purpose_label: string | null } }
+  | { SetClassification: { classification: string } }
+  | 'Delete'
+  | 'Restore';
+
+export interface BulkPreview {
+  matched: number;
+  sample: Attendance[];
+}
+
 export interface UpdateAttendanceRequest {
   school_id?: string;
   full_name?: string;
@@ -29,9 +64,104 @@ export interface UpdateAttendanceRequest {
   purpose_label?: string;  // Changed from purpose_id
 }
 
-export interface Credentials {
-  username: string;
-  password: string;
+export interface AttendanceFilter {
+  from?: string | null;  // ISO string, inclusive
+  to?: string | null;    // ISO string, exclusive
+  from_date?: string | null;  // YYYY-MM-DD library day, inclusive
+  to_date?: string | null;    // YYYY-MM-DD library day, inclusive
+  course?: string | null;
+  purpose_label?: string | null;
+  classification?: string | null;
+  school_id_prefix?: string | null;
+  year_level?: string | null;
+  department?: string | null;
+  semester_id?: string | null;
+  is_manual?: boolean | null;
+  device_id?: number | null;
+  location?: string | null;
+  include_deleted?: boolean;
+}
+
+export type AttendanceSortField =
+  | 'TimeIn'
+  | 'TimeOut'
+  | 'SchoolId'
+  | 'FullName'
+  | 'Classification'
+  | 'Purpose'
+  | 'Course'
+  | 'Location';
+
+export interface AttendanceSort {
+  field: AttendanceSortField;
+  order: 'Asc' | 'Desc';
+}
+
+export interface PaginatedAttendances {
+  attendances: Attendance[];
+  total_count: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+}
+
+export interface PaginatedAttendancesWithDates extends Omit<PaginatedAttendances, 'attendances'> {
+  attendances: AttendanceWithDates[];
+}
+
+export type ExportColumn =
+  | 'Id'
+  | 'SchoolId'
+  | 'FullName'
+  | 'TimeIn'
+  | 'TimeOut'
+  | 'DurationMinutes'
+  | 'Classification'
+  | 'Purpose'
+  | 'Course'
+  | 'Department'
+  | 'YearLevel'
+  | 'EntryMethod'
+  | 'EnteredBy'
+  | 'Location';
+
+export type ExportFormat = 'Csv' | 'Xlsx';
+
+export interface ExportSummary {
+  file_path: string;
+  row_count: number;
+}
+
+export type ReportPeriod =
+  | { Daily: { date: string } }
+  | { Weekly: { date: string } }
+  | { Semester: { semester_id: string } };
+
+export interface ReportSummary {
+  file_path: string;
+  title: string;
+  total_visits: number;
+  page_count: number;
+}
+
+export interface SeriesPoint {
+  label: string;
+  value: number;
+}
+
+export interface Occupancy {
+  current: number;
+  capacity: number | null;
+  over_capacity: boolean;
+  by_classification: SeriesPoint[];
+  by_purpose: SeriesPoint[];
+  as_of: string;
+}
+
+export interface OccupancySettings {
+  auto_checkout_time: string | null;
+  max_visit_hours: number | null;
+  capacity: number | null;
 }
 
 function convertToDateAttendance(attendance: Attendance): AttendanceWithDates {
@@ -54,6 +184,16 @@ export const AttendanceApi = {
     }
   },
 
+  // Every location a visit has been recorded at, for the location filter
+  async getAttendanceLocations(): Promise<string[]> {
+    try {
+      return await invoke('get_attendance_locations') as string[];
+    } catch (error) {
+      logger.log(`Failed to fetch attendance locations: ${error}`, 'error');
+      throw error;
+    }
+  },
+
   async getUnfilteredAttendances(date?: Date): Promise<AttendanceWithDates[]> {
     try {
       logger.log('Fetching unfiltered attendances', 'info');
@@ -70,17 +210,6 @@ export const AttendanceApi = {
     }
   },
   
-  async getCredentials(): Promise<Credentials> {
-    try {
-      logger.log('Fetching credentials', 'info');
-      const credentials = await invoke('get_credentials');
-      return credentials as Credentials;
-    } catch (error) {
-      logger.log(`Failed to fetch credentials: ${error}`, 'error');
-      throw error;
-    }
-  },
-
   async createAttendance(
     attendance: CreateAttendanceRequest, 
     username: string, 
@@ -97,24 +226,91 @@ export const AttendanceApi = {
     }
   },
 
+  async createManualAttendance(
+    attendance: ManualAttendanceRequest,
+    username: string,
+    password: string
+  ): Promise<AttendanceWithDates> {
+    try {
+      logger.log(`Entering manual attendance for: ${attendance.school_id}`, 'info');
+      const result = await invoke('create_manual_attendance', { attendance, username, password });
+      logger.log(`Successfully entered manual attendance for: ${attendance.school_id}`, 'success');
+      return convertToDateAttendance(result as Attendance);
+    } catch (error) {
+      logger.log(`Failed to enter manual attendance: ${error}`, 'error');
+      throw error;
+    }
+  },
+
   async getFilteredAttendances(
-    course?: string, 
-    date?: Date
-  ): Promise<AttendanceWithDates[]> {
+    filter: AttendanceFilter,
+    sort?: AttendanceSort,
+    page: number = 1,
+    pageSize: number = 50
+  ): Promise<PaginatedAttendancesWithDates> {
     try {
       logger.log('Fetching filtered attendances', 'info');
-      const attendances = await invoke('get_filtered_attendances', { 
-        course: course || null, 
-        date: date?.toISOString() || null
-      });
-      logger.log(`Successfully fetched ${(attendances as Attendance[]).length} filtered attendances`, 'success');
-      return (attendances as Attendance[]).map(convertToDateAttendance);
+      const result = await invoke('get_filtered_attendances', { 
+        filter,
+        sort: sort || null,
+        page,
+        pageSize
+      }) as PaginatedAttendances;
+      logger.log(`Successfully fetched ${result.attendances.length} of ${result.total_count} filtered attendances`, 'success');
+      return {
+        ...result,
+        attendances: result.attendances.map(convertToDateAttendance)
+      };
     } catch (error) {
       logger.log(`Failed to fetch filtered attendances: ${error}`, 'error');
       throw error;
     }
   },
 
+  async exportAttendances(
+    filter: AttendanceFilter,
+    format: ExportFormat,
+    filePath: string,
+    columns: ExportColumn[] = [],
+    sort?: AttendanceSort
+  ): Promise<ExportSummary> {
+    try {
+      logger.log(`Exporting attendances to ${filePath}`, 'info');
+      const summary = await invoke('export_attendances', {
+        filter,
+        sort: sort || null,
+        columns,
+        format,
+        filePath
+      }) as ExportSummary;
+      logger.log(`Successfully exported ${summary.row_count} attendances`, 'success');
+      return summary;
+    } catch (error) {
+      logger.log(`Failed to export attendances: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async generateAttendanceReport(
+    period: ReportPeriod,
+    filePath: string,
+    preparedBy?: string
+  ): Promise<ReportSummary> {
+    try {
+      logger.log(`Generating attendance report at ${filePath}`, 'info');
+      const summary = await invoke('generate_attendance_report', {
+        period,
+        filePath,
+        preparedBy: preparedBy || null
+      }) as ReportSummary;
+      logger.log(`Successfully generated ${summary.title} (${summary.page_count} pages)`, 'success');
+      return summary;
+    } catch (error) {
+      logger.log(`Failed to generate attendance report: ${error}`, 'error');
+      throw error;
+    }
+  },
+
   async getAllAttendances(): Promise<AttendanceWithDates[]> {
     try {
       logger.log('Fetching all attendances', 'info');
@@ -143,11 +339,12 @@ export const AttendanceApi = {
     id: string, 
     attendance: UpdateAttendanceRequest, 
     username: string, 
-    password: string
+    password: string,
+    reason?: string
   ): Promise<AttendanceWithDates> {
     try {
       logger.log(`Updating attendance ${id}`, 'info');
-      const result = await invoke('update_attendance', { id, attendance, username, password });
+      const result = await invoke('update_attendance', { id, attendance, reason: reason || null, username, password });
       logger.log(`Successfully updated attendance ${id}`, 'success');
       return convertToDateAttendance(result as Attendance);
     } catch (error) {
@@ -159,11 +356,12 @@ export const AttendanceApi = {
   async deleteAttendance(
     id: string, 
     username: string, 
-    password: string
+    password: string,
+    reason?: string
   ): Promise<void> {
     try {
       logger.log(`Deleting attendance ${id}`, 'info');
-      await invoke('delete_attendance', { id, username, password });
+      await invoke('delete_attendance', { id, reason: reason || null, username, password });
       logger.log(`Successfully deleted attendance ${id}`, 'success');
     } catch (error) {
       logger.log(`Failed to delete attendance ${id}: ${error}`, 'error');
@@ -171,6 +369,71 @@ export const AttendanceApi = {
     }
   },
 
+  async restoreAttendance(
+    id: string,
+    username: string,
+    password: string,
+    reason?: string
+  ): Promise<AttendanceWithDates> {
+    try {
+      logger.log(`Restoring attendance ${id}`, 'info');
+      const result = await invoke('restore_attendance', { id, reason: reason || null, username, password });
+      logger.log(`Successfully restored attendance ${id}`, 'success');
+      return convertToDateAttendance(result as Attendance);
+    } catch (error) {
+      logger.log(`Failed to restore attendance ${id}: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async previewBulkAttendanceAction(
+    filter: AttendanceFilter,
+    action: BulkAttendanceAction
+  ): Promise<BulkPreview> {
+    try {
+      return await invoke('preview_bulk_attendance_action', { filter, action }) as BulkPreview;
+    } catch (error) {
+      logger.log(`Failed to preview bulk attendance action: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // expectedCount is the `matched` value from the preview being confirmed
+  async applyBulkAttendanceAction(
+    filter: AttendanceFilter,
+    action: BulkAttendanceAction,
+    expectedCount: number,
+    username: string,
+    password: string,
+    reason?: string
+  ): Promise<number> {
+    try {
+      logger.log(`Applying bulk attendance action to ${expectedCount} records`, 'info');
+      const changed = await invoke('apply_bulk_attendance_action', {
+        filter,
+        action,
+        expectedCount,
+        reason: reason || null,
+        username,
+        password
+      }) as number;
+      logger.log(`Successfully updated ${changed} attendance records`, 'success');
+      return changed;
+    } catch (error) {
+      logger.log(`Failed to apply bulk attendance action: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async getAttendanceHistory(id: string): Promise<AttendanceChange[]> {
+    try {
+      return await invoke('get_attendance_history', { id }) as AttendanceChange[];
+    } catch (error) {
+      logger.log(`Failed to fetch history for attendance ${id}: ${error}`, 'error');
+      throw error;
+    }
+  },
+
   async getAttendancesBySemester(semester_id: string): Promise<AttendanceWithDates[]> {
     try {
       logger.log(`Fetching attendances for semester: ${semester_id}`, 'info');
@@ -193,5 +456,74 @@ export const AttendanceApi = {
       logger.log(`Failed to fetch attendances for school account: ${error}`, 'error');
       throw error;
     }
+  },
+
+  async getOccupancy(): Promise<Occupancy> {
+    try {
+      return await invoke('get_occupancy') as Occupancy;
+    } catch (error) {
+      logger.log(`Failed to fetch occupancy: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async getOccupancySettings(): Promise<OccupancySettings> {
+    try {
+      return await invoke('get_occupancy_settings') as OccupancySettings;
+    } catch (error) {
+      logger.log(`Failed to fetch occupancy settings: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async setOccupancySettings(
+    settings: OccupancySettings,
+    username: string,
+    password: string
+  ): Promise<OccupancySettings> {
+    try {
+      logger.log('Updating occupancy settings', 'info');
+      const updated = await invoke('set_occupancy_settings', { settings, username, password }) as OccupancySettings;
+      logger.log('Successfully updated occupancy settings', 'success');
+      return updated;
+    } catch (error) {
+      logger.log(`Failed to update occupancy settings: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async expireStaleVisits(): Promise<AttendanceWithDates[]> {
+    try {
+      const expired = await invoke('expire_stale_visits') as Attendance[];
+      if (expired.length > 0) {
+        logger.log(`Auto checked out ${expired.length} stale visits`, 'info');
+      }
+      return expired.map(convertToDateAttendance);
+    } catch (error) {
+      logger.log(`Failed to expire stale visits: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // The library's UTC offset, e.g. "+08:00"
+  async getLibraryTimezone(): Promise<string> {
+    try {
+      return await invoke('get_library_timezone') as string;
+    } catch (error) {
+      logger.log(`Failed to fetch library timezone: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async setLibraryTimezone(utcOffset: string, username: string, password: string): Promise<string> {
+    try {
+      logger.log(`Setting library timezone to UTC${utcOffset}`, 'info');
+      const saved = await invoke('set_library_timezone', { utcOffset, username, password }) as string;
+      logger.log('Successfully updated library timezone', 'success');
+      return saved;
+    } catch (error) {
+      logger.log(`Failed to update library timezone: ${error}`, 'error');
+      throw error;
+    }
   }
 };
\ No newline at end of file
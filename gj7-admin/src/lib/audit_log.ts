@@ -0,0 +1,114 @@
+// lib/audit_log.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { logger } from './logger';
+import { ExportSummary } from './attendance';
+
+export type AuditAction =
+  | 'CsvImported'
+  | 'SemesterCreated'
+  | 'SemesterUpdated'
+  | 'SemesterDeleted'
+  | 'SemesterActivated'
+  | 'PurposeCreated'
+  | 'PurposeUpdated'
+  | 'PurposeDeleted'
+  | 'PurposeRestored'
+  | 'AccountSemesterChanged'
+  | 'AttendanceCreated'
+  | 'AttendanceUpdated'
+  | 'AttendanceDeleted'
+  | 'AttendanceRestored'
+  | 'AttendanceBulkAction'
+  | 'UserCreated'
+  | 'UserRoleChanged'
+  | 'UserActivated'
+  | 'UserDeactivated'
+  | 'UserDeleted'
+  | 'PasswordChanged'
+  | 'PasswordReset'
+  | 'SettingsChanged'
+  | 'KioskPairingCodeCreated'
+  | 'KioskPaired'
+  | 'KioskRevoked';
+
+export interface AuditEntry {
+  id: number;
+  occurred_at: string;
+  // The user who authorized the action
+  username: string;
+  action: AuditAction;
+  target: string | null;
+  summary: string | null;
+}
+
+export interface AuditLogFilter {
+  username?: string;
+  action?: AuditAction;
+  // ISO timestamps; `to` is exclusive
+  from?: string;
+  to?: string;
+}
+
+export interface PaginatedAuditLog {
+  entries: AuditEntry[];
+  total_count: number;
+  page: number;
+  page_size: number;
+  total_pages: number;
+}
+
+export interface BrokenLink {
+  // The chain entry where the break was found; null for a record with no entry
+  seq: number | null;
+  source: 'attendance' | 'audit_log';
+  record_id: string;
+  reason: string;
+}
+
+export interface ChainVerification {
+  is_intact: boolean;
+  entries_checked: number;
+  records_checked: number;
+  // Hash of the newest entry, worth keeping to show later that nothing was cut off the end
+  head_hash: string | null;
+  first_broken: BrokenLink | null;
+}
+
+export const AuditLogApi = {
+  // Newest first
+  async getAuditLog(filter: AuditLogFilter = {}, page = 1, pageSize = 50): Promise<PaginatedAuditLog> {
+    try {
+      return await invoke('get_audit_log', { filter, page, pageSize }) as PaginatedAuditLog;
+    } catch (error) {
+      logger.log(`Failed to fetch audit log: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async exportAuditLog(filter: AuditLogFilter, filePath: string): Promise<ExportSummary> {
+    try {
+      logger.log(`Exporting audit log to ${filePath}`, 'info');
+      const summary = await invoke('export_audit_log', { filter, filePath }) as ExportSummary;
+      logger.log(`Successfully exported ${summary.row_count} audit log entries`, 'success');
+      return summary;
+    } catch (error) {
+      logger.log(`Failed to export audit log: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // Walks the attendance and audit hash chain; the first broken link is reported
+  async verifyHashChain(): Promise<ChainVerification> {
+    try {
+      const verification = await invoke('verify_hash_chain') as ChainVerification;
+      if (!verification.is_intact) {
+        logger.log(`Hash chain broken: ${verification.first_broken?.reason}`, 'error');
+      }
+      return verification;
+    } catch (error) {
+      logger.log(`Failed to verify hash chain: ${error}`, 'error');
+      throw error;
+    }
+  }
+};
@@ -0,0 +1,244 @@
+// lib/auth.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { logger } from './logger';
+
+export interface Credentials {
+  username: string;
+  password: string;
+}
+
+export type Role = 'Librarian' | 'Assistant' | 'Viewer';
+
+export type Permission =
+  | 'ViewRecords'
+  | 'ExportReports'
+  | 'RecordAttendance'
+  | 'EditAttendance'
+  | 'ManageNotes'
+  | 'ManageAccounts'
+  | 'ImportCsv'
+  | 'ManageSemesters'
+  | 'ManagePurposes'
+  | 'ManageSettings'
+  | 'ManageUsers'
+  | 'ViewAuditLog'
+  | 'ManageDevices';
+
+// The password itself is never returned by the backend
+export interface User {
+  id: number;
+  username: string;
+  role: Role;
+  is_active: boolean;
+  recovery_codes_remaining: number;
+  has_pin: boolean;
+}
+
+export interface SessionUser {
+  id: number;
+  username: string;
+  role: Role;
+  permissions: Permission[];
+  has_pin: boolean;
+}
+
+export type LockState = 'SignedOut' | 'Unlocked' | 'Locked';
+
+export interface PinCheck {
+  verified: boolean;
+  attempts_remaining: number;
+  // ISO timestamp while further attempts are locked out
+  locked_until: string | null;
+}
+
+export interface IdleLockSettings {
+  // 0 turns the idle lock off
+  idle_minutes: number;
+}
+
+export const AuthApi = {
+  async authenticate(username: string, password: string): Promise<boolean> {
+    try {
+      return await invoke('authenticate', { username, password }) as boolean;
+    } catch (error) {
+      logger.log(`Failed to authenticate: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async login(username: string, password: string): Promise<SessionUser> {
+    try {
+      const user = await invoke('login', { username, password }) as SessionUser;
+      logger.log(`Signed in as ${user.username}`, 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to sign in: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async logout(): Promise<void> {
+    await invoke('logout');
+  },
+
+  async getCurrentUser(): Promise<SessionUser | null> {
+    return await invoke('get_current_user') as SessionUser | null;
+  },
+
+  async getLockState(): Promise<LockState> {
+    return await invoke('get_lock_state') as LockState;
+  },
+
+  async recordActivity(): Promise<void> {
+    await invoke('record_activity');
+  },
+
+  async lockSession(): Promise<LockState> {
+    return await invoke('lock_session') as LockState;
+  },
+
+  async setPin(pin: string, username: string, password: string): Promise<User> {
+    try {
+      const user = await invoke('set_pin', { pin, username, password }) as User;
+      logger.log('Successfully set PIN', 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to set PIN: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async clearPin(username: string, password: string): Promise<User> {
+    try {
+      const user = await invoke('clear_pin', { username, password }) as User;
+      logger.log('Successfully removed PIN', 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to remove PIN: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async getPinStatus(): Promise<PinCheck> {
+    return await invoke('get_pin_status') as PinCheck;
+  },
+
+  // Also unlocks the app when it was locked and the PIN matches
+  async verifyPin(pin: string): Promise<PinCheck> {
+    return await invoke('verify_pin', { pin }) as PinCheck;
+  },
+
+  async getIdleLockSettings(): Promise<IdleLockSettings> {
+    return await invoke('get_idle_lock_settings') as IdleLockSettings;
+  },
+
+  async setIdleLockSettings(settings: IdleLockSettings, username: string, password: string): Promise<IdleLockSettings> {
+    try {
+      const saved = await invoke('set_idle_lock_settings', { settings, username, password }) as IdleLockSettings;
+      logger.log(`Idle lock set to ${saved.idle_minutes} minutes`, 'success');
+      return saved;
+    } catch (error) {
+      logger.log(`Failed to update idle lock: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async changePassword(username: string, currentPassword: string, newPassword: string): Promise<void> {
+    try {
+      logger.log('Changing password', 'info');
+      await invoke('change_password', { username, currentPassword, newPassword });
+      logger.log('Successfully changed password', 'success');
+    } catch (error) {
+      logger.log(`Failed to change password: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // The codes are only shown once; store them somewhere safe
+  async generateRecoveryCodes(username: string, password: string): Promise<string[]> {
+    try {
+      logger.log('Generating recovery codes', 'info');
+      const codes = await invoke('generate_recovery_codes', { username, password }) as string[];
+      logger.log(`Generated ${codes.length} recovery codes`, 'success');
+      return codes;
+    } catch (error) {
+      logger.log(`Failed to generate recovery codes: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async resetPasswordWithRecoveryCode(username: string, recoveryCode: string, newPassword: string): Promise<void> {
+    try {
+      logger.log('Resetting password with a recovery code', 'info');
+      await invoke('reset_password_with_recovery_code', { username, recoveryCode, newPassword });
+      logger.log('Successfully reset password', 'success');
+    } catch (error) {
+      logger.log(`Failed to reset password: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async listUsers(): Promise<User[]> {
+    try {
+      return await invoke('list_users') as User[];
+    } catch (error) {
+      logger.log(`Failed to fetch users: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async createUser(newUser: Credentials, role: Role, username: string, password: string): Promise<User> {
+    try {
+      logger.log(`Creating ${role} user: ${newUser.username}`, 'info');
+      const user = await invoke('create_user', { newUser, role, username, password }) as User;
+      logger.log(`Successfully created user: ${user.username}`, 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to create user: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async setUserRole(userId: number, role: Role, username: string, password: string): Promise<User> {
+    try {
+      const user = await invoke('set_user_role', { userId, role, username, password }) as User;
+      logger.log(`${user.username} is now a ${role}`, 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to change user role: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async setUserActive(userId: number, isActive: boolean, username: string, password: string): Promise<User> {
+    try {
+      const user = await invoke('set_user_active', { userId, isActive, username, password }) as User;
+      logger.log(`${user.username} ${isActive ? 'activated' : 'deactivated'}`, 'success');
+      return user;
+    } catch (error) {
+      logger.log(`Failed to update user: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async deleteUser(userId: number, username: string, password: string): Promise<void> {
+    try {
+      await invoke('delete_user', { userId, username, password });
+      logger.log('Successfully deleted user', 'success');
+    } catch (error) {
+      logger.log(`Failed to delete user: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async resetUserPassword(userId: number, newPassword: string, username: string, password: string): Promise<void> {
+    try {
+      await invoke('reset_user_password', { userId, newPassword, username, password });
+      logger.log('Successfully reset user password', 'success');
+    } catch (error) {
+      logger.log(`Failed to reset user password: ${error}`, 'error');
+      throw error;
+    }
+  }
+};
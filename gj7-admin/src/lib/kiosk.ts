@@ -0,0 +1,99 @@
+// lib/kiosk.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { logger } from './logger';
+
+// Shown once; a kiosk types it in to pair with the network server
+export interface PairingCode {
+  code: string;
+  expires_at: string;
+}
+
+export interface KioskDevice {
+  id: number;
+  name: string;
+  paired_at: string;
+  // The user who generated the pairing code
+  paired_by: string;
+  revoked_at: string | null;
+  // Reported by the kiosk in its heartbeats
+  location: string | null;
+  app_version: string | null;
+  last_heartbeat_at: string | null;
+  // Connected and sending heartbeats
+  is_online: boolean;
+}
+
+// Where the kiosk network server listens; saving restarts it
+export interface NetworkServerSettings {
+  bind_address: string;
+  port: number;
+  // HTTPS/WSS with a self-signed certificate
+  tls_enabled: boolean;
+}
+
+export const KioskApi = {
+  // Replaces any code that has not been used yet
+  async createPairingCode(username: string, password: string): Promise<PairingCode> {
+    try {
+      const pairingCode = await invoke('create_pairing_code', { username, password }) as PairingCode;
+      logger.log('Kiosk pairing code created', 'success');
+      return pairingCode;
+    } catch (error) {
+      logger.log(`Failed to create pairing code: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // Active devices first
+  async listDevices(): Promise<KioskDevice[]> {
+    try {
+      return await invoke('list_kiosk_devices') as KioskDevice[];
+    } catch (error) {
+      logger.log(`Failed to fetch kiosk devices: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async revokeDevice(deviceId: number, username: string, password: string): Promise<KioskDevice> {
+    try {
+      const device = await invoke('revoke_kiosk_device', { deviceId, username, password }) as KioskDevice;
+      logger.log(`Kiosk ${device.name} revoked`, 'success');
+      return device;
+    } catch (error) {
+      logger.log(`Failed to revoke kiosk: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async getNetworkSettings(): Promise<NetworkServerSettings> {
+    return await invoke('get_network_server_settings') as NetworkServerSettings;
+  },
+
+  async setNetworkSettings(settings: NetworkServerSettings, username: string, password: string): Promise<NetworkServerSettings> {
+    try {
+      const saved = await invoke('set_network_server_settings', { settings, username, password }) as NetworkServerSettings;
+      logger.log(`Network server moved to ${saved.bind_address}:${saved.port}`, 'success');
+      return saved;
+    } catch (error) {
+      logger.log(`Failed to update network server settings: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  // SHA-256 of the server key, to check against the key kiosks pin when they pair
+  async getCertificateFingerprint(): Promise<string> {
+    return await invoke('get_server_certificate_fingerprint') as string;
+  },
+
+  // Called when the server cannot start, e.g. because the port is in use
+  onServerError(handler: (message: string) => void): Promise<UnlistenFn> {
+    return listen<string>('network-server-error', (event) => handler(event.payload));
+  },
+
+  // Called with the device whenever a kiosk goes online or offline
+  onStatusChanged(handler: (device: KioskDevice) => void): Promise<UnlistenFn> {
+    return listen<KioskDevice>('kiosk-status-changed', (event) => handler(event.payload));
+  }
+};
@@ -37,23 +37,7 @@ function convertToDateNote(note: Note): NoteWithDates {
   };
 }
 
-export interface Credentials {
-  username: string;
-  password: string;
-}
-
 export const NotesApi = {
-  async getCredentials(): Promise<Credentials> {
-    try {
-      logger.log('Fetching credentials', 'info');
-      const credentials = await invoke('get_credentials');
-      return credentials as Credentials;
-    } catch (error) {
-      logger.log(`Failed to fetch credentials: ${error}`, 'error');
-      throw error;
-    }
-  },
-
   async createNote(note: CreateNoteRequest, username: string, password: string): Promise<NoteWithDates> {
     try {
       logger.log(`Creating new note: ${note.title}`, 'info');
@@ -0,0 +1,46 @@
+// lib/search.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { logger } from './logger';
+
+export type SearchResultKind = 'Account' | 'Attendance' | 'Note';
+
+export interface SearchResult {
+  kind: SearchResultKind;
+  // School account or attendance UUID, or the note's numeric id
+  id: string;
+  title: string;
+  // Matched terms are wrapped in <mark></mark>; render with snippetParts
+  snippet: string;
+  timestamp: string | null;
+  rank: number;
+}
+
+export interface SnippetPart {
+  text: string;
+  highlighted: boolean;
+}
+
+// Splits a snippet on its highlight markers so it can be rendered as plain text
+export function snippetParts(snippet: string): SnippetPart[] {
+  return snippet
+    .split(/(<mark>.*?<\/mark>)/)
+    .filter(part => part.length > 0)
+    .map(part => part.startsWith('<mark>') && part.endsWith('</mark>')
+      ? { text: part.slice(6, -7), highlighted: true }
+      : { text: part, highlighted: false });
+}
+
+export const SearchApi = {
+  // Ranked matches across school accounts, attendance and notes
+  async search(query: string, limit?: number): Promise<SearchResult[]> {
+    try {
+      const results = await invoke('search_all', { query, limit }) as SearchResult[];
+      logger.log(`Found ${results.length} results matching "${query}"`, 'success');
+      return results;
+    } catch (error) {
+      logger.log(`Failed to search: ${error}`, 'error');
+      throw error;
+    }
+  }
+};
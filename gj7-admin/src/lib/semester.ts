@@ -15,6 +15,17 @@ export interface CreateSemesterRequest {
   is_active?: boolean;
 }
 
+export interface SemesterAccount {
+  semester_id: string;
+  school_id: string;
+  course: string | null;
+  department: string | null;
+  position: string | null;
+  major: string | null;
+  year_level: string | null;
+  recorded_at: string;
+}
+
 export const SemesterApi = {
   async createSemester(semester: CreateSemesterRequest, username: string, password: string): Promise<Semester> {
     try {
@@ -122,5 +133,29 @@ export const SemesterApi = {
       logger.log(`Failed to delete semester ${id}: ${error}`, 'error');
       throw error;
     }
+  },
+
+  async getSemesterAccounts(semesterId: string): Promise<SemesterAccount[]> {
+    try {
+      logger.log(`Fetching account snapshots for semester ${semesterId}`, 'info');
+      const accounts = await invoke('get_semester_accounts', { semesterId });
+      logger.log(`Successfully fetched ${(accounts as SemesterAccount[]).length} account snapshots`, 'success');
+      return accounts as SemesterAccount[];
+    } catch (error) {
+      logger.log(`Failed to fetch account snapshots: ${error}`, 'error');
+      throw error;
+    }
+  },
+
+  async getAccountSemesterHistory(schoolId: string): Promise<SemesterAccount[]> {
+    try {
+      logger.log(`Fetching semester history for ${schoolId}`, 'info');
+      const history = await invoke('get_account_semester_history', { schoolId });
+      logger.log(`Successfully fetched semester history for ${schoolId}`, 'success');
+      return history as SemesterAccount[];
+    } catch (error) {
+      logger.log(`Failed to fetch semester history for ${schoolId}: ${error}`, 'error');
+      throw error;
+    }
   }
 };
\ No newline at end of file
@@ -2,7 +2,6 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import { logger } from './logger';
-import { Credentials } from './notes';
 
 export interface SettingsStyle {
   id?: number;
@@ -55,17 +54,6 @@ export const SettingsStylesApi = {
         }
     },
         
-    async getCredentials(): Promise<Credentials> {
-        try {
-        logger.log('Fetching credentials', 'info');
-        const credentials = await invoke('get_credentials');
-        return credentials as Credentials;
-        } catch (error) {
-        logger.log(`Failed to fetch credentials: ${error}`, 'error');
-        throw error;
-        }
-    },
-
     async createSettingsStyle(
         settingsStyle: CreateSettingsStyleRequest, 
         username: string, 
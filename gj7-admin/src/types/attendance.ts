@@ -7,6 +7,13 @@ export interface Attendance {
     time_in_date: string; // ISO string from backend
     classification: string;
     purpose_label?: string;
+    time_out_date?: string | null; // ISO string, null while the visit is open
+    semester_id?: string | null;   // Semester active when the visit was recorded
+    is_manual?: boolean;           // Entered from the paper logbook instead of scanned
+    entered_by?: string | null;
+    is_deleted?: boolean;
+    device_id?: number | null;     // Kiosk the visit was checked in at
+    location?: string | null;      // Where that kiosk stood at the time
   }
 
   
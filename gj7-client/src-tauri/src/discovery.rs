@@ -0,0 +1,162 @@
+// src/discovery.rs
+
+use std::fs;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::server_client::{load_pinned_key, presents_key};
+
+// Must match the admin app's discovery beacon
+const DISCOVERY_PORT: u16 = 38080;
+const DISCOVERY_SERVICE: &str = "gj7-attendance";
+// The admin app announces itself every 2 seconds
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(6);
+const PAIRED_SERVER_FILE: &str = "paired_server_id.txt";
+
+#[derive(Deserialize)]
+struct DiscoveryBeacon {
+    service: String,
+    server_id: String,
+    port: u16,
+    tls: bool,
+}
+
+// An admin server found on the local network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveredServer {
+    pub server_id: String,
+    pub address: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+fn paired_server_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(PAIRED_SERVER_FILE))
+        .map_err(|e| e.to_string())
+}
+
+fn load_paired_server_id(app: &AppHandle) -> Option<String> {
+    let contents = fs::read_to_string(paired_server_path(app).ok()?).ok()?;
+    Some(contents.trim().to_string()).filter(|server_id| !server_id.is_empty())
+}
+
+// Set when the kiosk pairs; None for servers that do not say who they are
+pub(crate) fn save_paired_server_id(app: &AppHandle, server_id: Option<&str>) -> Result<(), String> {
+    let path = paired_server_path(app)?;
+    match server_id {
+        Some(server_id) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, server_id).map_err(|e| e.to_string())
+        }
+        None if path.exists() => fs::remove_file(path).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// Every server heard until the timeout runs out, in the order they were first heard
+fn listen_for_servers() -> Result<Vec<DiscoveredServer>, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+        .map_err(|e| format!("Failed to listen on UDP port {}: {}", DISCOVERY_PORT, e))?;
+
+    let deadline = Instant::now() + DISCOVERY_TIMEOUT;
+    let mut heard: Vec<DiscoveredServer> = Vec::new();
+    let mut buffer = [0u8; 1024];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining)).map_err(|e| e.to_string())?;
+
+        let (length, sender) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let beacon = match serde_json::from_slice::<DiscoveryBeacon>(&buffer[..length]) {
+            Ok(beacon) if beacon.service == DISCOVERY_SERVICE => beacon,
+            _ => continue,
+        };
+        let server = DiscoveredServer {
+            server_id: beacon.server_id,
+            address: sender.ip().to_string(),
+            port: beacon.port,
+            tls: beacon.tls,
+        };
+
+        if !heard.contains(&server) {
+            heard.push(server);
+        }
+    }
+
+    Ok(heard)
+}
+
+// Beacons are not authenticated, so anyone on the network can claim any server id.
+// A kiosk that pinned the server's key only accepts a server over HTTPS that proves
+// it holds that key, so a forged beacon can neither move it nor turn HTTPS off.
+async fn can_trust(pinned_key: Option<&str>, server: &DiscoveredServer) -> bool {
+    match pinned_key {
+        Some(pinned_key) => {
+            let url = format!("https://{}:{}/occupancy", server.address, server.port);
+            server.tls && presents_key(&url, pinned_key).await
+        }
+        None => true,
+    }
+}
+
+// Finds the admin server on the local network. A paired kiosk only accepts the server
+// it paired with, unless `confirmed` says someone chose to look for another; even then
+// the new server must present the key pinned when the kiosk paired.
+#[tauri::command]
+pub async fn discover_server(app: AppHandle, confirmed: bool) -> Result<DiscoveredServer, String> {
+    let paired_id = load_paired_server_id(&app);
+    let pinned_key = load_pinned_key(&app);
+
+    let heard = tauri::async_runtime::spawn_blocking(listen_for_servers)
+        .await
+        .map_err(|e| e.to_string())??;
+    if heard.is_empty() {
+        return Err("No server found on the local network".to_string());
+    }
+
+    let candidates = match &paired_id {
+        None => heard,
+        Some(paired_id) => {
+            let (own, others): (Vec<_>, Vec<_>) = heard
+                .into_iter()
+                .partition(|server| server.server_id == *paired_id);
+
+            if !own.is_empty() {
+                own
+            } else if !confirmed {
+                return Err("The server this kiosk paired with was not found".to_string());
+            } else if pinned_key.is_none() {
+                return Err("Only other servers were found; pair this kiosk with one to switch".to_string());
+            } else {
+                others
+            }
+        }
+    };
+
+    for server in candidates {
+        if can_trust(pinned_key.as_deref(), &server).await {
+            // It holds the pinned key, so it is the same server under a new id, e.g.
+            // after the admin app's database was restored; follow it from now on
+            if paired_id.is_some() && paired_id.as_deref() != Some(server.server_id.as_str()) {
+                save_paired_server_id(&app, Some(&server.server_id))?;
+            }
+            return Ok(server);
+        }
+    }
+    Err("No server found that presents this kiosk's pinned key".to_string())
+}
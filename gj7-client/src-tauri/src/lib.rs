@@ -1,5 +1,8 @@
 // src/lib.rs
 
+mod discovery;
+mod server_client;
+
 use tauri::{Manager, Window};
 
 #[tauri::command]
@@ -30,6 +33,7 @@ async fn close_window(window: Window) -> Result<(), String> {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .manage(server_client::ServerSocket::default())
         .setup(|app| {
             let splashscreen = app.get_webview_window("splashscreen").unwrap();
             let main_window = app.get_webview_window("main").unwrap();
@@ -53,7 +57,14 @@ pub fn run() {
             close_splashscreen, 
             minimize_window, 
             maximize_window, 
-            close_window
+            close_window,
+            discovery::discover_server,
+            server_client::server_request,
+            server_client::pair_with_server,
+            server_client::get_pinned_server_key,
+            server_client::connect_server_socket,
+            server_client::send_server_socket,
+            server_client::close_server_socket
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
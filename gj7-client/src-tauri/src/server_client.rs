@@ -0,0 +1,403 @@
+// src/server_client.rs
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, SignatureScheme};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+
+use crate::discovery::save_paired_server_id;
+
+const PINNED_KEY_FILE: &str = "pinned_server_key.txt";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// SHA-256 of a DER public key as colon-separated hex, as the admin app shows it
+fn key_fingerprint(spki_der: &[u8]) -> String {
+    Sha256::digest(spki_der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+// The admin server's certificate is self-signed and reissued whenever its addresses
+// change, so it is trusted by its public key instead. Without a pin, as when pairing,
+// any key is accepted; either way the key presented is kept so it can be pinned.
+#[derive(Debug)]
+pub(crate) struct PinnedKeyVerifier {
+    pinned: Option<String>,
+    presented: Mutex<Option<String>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PinnedKeyVerifier {
+    pub(crate) fn new(pinned: Option<String>) -> Self {
+        PinnedKeyVerifier {
+            pinned,
+            presented: Mutex::new(None),
+            provider: Arc::new(ring::default_provider()),
+        }
+    }
+
+    pub(crate) fn presented(&self) -> Option<String> {
+        self.presented.lock().unwrap().clone()
+    }
+
+    // Names the keys involved when a connection failed because they differ
+    pub(crate) fn describe_failure(&self, error: impl std::fmt::Display) -> String {
+        match (&self.pinned, self.presented()) {
+            (Some(pinned), Some(presented)) if *pinned != presented => format!(
+                "The server presented key {} but this kiosk pinned {}. Pair the kiosk again if the server was replaced.",
+                presented, pinned
+            ),
+            _ => error.to_string(),
+        }
+    }
+
+    pub(crate) fn client_config(self: Arc<Self>) -> Result<ClientConfig, String> {
+        let config = ClientConfig::builder_with_provider(self.provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .dangerous()
+            .with_custom_certificate_verifier(self)
+            .with_no_client_auth();
+        Ok(config)
+    }
+}
+
+impl ServerCertVerifier for PinnedKeyVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let certificate = webpki::EndEntityCert::try_from(end_entity)
+            .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        let fingerprint = key_fingerprint(certificate.subject_public_key_info().as_ref());
+        *self.presented.lock().unwrap() = Some(fingerprint.clone());
+
+        match &self.pinned {
+            Some(pinned) if *pinned != fingerprint => Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure
+            )),
+            _ => Ok(ServerCertVerified::assertion()),
+        }
+    }
+
+    // The handshake signatures are still checked, so the server must hold the key
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn uses_tls(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("wss://")
+}
+
+fn pinned_key_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(PINNED_KEY_FILE))
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_pinned_key(app: &AppHandle) -> Option<String> {
+    let contents = fs::read_to_string(pinned_key_path(app).ok()?).ok()?;
+    Some(contents.trim().to_string()).filter(|key| !key.is_empty())
+}
+
+// None forgets the pin, for kiosks paired over plain HTTP
+fn save_pinned_key(app: &AppHandle, key: Option<&str>) -> Result<(), String> {
+    let path = pinned_key_path(app)?;
+    match key {
+        Some(key) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, key).map_err(|e| e.to_string())
+        }
+        None if path.exists() => fs::remove_file(path).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// HTTPS connections must present the key pinned at pairing; there is nothing to check over plain HTTP
+fn pinned_verifier(app: &AppHandle, url: &str) -> Result<Option<Arc<PinnedKeyVerifier>>, String> {
+    if !uses_tls(url) {
+        return Ok(None);
+    }
+    let pinned = load_pinned_key(app)
+        .ok_or_else(|| "No server key is pinned on this kiosk; pair it again".to_string())?;
+    Ok(Some(Arc::new(PinnedKeyVerifier::new(Some(pinned)))))
+}
+
+// A response from the admin server, passed to the webview as is
+#[derive(Debug, Serialize)]
+pub struct ServerResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+async fn send_request(
+    verifier: Option<Arc<PinnedKeyVerifier>>,
+    method: &str,
+    url: &str,
+    token: Option<&str>,
+    body: Option<Value>
+) -> Result<ServerResponse, String> {
+    let mut builder = reqwest::Client::builder().timeout(REQUEST_TIMEOUT);
+    if let Some(verifier) = &verifier {
+        builder = builder.use_preconfigured_tls(verifier.clone().client_config()?);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+
+    let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let mut request = client.request(method, url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+
+    let response = request.send().await.map_err(|e| match &verifier {
+        Some(verifier) => verifier.describe_failure(e),
+        None => e.to_string(),
+    })?;
+    let status = response.status().as_u16();
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+
+    Ok(ServerResponse { status, body })
+}
+
+// Whether the server at this HTTPS address holds the pinned key; any response will do
+pub(crate) async fn presents_key(url: &str, pinned_key: &str) -> bool {
+    let verifier = Arc::new(PinnedKeyVerifier::new(Some(pinned_key.to_string())));
+    send_request(Some(verifier), "GET", url, None, None).await.is_ok()
+}
+
+// Every request to the server but pairing goes through here
+#[tauri::command]
+pub async fn server_request(
+    app: AppHandle,
+    method: String,
+    url: String,
+    token: Option<String>,
+    body: Option<Value>
+) -> Result<ServerResponse, String> {
+    let verifier = pinned_verifier(&app, &url)?;
+    send_request(verifier, &method, &url, token.as_deref(), body).await
+}
+
+// Pairing is the one request made before the server's key is known. The key it
+// presents is pinned for every later connection, and shown so it can be compared
+// with the fingerprint in the admin app. Discovery then keeps to the server's id.
+#[tauri::command]
+pub async fn pair_with_server(
+    app: AppHandle,
+    url: String,
+    body: Value
+) -> Result<ServerResponse, String> {
+    let verifier = uses_tls(&url).then(|| Arc::new(PinnedKeyVerifier::new(None)));
+    let response = send_request(verifier.clone(), "POST", &url, None, Some(body)).await?;
+
+    if (200..300).contains(&response.status) {
+        let presented = verifier.and_then(|verifier| verifier.presented());
+        save_pinned_key(&app, presented.as_deref())?;
+        save_paired_server_id(&app, response.body.get("server_id").and_then(Value::as_str))?;
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub fn get_pinned_server_key(app: AppHandle) -> Option<String> {
+    load_pinned_key(&app)
+}
+
+// The kiosk's websocket to the server. The webview picks the id of each connection
+// and listens for its events before connecting, so no early message is missed and
+// a replaced connection's late events can be told apart.
+#[derive(Default)]
+pub struct ServerSocket {
+    sender: Mutex<Option<(String, mpsc::UnboundedSender<Message>)>>,
+}
+
+#[derive(Clone, Serialize)]
+struct SocketEvent {
+    id: String,
+    data: String,
+}
+
+#[tauri::command]
+pub async fn connect_server_socket(
+    app: AppHandle,
+    socket: State<'_, ServerSocket>,
+    id: String,
+    url: String
+) -> Result<(), String> {
+    let verifier = pinned_verifier(&app, &url)?;
+    let connector = match &verifier {
+        Some(verifier) => Connector::Rustls(Arc::new(verifier.clone().client_config()?)),
+        None => Connector::Plain,
+    };
+
+    let (stream, _) = tokio_tungstenite::connect_async_tls_with_config(url.as_str(), None, false, Some(connector))
+        .await
+        .map_err(|e| match &verifier {
+            Some(verifier) => verifier.describe_failure(e),
+            None => e.to_string(),
+        })?;
+
+    // Replacing the sender closes the previous connection
+    let (sender, mut outgoing) = mpsc::unbounded_channel();
+    *socket.sender.lock().unwrap() = Some((id.clone(), sender));
+
+    let (mut write, mut read) = stream.split();
+    tauri::async_runtime::spawn(async move {
+        let reason = loop {
+            tokio::select! {
+                message = outgoing.recv() => match message {
+                    Some(message) => {
+                        if let Err(e) = write.send(message).await {
+                            break e.to_string();
+                        }
+                    }
+                    None => {
+                        let _ = write.send(Message::Close(None)).await;
+                        break String::new();
+                    }
+                },
+                message = read.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let _ = app.emit("server-socket-message", SocketEvent { id: id.clone(), data: text });
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        break frame.map(|frame| frame.reason.to_string()).unwrap_or_default();
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => break e.to_string(),
+                    None => break String::new(),
+                },
+            }
+        };
+        let _ = app.emit("server-socket-closed", SocketEvent { id, data: reason });
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn send_server_socket(
+    socket: State<'_, ServerSocket>,
+    id: String,
+    message: String
+) -> Result<(), String> {
+    match &*socket.sender.lock().unwrap() {
+        Some((current, sender)) if *current == id => sender
+            .send(Message::Text(message))
+            .map_err(|_| "The connection to the server is closed".to_string()),
+        _ => Err("The connection to the server is closed".to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn close_server_socket(socket: State<'_, ServerSocket>, id: String) {
+    let mut sender = socket.sender.lock().unwrap();
+    if matches!(&*sender, Some((current, _)) if *current == id) {
+        *sender = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{Certificate, CertificateParams, KeyPair};
+
+    // A certificate like the admin app's, for `names`, and the fingerprint it shows
+    fn server_certificate(key_pair: Option<KeyPair>, names: &[&str]) -> (CertificateDer<'static>, String) {
+        let mut params = CertificateParams::new(names.iter().map(|name| name.to_string()).collect::<Vec<_>>());
+        params.key_pair = key_pair;
+        let certificate = Certificate::from_params(params).unwrap();
+        let fingerprint = key_fingerprint(&certificate.get_key_pair().public_key_der());
+        (CertificateDer::from(certificate.serialize_der().unwrap()), fingerprint)
+    }
+
+    fn verify(verifier: &PinnedKeyVerifier, certificate: &CertificateDer<'_>) -> Result<ServerCertVerified, rustls::Error> {
+        let server_name = ServerName::try_from("192.168.1.20").unwrap();
+        verifier.verify_server_cert(certificate, &[], &server_name, &[], UnixTime::now())
+    }
+
+    #[test]
+    fn accepts_the_pinned_key() {
+        let (certificate, fingerprint) = server_certificate(None, &["192.168.1.20"]);
+        let verifier = PinnedKeyVerifier::new(Some(fingerprint.clone()));
+
+        assert!(verify(&verifier, &certificate).is_ok());
+        assert_eq!(verifier.presented(), Some(fingerprint));
+    }
+
+    #[test]
+    fn accepts_a_reissued_certificate_for_the_pinned_key() {
+        let key_pem = KeyPair::generate(&rcgen::PKCS_ECDSA_P256_SHA256).unwrap().serialize_pem();
+        let (_, fingerprint) = server_certificate(Some(KeyPair::from_pem(&key_pem).unwrap()), &["192.168.1.20"]);
+        let (reissued, _) = server_certificate(Some(KeyPair::from_pem(&key_pem).unwrap()), &["10.0.0.5"]);
+
+        assert!(verify(&PinnedKeyVerifier::new(Some(fingerprint)), &reissued).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_different_key() {
+        let (_, pinned) = server_certificate(None, &["192.168.1.20"]);
+        let (certificate, presented) = server_certificate(None, &["192.168.1.20"]);
+        let verifier = PinnedKeyVerifier::new(Some(pinned.clone()));
+
+        assert_eq!(
+            verify(&verifier, &certificate).unwrap_err(),
+            rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure)
+        );
+        let failure = verifier.describe_failure("handshake failed");
+        assert!(failure.contains(&pinned) && failure.contains(&presented));
+    }
+
+    #[test]
+    fn unpinned_verifier_keeps_the_presented_key() {
+        let (certificate, fingerprint) = server_certificate(None, &["192.168.1.20"]);
+        let verifier = PinnedKeyVerifier::new(None);
+
+        assert!(verify(&verifier, &certificate).is_ok());
+        assert_eq!(verifier.presented(), Some(fingerprint));
+    }
+}
@@ -3,7 +3,6 @@
 import { useState, useEffect } from "react";
 import { Attendance, CreateAttendanceRequest } from "@/types/attendance";
 import { SchoolIdLookupResponse } from "@/types/school_accounts";
-import axios from "axios";
 import Step1SchoolID from "./steps/Step1SschoolID";
 import Step2ChoosePurpose from "./steps/Step2ChoosePurpose";
 import FooterGreetings from "./FooterGreetings";
@@ -13,6 +12,8 @@ import { useToast } from "@/hooks/use-toast";
 import { DURATIONS } from "./steps/config/durations";
 import { ServerConfigModal } from "./ServerConfigModal";
 import { useAttendanceWebSocket } from "@/utils/websocket";
+import { getDeviceToken, isUnauthorized, serverUrl } from "@/utils/kiosk_auth";
+import { serverGet } from "@/utils/server_client";
 
 const InputScanner = () => {
   const [currentStep, setCurrentStep] = useState(1);
@@ -39,12 +40,13 @@ const InputScanner = () => {
       console.log('Fetching details for school ID:', schoolId);
       
       // Use the serverIp state here
-      const response = await axios.get<SchoolIdLookupResponse>(
-        `http://${serverIp}:8080/school_id/${schoolId}`
+      const lookup = await serverGet<SchoolIdLookupResponse>(
+        serverUrl(serverIp, `/school_id/${schoolId}`),
+        getDeviceToken()
       );
   
-      if (response.data && response.data.full_name) {
-        setAccountInfo(response.data);
+      if (lookup && lookup.full_name) {
+        setAccountInfo(lookup);
         setCurrentStep(2);
       } else {
         console.error('Invalid response structure');
@@ -58,7 +60,9 @@ const InputScanner = () => {
       console.error('Error fetching student details:', error);
       toast({
         title: "Error",
-        description: "Failed to fetch student information",
+        description: isUnauthorized(error)
+          ? "This kiosk is not paired. Use Configure LAN to pair it."
+          : "Failed to fetch student information",
         variant: "destructive"
       });
     }
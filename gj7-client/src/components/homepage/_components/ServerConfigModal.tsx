@@ -12,7 +12,19 @@ import {
 import { Input } from '@/components/ui/input';
 import { Label } from "@/components/ui/label";
 import { useToast } from "@/hooks/use-toast";
-import { LockKeyhole } from 'lucide-react';
+import { LockKeyhole, Search } from 'lucide-react';
+import {
+  getDeviceLocation,
+  getDeviceName,
+  getDeviceToken,
+  getServerPort,
+  getServerUsesTls,
+  pairKiosk,
+  saveServerConnection,
+  setDeviceLocation
+} from '@/utils/kiosk_auth';
+import { discoverServer } from '@/utils/server_discovery';
+import { getPinnedServerKey } from '@/utils/server_client';
 
 const SERVER_IP_KEY = 'app_server_ip';
 
@@ -63,14 +75,96 @@ export const useServerConfig = () => {
 
 export const ServerConfigModal = () => {
   const [inputIp, setInputIp] = useState('');
+  const [pairingCode, setPairingCode] = useState('');
+  const [deviceName, setDeviceName] = useState(getDeviceName() ?? '');
+  const [location, setLocation] = useState(getDeviceLocation() ?? '');
+  const [port, setPort] = useState(String(getServerPort()));
+  const [useTls, setUseTls] = useState(getServerUsesTls());
   const { serverIp, saveServerIp } = useServerConfig();
   const [isOpen, setIsOpen] = useState(false);
+  const [isDiscovering, setIsDiscovering] = useState(false);
+  const [pinnedKey, setPinnedKey] = useState<string | null>(null);
+  const { toast } = useToast();
+
+  // Fills in the fields from the admin app's beacon; nothing is saved until Save.
+  // Pressing Find is what confirms a paired kiosk may look beyond its own server.
+  const handleDiscover = useCallback(async () => {
+    setIsDiscovering(true);
+    try {
+      const server = await discoverServer(true);
+      // A beacon cannot turn HTTPS off; that takes unticking it by hand
+      if (useTls && !server.tls) {
+        toast({
+          title: "Server Not Using HTTPS",
+          description: `${server.address}:${server.port} does not use HTTPS, so the settings were left as they are`,
+          variant: "destructive"
+        });
+        return;
+      }
+      setInputIp(server.address);
+      setPort(String(server.port));
+      setUseTls(server.tls);
+      toast({
+        title: "Server Found",
+        description: `${server.address}:${server.port}`,
+        variant: "default"
+      });
+    } catch (error) {
+      console.error('Failed to discover server:', error);
+      toast({
+        title: "No Server Found",
+        description: typeof error === 'string' ? error : "Make sure the admin app is running on the same network",
+        variant: "destructive"
+      });
+    } finally {
+      setIsDiscovering(false);
+    }
+  }, [useTls, toast]);
   
-  const handleSave = useCallback(() => {
-    if (saveServerIp(inputIp)) {
-      setIsOpen(false);
+  // A pairing code is only needed the first time, or after the kiosk was revoked
+  const handleSave = useCallback(async () => {
+    // Must match the admin app's Network Server settings
+    const portNumber = Number(port);
+    if (!Number.isInteger(portNumber) || portNumber < 1 || portNumber > 65535) {
+      toast({
+        title: "Invalid Port",
+        description: "Please enter a port between 1 and 65535",
+        variant: "destructive"
+      });
+      return;
     }
-  }, [inputIp, saveServerIp]);
+    if (!saveServerIp(inputIp)) {
+      return;
+    }
+    saveServerConnection(portNumber, useTls);
+
+    // Reported to the admin app with the next heartbeat
+    setDeviceLocation(location);
+
+    if (pairingCode.trim()) {
+      try {
+        await pairKiosk(inputIp.trim(), pairingCode.trim(), deviceName.trim());
+        setPairingCode('');
+        toast({
+          title: "Kiosk Paired",
+          description: "Reconnecting to the server...",
+          variant: "default"
+        });
+        // The websocket only picks up the new token when it connects
+        setTimeout(() => window.location.reload(), 1500);
+      } catch (error) {
+        console.error('Failed to pair kiosk:', error);
+        toast({
+          title: "Pairing Failed",
+          description: "Check the pairing code in the admin app and try again",
+          variant: "destructive"
+        });
+        return;
+      }
+    }
+
+    setIsOpen(false);
+  }, [inputIp, port, useTls, pairingCode, deviceName, location, saveServerIp, toast]);
 
   const handleKeyDown = useCallback((e: React.KeyboardEvent) => {
     if (e.key === 'Enter') {
@@ -82,6 +176,9 @@ export const ServerConfigModal = () => {
 
   useEffect(() => {
     setInputIp(serverIp);
+    setPort(String(getServerPort()));
+    setUseTls(getServerUsesTls());
+    getPinnedServerKey().then(setPinnedKey).catch(() => setPinnedKey(null));
   }, [serverIp, isOpen]);
 
   return (
@@ -104,14 +201,92 @@ export const ServerConfigModal = () => {
             <Input
               id="serverIp"
               placeholder="e.g., localhost or 192.168.1.100"
-              className="col-span-3"
+              className="col-span-2"
               value={inputIp}
               onChange={(e) => setInputIp(e.target.value)}
             />
+            <Button type="button" variant="outline" onClick={handleDiscover} disabled={isDiscovering}>
+              <Search />{isDiscovering ? 'Searching...' : 'Find'}
+            </Button>
+          </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="serverPort" className="text-right">
+              Port
+            </Label>
+            <Input
+              id="serverPort"
+              type="number"
+              min={1}
+              max={65535}
+              className="col-span-3"
+              value={port}
+              onChange={(e) => setPort(e.target.value)}
+            />
+          </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="useTls" className="text-right">
+              HTTPS
+            </Label>
+            <div className="col-span-3 flex items-center space-x-2">
+              <input
+                id="useTls"
+                type="checkbox"
+                checked={useTls}
+                onChange={(e) => setUseTls(e.target.checked)}
+              />
+              <span className="text-sm text-muted-foreground">
+                The server's key is pinned when this kiosk pairs; check it against the fingerprint in the admin app
+              </span>
+            </div>
+          </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="deviceName" className="text-right">
+              Kiosk Name
+            </Label>
+            <Input
+              id="deviceName"
+              placeholder="e.g., Main entrance"
+              className="col-span-3"
+              value={deviceName}
+              onChange={(e) => setDeviceName(e.target.value)}
+            />
+          </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="location" className="text-right">
+              Location
+            </Label>
+            <Input
+              id="location"
+              placeholder="e.g., Second floor reading area"
+              maxLength={64}
+              className="col-span-3"
+              value={location}
+              onChange={(e) => setLocation(e.target.value)}
+            />
+          </div>
+          <div className="grid grid-cols-4 items-center gap-4">
+            <Label htmlFor="pairingCode" className="text-right">
+              Pairing Code
+            </Label>
+            <Input
+              id="pairingCode"
+              placeholder="From the admin app's Kiosks settings"
+              className="col-span-3"
+              value={pairingCode}
+              onChange={(e) => setPairingCode(e.target.value)}
+            />
+          </div>
+          <div className="text-sm text-muted-foreground">
+            Current server: {serverIp}:{getServerPort()}{getServerUsesTls() ? ' (HTTPS)' : ''}
           </div>
           <div className="text-sm text-muted-foreground">
-            Current saved IP: {serverIp}
+            {getDeviceToken() ? 'This kiosk is paired' : 'This kiosk is not paired yet'}
           </div>
+          {pinnedKey && (
+            <div className="text-sm text-muted-foreground">
+              Pinned server key (SHA-256): <span className="font-mono break-all">{pinnedKey}</span>
+            </div>
+          )}
         </div>
         <div className="flex justify-end">
           <DialogClose asChild>
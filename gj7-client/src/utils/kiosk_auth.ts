@@ -0,0 +1,93 @@
+// utils/kiosk_auth.ts
+
+import { getVersion } from '@tauri-apps/api/app';
+import { pairWithServer, ServerError } from './server_client';
+
+const DEVICE_TOKEN_KEY = 'kiosk_device_token';
+const DEVICE_NAME_KEY = 'kiosk_device_name';
+const DEVICE_LOCATION_KEY = 'kiosk_device_location';
+const SERVER_PORT_KEY = 'app_server_port';
+const SERVER_TLS_KEY = 'app_server_tls';
+
+// Matches the admin app's default network server settings
+export const DEFAULT_SERVER_PORT = 8080;
+
+// The server marks a kiosk offline after missing about two heartbeats
+export const HEARTBEAT_INTERVAL_MS = 30000;
+
+interface PairedDevice {
+    device: {
+        id: number;
+        name: string;
+    };
+    token: string;
+}
+
+export const getDeviceToken = (): string | null => localStorage.getItem(DEVICE_TOKEN_KEY);
+
+export const getDeviceName = (): string | null => localStorage.getItem(DEVICE_NAME_KEY);
+
+export const getDeviceLocation = (): string | null => localStorage.getItem(DEVICE_LOCATION_KEY);
+
+export const setDeviceLocation = (location: string) => {
+    if (location.trim()) {
+        localStorage.setItem(DEVICE_LOCATION_KEY, location.trim());
+    } else {
+        localStorage.removeItem(DEVICE_LOCATION_KEY);
+    }
+};
+
+// Sent over the websocket so the admin app can show this kiosk as online
+export const heartbeatMessage = async (): Promise<string> => {
+    const appVersion = await getVersion().catch(() => null);
+    return JSON.stringify({
+        type: 'Heartbeat',
+        data: {
+            app_version: appVersion,
+            location: getDeviceLocation(),
+        },
+    });
+};
+
+export const getServerPort = (): number =>
+    Number(localStorage.getItem(SERVER_PORT_KEY)) || DEFAULT_SERVER_PORT;
+
+// HTTPS/WSS, when turned on in the admin app's network server settings
+export const getServerUsesTls = (): boolean => localStorage.getItem(SERVER_TLS_KEY) === 'true';
+
+export const saveServerConnection = (port: number, useTls: boolean) => {
+    localStorage.setItem(SERVER_PORT_KEY, String(port));
+    localStorage.setItem(SERVER_TLS_KEY, String(useTls));
+};
+
+export const serverUrl = (serverIp: string, path: string): string =>
+    `${getServerUsesTls() ? 'https' : 'http'}://${serverIp}:${getServerPort()}${path}`;
+
+// Called when the server no longer accepts the token, e.g. after it was revoked
+export const clearDeviceToken = () => {
+    localStorage.removeItem(DEVICE_TOKEN_KEY);
+};
+
+// Browsers cannot send headers with a websocket handshake, so the token goes in the query
+export const websocketUrl = (serverIp: string): string | null => {
+    const token = getDeviceToken();
+    if (!token) {
+        return null;
+    }
+    const scheme = getServerUsesTls() ? 'wss' : 'ws';
+    return `${scheme}://${serverIp}:${getServerPort()}/ws?token=${encodeURIComponent(token)}`;
+};
+
+// Exchanges the one-time code shown in the admin app for this kiosk's token
+export const pairKiosk = async (serverIp: string, code: string, deviceName: string): Promise<void> => {
+    const paired = await pairWithServer<PairedDevice>(serverUrl(serverIp, '/pair'), {
+        code,
+        device_name: deviceName,
+    });
+
+    localStorage.setItem(DEVICE_TOKEN_KEY, paired.token);
+    localStorage.setItem(DEVICE_NAME_KEY, paired.device.name);
+};
+
+export const isUnauthorized = (error: unknown): boolean =>
+    error instanceof ServerError && error.status === 401;
@@ -0,0 +1,114 @@
+// utils/server_client.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+// Requests and the websocket go through the Rust side, which checks that an HTTPS
+// server presents the key pinned when this kiosk paired
+
+interface ServerResponse<T> {
+    status: number;
+    body: T;
+}
+
+export class ServerError extends Error {
+    constructor(readonly status: number, readonly body: unknown) {
+        super(`Server responded with ${status}`);
+    }
+}
+
+const checked = <T>(response: ServerResponse<T>): T => {
+    if (response.status < 200 || response.status >= 300) {
+        throw new ServerError(response.status, response.body);
+    }
+    return response.body;
+};
+
+export const serverGet = async <T>(url: string, token: string | null): Promise<T> =>
+    checked(await invoke<ServerResponse<T>>('server_request', { method: 'GET', url, token }));
+
+// Pins the key the server presents, when it uses HTTPS
+export const pairWithServer = async <T>(url: string, body: object): Promise<T> =>
+    checked(await invoke<ServerResponse<T>>('pair_with_server', { url, body }));
+
+// SHA-256 of the server key, to compare with the fingerprint in the admin app
+export const getPinnedServerKey = (): Promise<string | null> =>
+    invoke<string | null>('get_pinned_server_key');
+
+interface SocketEvent {
+    id: string;
+    data: string;
+}
+
+// The parts of WebSocket the kiosk uses, over a connection opened on the Rust side
+export class ServerSocket {
+    readonly id = crypto.randomUUID();
+    readyState: number = WebSocket.CONNECTING;
+    onopen: (() => void) | null = null;
+    onmessage: ((event: { data: string }) => void) | null = null;
+    onerror: ((error: unknown) => void) | null = null;
+    onclose: ((event: { reason: string }) => void) | null = null;
+    private unlisten: UnlistenFn[] = [];
+
+    constructor(url: string) {
+        this.connect(url);
+    }
+
+    private async connect(url: string) {
+        // Listen first; the server sends its current state as soon as the kiosk connects
+        this.unlisten = await Promise.all([
+            listen<SocketEvent>('server-socket-message', ({ payload }) => {
+                if (payload.id === this.id) {
+                    this.onmessage?.({ data: payload.data });
+                }
+            }),
+            listen<SocketEvent>('server-socket-closed', ({ payload }) => {
+                if (payload.id === this.id) {
+                    this.closed(payload.data);
+                }
+            }),
+        ]);
+        if (this.readyState === WebSocket.CLOSED) {
+            this.unlisten.forEach((unlisten) => unlisten());
+            return;
+        }
+
+        try {
+            await invoke('connect_server_socket', { id: this.id, url });
+        } catch (error) {
+            this.onerror?.(error);
+            this.closed(String(error));
+            return;
+        }
+
+        if (this.readyState === WebSocket.CONNECTING) {
+            this.readyState = WebSocket.OPEN;
+            this.onopen?.();
+        } else {
+            // Closed while connecting
+            invoke('close_server_socket', { id: this.id });
+        }
+    }
+
+    send(message: string) {
+        invoke('send_server_socket', { id: this.id, message }).catch((error) => this.onerror?.(error));
+    }
+
+    close() {
+        if (this.readyState === WebSocket.CONNECTING) {
+            this.closed('');
+        } else if (this.readyState === WebSocket.OPEN) {
+            this.readyState = WebSocket.CLOSING;
+            invoke('close_server_socket', { id: this.id });
+        }
+    }
+
+    private closed(reason: string) {
+        if (this.readyState === WebSocket.CLOSED) {
+            return;
+        }
+        this.readyState = WebSocket.CLOSED;
+        this.unlisten.forEach((unlisten) => unlisten());
+        this.onclose?.({ reason });
+    }
+}
@@ -0,0 +1,44 @@
+// utils/server_discovery.ts
+
+import { invoke } from '@tauri-apps/api/core';
+import { getServerPort, getServerUsesTls, saveServerConnection } from './kiosk_auth';
+
+const SERVER_IP_KEY = 'app_server_ip';
+
+// An admin server announcing itself on the local network
+export interface DiscoveredServer {
+    server_id: string;
+    address: string;
+    port: number;
+    tls: boolean;
+}
+
+// Listens for the admin app's beacon for a few seconds; rejects if none is heard.
+// A paired kiosk only accepts another server than its own once someone `confirmed` it.
+export const discoverServer = (confirmed: boolean): Promise<DiscoveredServer> =>
+    invoke<DiscoveredServer>('discover_server', { confirmed });
+
+// Points the kiosk at the server; returns whether its address or port changed.
+// Beacons are not authenticated, so the scheme is never taken from one.
+export const applyDiscoveredServer = (server: DiscoveredServer): boolean => {
+    if (server.tls !== getServerUsesTls()) {
+        throw new Error(`The server found ${server.tls ? 'uses' : 'does not use'} HTTPS, unlike this kiosk's settings`);
+    }
+
+    const changed = localStorage.getItem(SERVER_IP_KEY) !== server.address
+        || getServerPort() !== server.port;
+
+    localStorage.setItem(SERVER_IP_KEY, server.address);
+    saveServerConnection(server.port, getServerUsesTls());
+    return changed;
+};
+
+// For when the server stops answering, e.g. after DHCP gave the admin PC a new address
+export const rediscoverServer = async (): Promise<boolean> => {
+    try {
+        return applyDiscoveredServer(await discoverServer(false));
+    } catch (error) {
+        console.warn('Server discovery failed:', error);
+        return false;
+    }
+};
@@ -2,6 +2,9 @@
 
 import { Attendance, CreateAttendanceRequest } from '@/types/attendance';
 import { useState, useEffect, useCallback } from 'react';
+import { clearDeviceToken, heartbeatMessage, HEARTBEAT_INTERVAL_MS, websocketUrl } from './kiosk_auth';
+import { rediscoverServer } from './server_discovery';
+import { ServerSocket } from './server_client';
 
 export enum AttendanceEventType {
     NewAttendance = 'NewAttendance',
@@ -52,7 +55,7 @@ const createExponentialBackoff = (
 };
 
 export const useAttendanceWebSocket = () => {
-    const [socket, setSocket] = useState<WebSocket | null>(null);
+    const [socket, setSocket] = useState<ServerSocket | null>(null);
     const [attendances, setAttendances] = useState<Attendance[]>([]);
     const [isConnected, setIsConnected] = useState(false);
     const [connectionError, setConnectionError] = useState<string | null>(null);
@@ -67,18 +70,37 @@ export const useAttendanceWebSocket = () => {
         }
 
         const savedIp = localStorage.getItem('app_server_ip') || 'localhost';
-        const wsUrl = `ws://${savedIp}:8080/ws`;
+        const wsUrl = websocketUrl(savedIp);
+        if (!wsUrl) {
+            setIsConnected(false);
+            setConnectionError('This kiosk is not paired with the server');
+            return null;
+        }
 
         try {
-            const newSocket = new WebSocket(wsUrl);
+            const newSocket = new ServerSocket(wsUrl);
+            let heartbeatTimer: ReturnType<typeof setInterval> | null = null;
+            let opened = false;
+
+            const sendHeartbeat = async () => {
+                const message = await heartbeatMessage();
+                if (newSocket.readyState === WebSocket.OPEN) {
+                    newSocket.send(message);
+                }
+            };
 
             // Connection opened successfully
             newSocket.onopen = () => {
                 console.log('WebSocket Connected successfully');
+                opened = true;
                 setIsConnected(true);
                 setConnectionError(null);
                 // Reset retry count on successful connection
                 getReconnectInterval(); // Reset
+
+                // Keep the admin app's kiosk status up to date
+                sendHeartbeat();
+                heartbeatTimer = setInterval(sendHeartbeat, HEARTBEAT_INTERVAL_MS);
             };
 
             // Handle incoming messages
@@ -93,6 +115,12 @@ export const useAttendanceWebSocket = () => {
                         setAttendances(processedAttendances);
                     } 
                     
+                    // The admin app revoked this kiosk; it has to be paired again
+                    if (data.Error === 'DeviceRevoked') {
+                        clearDeviceToken();
+                        setConnectionError('This kiosk was revoked and must be paired again');
+                    }
+
                     if (data.NewAttendance) {
                         const processedAttendance = processAttendance(data.NewAttendance);
                         setAttendances(prev => {
@@ -115,9 +143,20 @@ export const useAttendanceWebSocket = () => {
             };
 
             // Connection closed
-            newSocket.onclose = (event) => {
+            newSocket.onclose = async (event) => {
                 console.log('WebSocket Disconnected', event);
                 setIsConnected(false);
+                if (heartbeatTimer) {
+                    clearInterval(heartbeatTimer);
+                }
+
+                // The server may have moved, e.g. after DHCP gave the admin PC a new address.
+                // Reload so everything picks up the new address, as after pairing.
+                if (!opened && await rediscoverServer()) {
+                    console.log('Server found at a new address, reloading');
+                    window.location.reload();
+                    return;
+                }
 
                 // Attempt reconnection with exponential backoff
                 const reconnectInterval = getReconnectInterval();